@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+
+use crate::analytics::parse_amount;
+use crate::labels::{LabelKind, LabelRegistry};
+use crate::models::{Transfer, TransferKind};
+
+use super::{Alert, AlertCondition, AlertRule};
+
+const DEFAULT_THRESHOLD_PCT: f64 = 1.0;
+const DEFAULT_TOP_N: u32 = 10;
+
+/// Token state the rules are evaluated against
+pub struct TokenState<'a> {
+    /// On-chain total supply in raw units
+    pub supply: u128,
+    /// Holders ordered largest first
+    pub holders: &'a [(String, u128)],
+}
+
+/// Evaluate one rule against newly indexed transfers, returning the alerts it fires.
+/// Alert ids are assigned when they are recorded.
+pub fn evaluate(
+    rule: &AlertRule,
+    transfers: &[Transfer],
+    state: &TokenState<'_>,
+    labels: &LabelRegistry,
+) -> Vec<Alert> {
+    let threshold_pct = rule.threshold_pct.unwrap_or(DEFAULT_THRESHOLD_PCT);
+    let pct_of_supply = |amount: u128| {
+        if state.supply == 0 {
+            0.0
+        } else {
+            amount as f64 / state.supply as f64 * 100.0
+        }
+    };
+
+    let top_holders: HashSet<&str> = match rule.condition {
+        AlertCondition::TopHolderToCex => state
+            .holders
+            .iter()
+            .take(rule.top_n.unwrap_or(DEFAULT_TOP_N) as usize)
+            .map(|(address, _)| address.as_str())
+            .collect(),
+        _ => HashSet::new(),
+    };
+
+    transfers
+        .iter()
+        .filter_map(|transfer| {
+            let amount = parse_amount(&transfer.amount);
+            let message = match rule.condition {
+                AlertCondition::LargeTransfer if pct_of_supply(amount) > threshold_pct => format!(
+                    "Transfer of {:.2}% of supply from {} to {}",
+                    pct_of_supply(amount),
+                    transfer.from,
+                    transfer.to
+                ),
+                AlertCondition::TopHolderToCex
+                    if top_holders.contains(transfer.from.as_str())
+                        && labels.has_kind(&transfer.to, Some(rule.chain), LabelKind::CexHotWallet) =>
+                {
+                    format!("Top holder {} sent {} to exchange wallet {}", transfer.from, transfer.amount, transfer.to)
+                }
+                // LP supply includes the burned amount, so compare against supply before the burn
+                AlertCondition::LiquidityRemoved
+                    if transfer.kind == TransferKind::Burn
+                        && amount as f64 / (state.supply as f64 + amount as f64).max(1.0) * 100.0 > threshold_pct =>
+                {
+                    format!("{} LP tokens burned, liquidity removed from {}", transfer.amount, rule.token)
+                }
+                _ => return None,
+            };
+
+            Some(Alert {
+                id: 0,
+                rule_id: rule.id,
+                rule_name: rule.name.clone(),
+                chain: rule.chain,
+                token: rule.token.clone(),
+                tx_hash: transfer.tx_hash.clone(),
+                from: transfer.from.clone(),
+                to: transfer.to.clone(),
+                amount: transfer.amount.clone(),
+                message,
+                triggered_at: transfer.timestamp,
+            })
+        })
+        .collect()
+}
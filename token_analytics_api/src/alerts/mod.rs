@@ -0,0 +1,134 @@
+//! Rule-based alerts on new transfers of tracked tokens
+
+pub mod engine;
+pub mod watcher;
+pub mod webhooks;
+
+use anyhow::Result;
+use async_graphql::{Enum, InputObject, SimpleObject};
+use serde::{Deserialize, Serialize};
+
+use crate::analytics::normalize_address;
+use crate::cache::{counter_incr, hash_delete, hash_get, hash_set, hash_values, list_push_capped, list_range, RedisPool};
+use crate::models::Chain;
+
+const RULES_KEY: &str = "alerts:rules";
+const RULE_ID_KEY: &str = "alerts:rules:next_id";
+const ALERTS_KEY: &str = "alerts:fired";
+const ALERT_ID_KEY: &str = "alerts:fired:next_id";
+/// Fired alerts kept in Redis
+const MAX_ALERTS: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+pub enum AlertCondition {
+    /// A single transfer moves more than `threshold_pct` of total supply
+    LargeTransfer,
+    /// One of the `top_n` largest holders sends to an address labeled as a CEX hot wallet
+    TopHolderToCex,
+    /// LP tokens are burned (liquidity withdrawn) above `threshold_pct` of LP supply.
+    /// Set the rule's token to the pool address.
+    LiquidityRemoved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct AlertRule {
+    pub id: u64,
+    pub name: String,
+    pub chain: Chain,
+    pub token: String,
+    pub condition: AlertCondition,
+    /// Percentage of supply, used by `LARGE_TRANSFER` and `LIQUIDITY_REMOVED`
+    pub threshold_pct: Option<f64>,
+    /// Holder rank cut-off, used by `TOP_HOLDER_TO_CEX`
+    pub top_n: Option<u32>,
+    pub enabled: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct AlertRuleInput {
+    pub name: String,
+    pub chain: Chain,
+    pub token: String,
+    pub condition: AlertCondition,
+    pub threshold_pct: Option<f64>,
+    pub top_n: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Alert {
+    pub id: u64,
+    pub rule_id: u64,
+    pub rule_name: String,
+    pub chain: Chain,
+    pub token: String,
+    pub tx_hash: String,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub message: String,
+    pub triggered_at: i64,
+}
+
+/// Store a new enabled rule; its token is kept in normalized form so it
+/// matches the indexed transfers
+pub async fn create_rule(redis: &RedisPool, input: AlertRuleInput) -> Result<AlertRule> {
+    let rule = AlertRule {
+        id: counter_incr(redis, RULE_ID_KEY).await?,
+        name: input.name,
+        chain: input.chain,
+        token: normalize_address(input.chain, &input.token),
+        condition: input.condition,
+        threshold_pct: input.threshold_pct,
+        top_n: input.top_n,
+        enabled: true,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    save_rule(redis, &rule).await?;
+    Ok(rule)
+}
+
+pub async fn save_rule(redis: &RedisPool, rule: &AlertRule) -> Result<()> {
+    hash_set(redis, RULES_KEY, &rule.id.to_string(), &serde_json::to_string(rule)?).await
+}
+
+pub async fn get_rule(redis: &RedisPool, id: u64) -> Result<Option<AlertRule>> {
+    match hash_get(redis, RULES_KEY, &id.to_string()).await? {
+        Some(rule) => Ok(Some(serde_json::from_str(&rule)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn delete_rule(redis: &RedisPool, id: u64) -> Result<bool> {
+    hash_delete(redis, RULES_KEY, &id.to_string()).await
+}
+
+pub async fn list_rules(redis: &RedisPool) -> Result<Vec<AlertRule>> {
+    let mut rules = hash_values(redis, RULES_KEY)
+        .await?
+        .iter()
+        .map(|r| serde_json::from_str::<AlertRule>(r))
+        .collect::<Result<Vec<_>, _>>()?;
+    rules.sort_by_key(|r| r.id);
+    Ok(rules)
+}
+
+/// Assign an id to a fired alert and record it
+pub async fn record_alert(redis: &RedisPool, mut alert: Alert) -> Result<Alert> {
+    alert.id = counter_incr(redis, ALERT_ID_KEY).await?;
+    list_push_capped(redis, ALERTS_KEY, &serde_json::to_string(&alert)?, MAX_ALERTS).await?;
+    Ok(alert)
+}
+
+/// Most recent alerts first
+pub async fn recent_alerts(redis: &RedisPool, limit: usize) -> Result<Vec<Alert>> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+
+    let alerts = list_range(redis, ALERTS_KEY, 0, limit as isize - 1).await?;
+    Ok(alerts
+        .iter()
+        .map(|a| serde_json::from_str::<Alert>(a))
+        .collect::<Result<Vec<_>, _>>()?)
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_graphql::dataloader::Loader;
+use tracing::{debug, error, info, warn};
+
+use crate::analytics::{holders::holder_balances, parse_amount};
+use crate::cache::{cache_get, cache_set, RedisPool};
+use crate::config::Config;
+use crate::graphql::loaders::{SupplyKey, TokenLoader};
+use crate::indexer;
+use crate::labels::LabelRegistry;
+use crate::models::{Chain, Transfer};
+
+use super::engine::{evaluate, TokenState};
+use super::webhooks::WebhookDispatcher;
+use super::{list_rules, record_alert, AlertCondition, AlertRule};
+
+/// Last evaluated transfer per token, as `timestamp:block:log_index`
+fn cursor_key(chain: Chain, token: &str) -> String {
+    format!("alerts:cursor:{}:{}", chain, token)
+}
+
+fn position(transfer: &Transfer) -> (u64, u64) {
+    (transfer.block_number, transfer.log_index)
+}
+
+fn format_cursor(transfer: &Transfer) -> String {
+    format!("{}:{}:{}", transfer.timestamp, transfer.block_number, transfer.log_index)
+}
+
+/// Timestamp and position of the last evaluated transfer. Cursors in an
+/// older format read as missing, so the token restarts from the head.
+fn parse_cursor(cursor: &str) -> Option<(i64, (u64, u64))> {
+    let mut parts = cursor.split(':');
+    let timestamp = parts.next()?.parse().ok()?;
+    let block = parts.next()?.parse().ok()?;
+    let index = parts.next()?.parse().ok()?;
+    parts.next().is_none().then_some((timestamp, (block, index)))
+}
+
+/// Raw on-chain total supply, through the token loader's cache
+async fn onchain_supply(redis: &RedisPool, config: &Config, chain: Chain, token: &str) -> Result<u128> {
+    let key = SupplyKey(chain, token.to_string());
+    let supplies = TokenLoader::new(redis.clone(), config.clone())
+        .load(std::slice::from_ref(&key))
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+    Ok(supplies.get(&key).map(|s| parse_amount(s)).unwrap_or_default())
+}
+
+async fn check_token(
+    redis: &RedisPool,
+    config: &Config,
+    labels: &LabelRegistry,
+    webhooks: &WebhookDispatcher,
+    chain: Chain,
+    token: &str,
+    rules: &[&AlertRule],
+) -> Result<usize> {
+    indexer::sync_transfers(redis, config, chain, token).await?;
+
+    // Without a cursor this is the first pass for the token, so start from
+    // the head instead of alerting on its whole history
+    let cursor = cursor_key(chain, token);
+    let Some((since, last)) = cache_get(redis, &cursor).await?.as_deref().and_then(parse_cursor) else {
+        let transfers = indexer::load_transfers(redis, chain, token, i64::MIN, i64::MAX).await?;
+        if let Some(latest) = transfers.last() {
+            cache_set(redis, &cursor, &format_cursor(latest), 0).await?;
+        }
+        return Ok(0);
+    };
+
+    let new_transfers: Vec<Transfer> = indexer::load_transfers(redis, chain, token, since, i64::MAX)
+        .await?
+        .into_iter()
+        .filter(|t| position(t) > last)
+        .collect();
+    let Some(latest) = new_transfers.last().map(format_cursor) else {
+        return Ok(0);
+    };
+
+    // Holder balances are replayed from the whole history, so rules that
+    // rank holders are skipped until the backfill completes
+    let mut rules: Vec<&AlertRule> = rules.to_vec();
+    let mut holders = Vec::new();
+    if rules.iter().any(|r| r.condition == AlertCondition::TopHolderToCex) {
+        match indexer::load_complete_transfers(redis, chain, token).await {
+            Ok(transfers) => holders = holder_balances(&transfers),
+            Err(e) => {
+                debug!("Skipping top holder rules for {} on {}: {}", token, chain, e);
+                rules.retain(|r| r.condition != AlertCondition::TopHolderToCex);
+            }
+        }
+    }
+    let state = TokenState {
+        supply: onchain_supply(redis, config, chain, token).await?,
+        holders: &holders,
+    };
+
+    let mut fired = 0;
+    for rule in rules {
+        for alert in evaluate(rule, &new_transfers, &state, labels) {
+            let alert = record_alert(redis, alert).await?;
+            info!("🚨 Alert {} ({}): {}", alert.id, alert.rule_name, alert.message);
+            if let Err(e) = webhooks.dispatch(&alert).await {
+                warn!("Failed to dispatch webhooks for alert {}: {}", alert.id, e);
+            }
+            fired += 1;
+        }
+    }
+
+    cache_set(redis, &cursor, &latest, 0).await?;
+    Ok(fired)
+}
+
+/// Evaluate enabled rules against new transfers every `ALERT_POLL_SECONDS`
+/// and push fired alerts to webhooks
+pub async fn run(redis: RedisPool, config: Config, labels: LabelRegistry, webhooks: WebhookDispatcher) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.alert_poll_seconds.max(1)));
+    loop {
+        interval.tick().await;
+
+        let rules = match list_rules(&redis).await {
+            Ok(rules) => rules,
+            Err(e) => {
+                error!("Failed to load alert rules: {}", e);
+                continue;
+            }
+        };
+
+        let mut by_token: HashMap<(Chain, String), Vec<&AlertRule>> = HashMap::new();
+        for rule in rules.iter().filter(|r| r.enabled) {
+            by_token.entry((rule.chain, rule.token.clone())).or_default().push(rule);
+        }
+
+        for ((chain, token), rules) in by_token {
+            if let Err(e) = check_token(&redis, &config, &labels, &webhooks, chain, &token, &rules).await {
+                error!("Failed to evaluate alerts for {} on {}: {}", token, chain, e);
+            }
+        }
+    }
+}
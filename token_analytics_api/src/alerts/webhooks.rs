@@ -0,0 +1,429 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_graphql::{Enum, SimpleObject};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tracing::{info, warn};
+
+use crate::cache::{
+    counter_incr, hash_delete, hash_get, hash_set, hash_values, list_push_capped, list_range, list_remove,
+    RedisPool,
+};
+use crate::config::Config;
+
+use super::Alert;
+
+const ENDPOINTS_KEY: &str = "webhooks:endpoints";
+const ENDPOINT_ID_KEY: &str = "webhooks:endpoints:next_id";
+const DELIVERIES_KEY: &str = "webhooks:deliveries";
+const DELIVERY_ID_KEY: &str = "webhooks:deliveries:next_id";
+const DEAD_LETTER_KEY: &str = "webhooks:dead_letter";
+/// Delivery log entries kept in Redis
+const MAX_DELIVERIES: usize = 5_000;
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct WebhookEndpoint {
+    pub id: u64,
+    pub url: String,
+    pub description: Option<String>,
+    /// HMAC-SHA256 key; never returned over GraphQL
+    #[graphql(skip)]
+    pub secret: String,
+    pub enabled: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Delivered,
+    /// All attempts failed; the payload was moved to the dead-letter list
+    DeadLettered,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct WebhookDelivery {
+    pub id: u64,
+    pub endpoint_id: u64,
+    pub alert_id: u64,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if a response was received
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub completed_at: i64,
+}
+
+/// A delivery that exhausted its retries, kept with its payload for redelivery
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct DeadLetter {
+    pub delivery: WebhookDelivery,
+    pub payload: String,
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    /// Lets Slack incoming webhooks render the alert without a custom app
+    text: &'a str,
+    alert: &'a Alert,
+}
+
+/// Hex HMAC-SHA256 over `"{timestamp}.{body}"`, sent as `sha256=<hex>`.
+/// Receivers recompute it with the shared secret and reject stale timestamps.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+pub async fn create_endpoint(
+    redis: &RedisPool,
+    url: String,
+    secret: String,
+    description: Option<String>,
+) -> Result<WebhookEndpoint> {
+    reqwest::Url::parse(&url).map_err(|e| anyhow!("Invalid webhook URL: {}", e))?;
+    if secret.len() < 16 {
+        return Err(anyhow!("Webhook secret must be at least 16 characters"));
+    }
+
+    let endpoint = WebhookEndpoint {
+        id: counter_incr(redis, ENDPOINT_ID_KEY).await?,
+        url,
+        description,
+        secret,
+        enabled: true,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    hash_set(redis, ENDPOINTS_KEY, &endpoint.id.to_string(), &serde_json::to_string(&endpoint)?).await?;
+    Ok(endpoint)
+}
+
+pub async fn delete_endpoint(redis: &RedisPool, id: u64) -> Result<bool> {
+    hash_delete(redis, ENDPOINTS_KEY, &id.to_string()).await
+}
+
+pub async fn get_endpoint(redis: &RedisPool, id: u64) -> Result<Option<WebhookEndpoint>> {
+    match hash_get(redis, ENDPOINTS_KEY, &id.to_string()).await? {
+        Some(endpoint) => Ok(Some(serde_json::from_str(&endpoint)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn list_endpoints(redis: &RedisPool) -> Result<Vec<WebhookEndpoint>> {
+    let mut endpoints = hash_values(redis, ENDPOINTS_KEY)
+        .await?
+        .iter()
+        .map(|e| serde_json::from_str::<WebhookEndpoint>(e))
+        .collect::<Result<Vec<_>, _>>()?;
+    endpoints.sort_by_key(|e| e.id);
+    Ok(endpoints)
+}
+
+/// Most recent deliveries first, optionally for a single endpoint
+pub async fn list_deliveries(redis: &RedisPool, endpoint_id: Option<u64>, limit: usize) -> Result<Vec<WebhookDelivery>> {
+    let deliveries = list_range(redis, DELIVERIES_KEY, 0, -1).await?;
+    let mut result = Vec::new();
+    for delivery in &deliveries {
+        let delivery: WebhookDelivery = serde_json::from_str(delivery)?;
+        if endpoint_id.is_none_or(|id| delivery.endpoint_id == id) {
+            result.push(delivery);
+            if result.len() == limit {
+                break;
+            }
+        }
+    }
+    Ok(result)
+}
+
+pub async fn list_dead_letters(redis: &RedisPool, limit: usize) -> Result<Vec<DeadLetter>> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+
+    Ok(list_range(redis, DEAD_LETTER_KEY, 0, limit as isize - 1)
+        .await?
+        .iter()
+        .map(|d| serde_json::from_str::<DeadLetter>(d))
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Signs and delivers alert payloads to every enabled endpoint, retrying
+/// with exponential backoff before dead-lettering
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    redis: RedisPool,
+    http: reqwest::Client,
+    max_attempts: u32,
+    backoff: Duration,
+}
+
+impl WebhookDispatcher {
+    pub fn new(redis: RedisPool, config: &Config) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.webhook_timeout_ms))
+            .build()?;
+
+        Ok(Self {
+            redis,
+            http,
+            max_attempts: config.webhook_max_attempts.max(1),
+            backoff: Duration::from_millis(config.webhook_backoff_ms),
+        })
+    }
+
+    /// Fan an alert out to all enabled endpoints in the background
+    pub async fn dispatch(&self, alert: &Alert) -> Result<()> {
+        let payload = serde_json::to_string(&Payload { text: &alert.message, alert })?;
+        for endpoint in list_endpoints(&self.redis).await?.into_iter().filter(|e| e.enabled) {
+            let dispatcher = self.clone();
+            let payload = payload.clone();
+            let alert_id = alert.id;
+            tokio::spawn(async move {
+                if let Err(e) = dispatcher.deliver(&endpoint, alert_id, &payload).await {
+                    warn!("Failed to record webhook delivery to {}: {}", endpoint.url, e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Retry a dead letter once more through the normal retry policy
+    pub async fn redeliver(&self, delivery_id: u64) -> Result<WebhookDelivery> {
+        let dead_letters = list_range(&self.redis, DEAD_LETTER_KEY, 0, -1).await?;
+        let (raw, dead_letter) = dead_letters
+            .iter()
+            .find_map(|raw| {
+                serde_json::from_str::<DeadLetter>(raw)
+                    .ok()
+                    .filter(|d| d.delivery.id == delivery_id)
+                    .map(|d| (raw, d))
+            })
+            .ok_or_else(|| anyhow!("Dead letter for delivery {} not found", delivery_id))?;
+
+        let endpoint = get_endpoint(&self.redis, dead_letter.delivery.endpoint_id)
+            .await?
+            .ok_or_else(|| anyhow!("Webhook endpoint {} no longer exists", dead_letter.delivery.endpoint_id))?;
+
+        // Only the caller that removes the entry redelivers it
+        if !list_remove(&self.redis, DEAD_LETTER_KEY, raw).await? {
+            return Err(anyhow!("Dead letter for delivery {} is already being redelivered", delivery_id));
+        }
+        self.deliver(&endpoint, dead_letter.delivery.alert_id, &dead_letter.payload).await
+    }
+
+    async fn attempt(&self, endpoint: &WebhookEndpoint, payload: &str) -> (Option<u16>, Option<String>) {
+        let timestamp = chrono::Utc::now().timestamp();
+        let response = self
+            .http
+            .post(&endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&endpoint.secret, timestamp, payload))
+            .body(payload.to_string())
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+            Ok(response) => (Some(response.status().as_u16()), Some(format!("HTTP {}", response.status()))),
+            Err(e) => (None, Some(e.to_string())),
+        }
+    }
+
+    /// Send a payload through the retry policy, returning the number of
+    /// attempts made, the last HTTP status and the last error, if any
+    async fn send(&self, endpoint: &WebhookEndpoint, payload: &str) -> (u32, Option<u16>, Option<String>) {
+        let mut attempts = 0;
+        let (response_status, error) = loop {
+            attempts += 1;
+            let (status, error) = self.attempt(endpoint, payload).await;
+            if error.is_none() || attempts >= self.max_attempts {
+                break (status, error);
+            }
+
+            let delay = self.backoff.saturating_mul(2u32.saturating_pow(attempts - 1));
+            warn!(
+                "Webhook delivery to {} failed (attempt {}/{}), retrying in {:?}: {}",
+                endpoint.url, attempts, self.max_attempts, delay, error.as_deref().unwrap_or_default()
+            );
+            tokio::time::sleep(delay).await;
+        };
+        (attempts, response_status, error)
+    }
+
+    async fn deliver(&self, endpoint: &WebhookEndpoint, alert_id: u64, payload: &str) -> Result<WebhookDelivery> {
+        let (attempts, response_status, error) = self.send(endpoint, payload).await;
+        let delivery = WebhookDelivery {
+            id: counter_incr(&self.redis, DELIVERY_ID_KEY).await?,
+            endpoint_id: endpoint.id,
+            alert_id,
+            status: if error.is_none() { DeliveryStatus::Delivered } else { DeliveryStatus::DeadLettered },
+            attempts,
+            response_status,
+            error,
+            completed_at: chrono::Utc::now().timestamp(),
+        };
+        list_push_capped(&self.redis, DELIVERIES_KEY, &serde_json::to_string(&delivery)?, MAX_DELIVERIES).await?;
+
+        match delivery.status {
+            DeliveryStatus::Delivered => info!("📬 Delivered alert {} to {}", alert_id, endpoint.url),
+            DeliveryStatus::DeadLettered => {
+                warn!("Dead-lettered alert {} for {} after {} attempts", alert_id, endpoint.url, attempts);
+                let dead_letter = DeadLetter {
+                    delivery: delivery.clone(),
+                    payload: payload.to_string(),
+                };
+                list_push_capped(&self.redis, DEAD_LETTER_KEY, &serde_json::to_string(&dead_letter)?, MAX_DELIVERIES)
+                    .await?;
+            }
+        }
+
+        Ok(delivery)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef";
+    const PAYLOAD: &str = r#"{"text":"hi"}"#;
+
+    /// A request seen by the receiver, with lowercased header names
+    struct Received {
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    async fn read_request(socket: &mut TcpStream) -> Received {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        let header_end = loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "connection closed mid-request");
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+
+        let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+        let headers: HashMap<String, String> = head
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+            .collect();
+        let length: usize = headers.get("content-length").and_then(|l| l.parse().ok()).unwrap_or(0);
+        while buf.len() < header_end + length {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        Received {
+            headers,
+            body: String::from_utf8_lossy(&buf[header_end..header_end + length]).to_string(),
+        }
+    }
+
+    /// Local HTTP receiver answering the n-th request with `statuses[n]`,
+    /// repeating the last status once they run out
+    async fn receiver(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let log = received.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut socket).await;
+                let status = {
+                    let mut log = log.lock().unwrap();
+                    log.push(request);
+                    statuses[(log.len() - 1).min(statuses.len() - 1)]
+                };
+                let response = format!("HTTP/1.1 {} Test\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, received)
+    }
+
+    fn dispatcher(max_attempts: u32) -> WebhookDispatcher {
+        WebhookDispatcher {
+            // Sending never touches Redis; the client connects lazily
+            redis: RedisPool::open("redis://127.0.0.1/").unwrap(),
+            http: reqwest::Client::new(),
+            max_attempts,
+            backoff: Duration::from_millis(1),
+        }
+    }
+
+    fn endpoint(url: &str) -> WebhookEndpoint {
+        WebhookEndpoint {
+            id: 1,
+            url: url.to_string(),
+            description: None,
+            secret: SECRET.to_string(),
+            enabled: true,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn sign_matches_hmac_sha256_over_timestamp_and_body() {
+        assert_eq!(
+            sign(SECRET, 1_700_000_000, PAYLOAD),
+            "sha256=f3fbef9e6dd9321e73dc29c58e566ffc6b7f9a39c12eabc86e4133ab42f3513d"
+        );
+    }
+
+    #[tokio::test]
+    async fn delivers_signed_payload() {
+        let (url, received) = receiver(vec![200]).await;
+
+        let (attempts, status, error) = dispatcher(3).send(&endpoint(&url), PAYLOAD).await;
+        assert_eq!((attempts, status, error), (1, Some(200), None));
+
+        let received = received.lock().unwrap();
+        let request = &received[0];
+        let timestamp: i64 = request.headers[&TIMESTAMP_HEADER.to_lowercase()].parse().unwrap();
+        assert_eq!(request.body, PAYLOAD);
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(request.headers[&SIGNATURE_HEADER.to_lowercase()], sign(SECRET, timestamp, PAYLOAD));
+    }
+
+    #[tokio::test]
+    async fn retries_until_an_attempt_succeeds() {
+        let (url, received) = receiver(vec![500, 503, 200]).await;
+
+        let (attempts, status, error) = dispatcher(5).send(&endpoint(&url), PAYLOAD).await;
+        assert_eq!((attempts, status, error), (3, Some(200), None));
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts_for_dead_lettering() {
+        let (url, received) = receiver(vec![500]).await;
+
+        let (attempts, status, error) = dispatcher(3).send(&endpoint(&url), PAYLOAD).await;
+        assert_eq!((attempts, status), (3, Some(500)));
+        assert!(error.is_some(), "a failed last attempt dead-letters the delivery");
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,19 @@
+use std::collections::HashMap;
+
+use crate::models::Approval;
+
+/// Reduce an ordered approval log to the most recent non-zero approval per
+/// `(owner, spender)` pair. Each `Approval` event overwrites the previous
+/// allowance, so anything older is stale.
+pub fn latest_approvals(approvals: &[Approval]) -> Vec<Approval> {
+    let mut latest: HashMap<(&str, &str), &Approval> = HashMap::new();
+    for approval in approvals {
+        latest.insert((approval.owner.as_str(), approval.spender.as_str()), approval);
+    }
+
+    latest
+        .into_values()
+        .filter(|a| a.amount != "0")
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,49 @@
+use crate::models::{Candle, Interval, Trade};
+
+use super::parse_amount;
+
+/// Aggregate trades (ordered by execution) into candles per `interval`
+/// bucket. Trades from every pool share one series; unpriced trades are
+/// skipped and buckets without priced trades are omitted.
+pub fn build_candles(trades: &[Trade], decimals: u8, interval: Interval) -> Vec<Candle> {
+    let step = interval.seconds();
+    let scale = 10f64.powi(decimals as i32);
+
+    let mut candles: Vec<Candle> = Vec::new();
+    for trade in trades {
+        let Some(price) = trade.price_usd.filter(|p| p.is_finite() && *p > 0.0) else {
+            continue;
+        };
+        let volume = parse_amount(trade.token_amount()) as f64 / scale;
+        let bucket = trade.timestamp - trade.timestamp.rem_euclid(step);
+
+        match candles.last_mut() {
+            Some(candle) if candle.timestamp == bucket => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += volume;
+                candle.volume_usd += volume * price;
+                candle.trade_count += 1;
+            }
+            _ => candles.push(Candle {
+                timestamp: bucket,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                vwap: price,
+                volume,
+                volume_usd: volume * price,
+                trade_count: 1,
+            }),
+        }
+    }
+
+    for candle in &mut candles {
+        if candle.volume > 0.0 {
+            candle.vwap = candle.volume_usd / candle.volume;
+        }
+    }
+    candles
+}
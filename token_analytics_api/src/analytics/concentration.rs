@@ -0,0 +1,113 @@
+use crate::models::Concentration;
+
+/// Concentration metrics over balances sorted largest first.
+///
+/// Shares are relative to the summed balances passed in, so callers exclude
+/// non-circulating addresses (pools, burn addresses, bridges) beforehand.
+pub fn concentration(balances: &[u128]) -> Concentration {
+    let total: f64 = balances.iter().map(|b| *b as f64).sum();
+    let holder_count = balances.len() as u64;
+    if total <= 0.0 {
+        return Concentration {
+            holder_count,
+            gini: None,
+            hhi: None,
+            nakamoto: None,
+            top10_share: None,
+            top50_share: None,
+            top100_share: None,
+        };
+    }
+
+    let shares: Vec<f64> = balances.iter().map(|b| *b as f64 / total).collect();
+    let top_share = |n: usize| Some(shares.iter().take(n).sum::<f64>() * 100.0);
+
+    Concentration {
+        holder_count,
+        gini: Some(gini(&shares)),
+        // Conventional 0-10,000 scale: shares expressed in percent, squared
+        hhi: Some(shares.iter().map(|s| (s * 100.0).powi(2)).sum()),
+        nakamoto: Some(nakamoto(&shares)),
+        top10_share: top_share(10),
+        top50_share: top_share(50),
+        top100_share: top_share(100),
+    }
+}
+
+/// Gini coefficient of shares sorted descending
+fn gini(shares: &[f64]) -> f64 {
+    let n = shares.len() as f64;
+    if n < 2.0 {
+        return 0.0;
+    }
+
+    // Rank ascending: G = (2 * sum(i * x_i) / (n * sum(x))) - (n + 1) / n, with sum(x) = 1
+    let weighted: f64 = shares
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, share)| (i as f64 + 1.0) * share)
+        .sum();
+
+    (2.0 * weighted / n) - (n + 1.0) / n
+}
+
+/// Smallest number of holders that together control more than half the supply
+fn nakamoto(shares: &[f64]) -> u64 {
+    let mut cumulative = 0.0;
+    for (i, share) in shares.iter().enumerate() {
+        cumulative += share;
+        if cumulative > 0.5 {
+            return i as u64 + 1;
+        }
+    }
+    shares.len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} differs from {}", actual, expected);
+    }
+
+    #[test]
+    fn equal_balances_have_zero_gini() {
+        let metrics = concentration(&[250; 4]);
+
+        assert_eq!(metrics.holder_count, 4);
+        assert_close(metrics.gini.unwrap(), 0.0);
+        assert_close(metrics.hhi.unwrap(), 2500.0);
+        assert_eq!(metrics.nakamoto, Some(3));
+    }
+
+    #[test]
+    fn single_holder_is_fully_concentrated() {
+        let metrics = concentration(&[1_000_000]);
+
+        assert_close(metrics.gini.unwrap(), 0.0);
+        assert_close(metrics.hhi.unwrap(), 10_000.0);
+        assert_eq!(metrics.nakamoto, Some(1));
+        assert_close(metrics.top10_share.unwrap(), 100.0);
+    }
+
+    #[test]
+    fn unequal_balances() {
+        // Mean absolute difference over twice the mean: 4 / (2 * 4 * 2)
+        let metrics = concentration(&[3, 1]);
+
+        assert_close(metrics.gini.unwrap(), 0.25);
+        assert_close(metrics.hhi.unwrap(), 75.0 * 75.0 + 25.0 * 25.0);
+        assert_eq!(metrics.nakamoto, Some(1));
+        assert_eq!(concentration(&[40, 30, 30]).nakamoto, Some(2));
+    }
+
+    #[test]
+    fn empty_supply_has_no_metrics() {
+        let metrics = concentration(&[0, 0]);
+
+        assert_eq!(metrics.holder_count, 2);
+        assert!(metrics.gini.is_none() && metrics.hhi.is_none() && metrics.nakamoto.is_none());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::models::DistributionBucket;
+
+/// Token-unit balance edges: 0, 1, 10, ... 1M
+pub const DEFAULT_BALANCE_EDGES: [f64; 8] = [0.0, 1.0, 10.0, 100.0, 1_000.0, 10_000.0, 100_000.0, 1_000_000.0];
+/// USD value edges: $0, $100, $1k, $10k, $100k, $1M
+pub const DEFAULT_USD_EDGES: [f64; 6] = [0.0, 100.0, 1_000.0, 10_000.0, 100_000.0, 1_000_000.0];
+
+/// Count holders into `[edge_i, edge_i+1)` buckets by `value(balance)`; the
+/// last bucket is open-ended. `edges` must be sorted ascending. When the
+/// first edge is above zero, a `[0, edge_0)` bucket is added so every
+/// holder is counted. Summed balances are reported in token units.
+pub fn bucket_holders(
+    balances: &[f64],
+    edges: &[f64],
+    value: impl Fn(f64) -> f64,
+) -> Vec<DistributionBucket> {
+    let edges: Vec<f64> = match edges.first() {
+        Some(first) if *first > 0.0 => std::iter::once(0.0).chain(edges.iter().copied()).collect(),
+        _ => edges.to_vec(),
+    };
+    let mut buckets: Vec<DistributionBucket> = edges
+        .iter()
+        .enumerate()
+        .map(|(i, min)| DistributionBucket {
+            min: *min,
+            max: edges.get(i + 1).copied(),
+            holder_count: 0,
+            total_balance: 0.0,
+        })
+        .collect();
+
+    for balance in balances {
+        let value = value(*balance);
+        // Last bucket whose lower edge the value reaches
+        let Some(index) = edges.iter().rposition(|edge| value >= *edge) else {
+            continue;
+        };
+        buckets[index].holder_count += 1;
+        buckets[index].total_balance += balance;
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(buckets: &[DistributionBucket]) -> Vec<u64> {
+        buckets.iter().map(|b| b.holder_count).collect()
+    }
+
+    #[test]
+    fn buckets_by_lower_edge() {
+        let buckets = bucket_holders(&[0.5, 1.0, 9.0, 10.0, 5_000.0], &[0.0, 1.0, 10.0, 100.0], |b| b);
+
+        assert_eq!(counts(&buckets), vec![1, 2, 1, 1]);
+        assert_eq!(buckets[1].total_balance, 10.0);
+        assert_eq!(buckets[3].max, None);
+        assert_eq!(buckets[0].max, Some(1.0));
+    }
+
+    #[test]
+    fn adds_a_bucket_below_the_first_edge() {
+        let buckets = bucket_holders(&[0.5, 50.0, 500.0], &[10.0, 100.0], |b| b);
+
+        assert_eq!(buckets[0].min, 0.0);
+        assert_eq!(buckets[0].max, Some(10.0));
+        assert_eq!(counts(&buckets), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn buckets_by_value_but_sums_balances() {
+        // At $2 per token, 60 tokens are worth $120
+        let buckets = bucket_holders(&[10.0, 60.0], &DEFAULT_USD_EDGES, |b| b * 2.0);
+
+        assert_eq!(counts(&buckets), vec![1, 1, 0, 0, 0, 0]);
+        assert_eq!(buckets[1].total_balance, 60.0);
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use crate::models::{Interval, TokenHistoryPoint, Transfer, TransferKind};
+
+use super::parse_amount;
+
+/// Replay transfers (ordered, starting from the token's first event) and
+/// emit one point per `interval` bucket covering `from..to`.
+///
+/// Supply moves with mints and burns; holder counts track every non-zero balance.
+pub fn bucket_history(
+    transfers: &[Transfer],
+    from: i64,
+    to: i64,
+    interval: Interval,
+) -> Vec<TokenHistoryPoint> {
+    let step = interval.seconds();
+    let start = from - from.rem_euclid(step);
+
+    let mut balances: HashMap<&str, u128> = HashMap::new();
+    let mut supply: u128 = 0;
+    let mut holders: u64 = 0;
+    let mut events = transfers.iter().peekable();
+    let mut points = Vec::new();
+
+    let mut bucket_start = start;
+    while bucket_start <= to {
+        let bucket_end = bucket_start + step;
+        let mut transfer_count = 0;
+        let mut volume: u128 = 0;
+
+        while let Some(transfer) = events.next_if(|t| t.timestamp < bucket_end) {
+            let amount = parse_amount(&transfer.amount);
+
+            if transfer.kind == TransferKind::Mint {
+                supply = supply.saturating_add(amount);
+            } else {
+                let balance = balances.entry(transfer.from.as_str()).or_default();
+                let before = *balance;
+                *balance = balance.saturating_sub(amount);
+                if before > 0 && *balance == 0 {
+                    holders -= 1;
+                }
+            }
+
+            if transfer.kind == TransferKind::Burn {
+                supply = supply.saturating_sub(amount);
+            } else {
+                let balance = balances.entry(transfer.to.as_str()).or_default();
+                let before = *balance;
+                *balance = balance.saturating_add(amount);
+                if before == 0 && *balance > 0 {
+                    holders += 1;
+                }
+            }
+
+            if transfer.timestamp >= bucket_start {
+                transfer_count += 1;
+                volume = volume.saturating_add(amount);
+            }
+        }
+
+        points.push(TokenHistoryPoint {
+            timestamp: bucket_start,
+            total_supply: supply.to_string(),
+            holder_count: holders,
+            transfer_count,
+            volume: volume.to_string(),
+        });
+        bucket_start = bucket_end;
+    }
+
+    points
+}
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Transfer, TransferKind};
+
+use super::parse_amount;
+
+/// Supply and balances replayed from a token's complete transfer history
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HolderState {
+    /// Mints minus burns, in raw units
+    pub supply: u128,
+    /// Non-zero balances, largest first
+    pub balances: Vec<(String, u128)>,
+}
+
+/// Replay ordered transfers into current non-zero balances, largest first
+pub fn holder_balances(transfers: &[Transfer]) -> Vec<(String, u128)> {
+    let mut balances: HashMap<&str, u128> = HashMap::new();
+    for transfer in transfers {
+        let amount = parse_amount(&transfer.amount);
+        if transfer.kind != TransferKind::Mint {
+            let balance = balances.entry(transfer.from.as_str()).or_default();
+            *balance = balance.saturating_sub(amount);
+        }
+        if transfer.kind != TransferKind::Burn {
+            let balance = balances.entry(transfer.to.as_str()).or_default();
+            *balance = balance.saturating_add(amount);
+        }
+    }
+
+    let mut holders: Vec<(String, u128)> = balances
+        .into_iter()
+        .filter(|(_, balance)| *balance > 0)
+        .map(|(address, balance)| (address.to_string(), balance))
+        .collect();
+
+    holders.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    holders
+}
@@ -0,0 +1,373 @@
+use crate::models::{PoolImpact, PriceImpact, TradeSide};
+
+/// Slices an order is cut into when routing it across pools
+const ROUTE_STEPS: usize = 20;
+/// Relative shortfall still counted as a full fill
+const FILL_TOLERANCE: f64 = 1e-9;
+
+/// How a pool prices swaps, in raw token and quote units
+pub enum Curve {
+    /// `x * y = k` pools: Uniswap V2 and Raydium AMM v4
+    ConstantProduct { token_reserve: f64, quote_reserve: f64 },
+    /// Uniswap V3 liquidity between initialized ticks
+    Concentrated {
+        /// `sqrt(token1 / token0)` in raw units
+        sqrt_price: f64,
+        /// Liquidity active at the current price
+        liquidity: f64,
+        /// Initialized ticks and their `liquidityNet`, ascending
+        ticks: Vec<(i32, f64)>,
+        /// Ticks whose bitmap was read; nothing is known outside them
+        tick_range: (i32, i32),
+        token_is_token0: bool,
+    },
+}
+
+fn tick_sqrt_price(tick: i32) -> f64 {
+    1.0001f64.powf(tick as f64 / 2.0)
+}
+
+impl Curve {
+    /// Quote per token at the current price, in raw units
+    fn spot(&self) -> f64 {
+        match self {
+            Curve::ConstantProduct { token_reserve, quote_reserve } => quote_reserve / token_reserve,
+            Curve::Concentrated { sqrt_price, token_is_token0, .. } => {
+                let price = sqrt_price * sqrt_price;
+                if *token_is_token0 {
+                    price
+                } else {
+                    1.0 / price
+                }
+            }
+        }
+    }
+
+    /// Quote reserves in raw units; virtual reserves of the active range for V3
+    fn depth(&self) -> f64 {
+        match self {
+            Curve::ConstantProduct { quote_reserve, .. } => *quote_reserve,
+            Curve::Concentrated { sqrt_price, liquidity, token_is_token0, .. } => {
+                if *token_is_token0 {
+                    liquidity * sqrt_price
+                } else {
+                    liquidity / sqrt_price
+                }
+            }
+        }
+    }
+
+    /// Swap `amount_in` (net of fees) of the token, or of the quote when
+    /// `token_in` is false. Returns `(amount_out, amount_in_used)`.
+    fn swap(&self, amount_in: f64, token_in: bool) -> (f64, f64) {
+        match self {
+            Curve::ConstantProduct { token_reserve, quote_reserve } => {
+                let (reserve_in, reserve_out) = if token_in {
+                    (token_reserve, quote_reserve)
+                } else {
+                    (quote_reserve, token_reserve)
+                };
+                (reserve_out * amount_in / (reserve_in + amount_in), amount_in)
+            }
+            Curve::Concentrated { sqrt_price, liquidity, ticks, tick_range, token_is_token0 } => {
+                // Paying in token0 pushes the price (token1 per token0) down
+                let zero_for_one = token_in == *token_is_token0;
+                swap_concentrated(*sqrt_price, *liquidity, ticks, *tick_range, amount_in, zero_for_one)
+            }
+        }
+    }
+}
+
+/// Walk a V3 swap from tick to tick, adjusting liquidity at each crossing.
+/// The swap stops at the edge of `tick_range`, so orders that would move the
+/// price past it come back partially filled.
+fn swap_concentrated(
+    mut sqrt_price: f64,
+    mut liquidity: f64,
+    ticks: &[(i32, f64)],
+    tick_range: (i32, i32),
+    amount_in: f64,
+    zero_for_one: bool,
+) -> (f64, f64) {
+    // (sqrt price of the boundary, liquidity change when crossing it)
+    let mut crossings: Vec<(f64, f64)> = if zero_for_one {
+        ticks
+            .iter()
+            .rev()
+            .map(|(tick, net)| (tick_sqrt_price(*tick), -net))
+            .filter(|(target, _)| *target <= sqrt_price)
+            .collect()
+    } else {
+        ticks
+            .iter()
+            .map(|(tick, net)| (tick_sqrt_price(*tick), *net))
+            .filter(|(target, _)| *target > sqrt_price)
+            .collect()
+    };
+    let edge = if zero_for_one { tick_range.0 } else { tick_range.1 };
+    crossings.push((tick_sqrt_price(edge), 0.0));
+
+    let step_out = |liquidity: f64, from: f64, to: f64| {
+        if zero_for_one {
+            liquidity * (from - to)
+        } else {
+            liquidity * (1.0 / from - 1.0 / to)
+        }
+    };
+
+    let (mut remaining, mut amount_out) = (amount_in, 0.0);
+    for (target, net) in crossings {
+        if liquidity > 0.0 {
+            let step_in = if zero_for_one {
+                liquidity * (1.0 / target - 1.0 / sqrt_price)
+            } else {
+                liquidity * (target - sqrt_price)
+            };
+            if remaining <= step_in {
+                let next = if zero_for_one {
+                    1.0 / (1.0 / sqrt_price + remaining / liquidity)
+                } else {
+                    sqrt_price + remaining / liquidity
+                };
+                return (amount_out + step_out(liquidity, sqrt_price, next), amount_in);
+            }
+            amount_out += step_out(liquidity, sqrt_price, target);
+            remaining -= step_in;
+        }
+        sqrt_price = target;
+        liquidity = (liquidity + net).max(0.0);
+    }
+    (amount_out, amount_in - remaining)
+}
+
+/// A pool pairing the token with a USD-priced quote asset
+pub struct SimPool {
+    pub address: String,
+    pub dex: String,
+    /// Swap fee as a fraction of the input
+    pub fee: f64,
+    pub token_decimals: u8,
+    pub quote_decimals: u8,
+    pub quote_price_usd: f64,
+    pub curve: Curve,
+}
+
+impl SimPool {
+    fn token_scale(&self) -> f64 {
+        10f64.powi(self.token_decimals as i32)
+    }
+
+    fn quote_scale(&self) -> f64 {
+        10f64.powi(self.quote_decimals as i32)
+    }
+
+    fn spot_usd(&self) -> f64 {
+        self.curve.spot() * self.token_scale() / self.quote_scale() * self.quote_price_usd
+    }
+
+    fn depth_usd(&self) -> f64 {
+        self.curve.depth() / self.quote_scale() * self.quote_price_usd
+    }
+
+    /// Fill `input` USD of quote (buys) or token units (sells). Returns
+    /// `(output, input_used)`, with output in token units (buys) or USD (sells).
+    fn fill(&self, side: TradeSide, input: f64) -> (f64, f64) {
+        if input <= 0.0 {
+            return (0.0, 0.0);
+        }
+        let kept = 1.0 - self.fee;
+        match side {
+            TradeSide::Buy => {
+                let quote_in = input / self.quote_price_usd * self.quote_scale();
+                let (out, used) = self.curve.swap(quote_in * kept, false);
+                (out / self.token_scale(), used / kept / self.quote_scale() * self.quote_price_usd)
+            }
+            TradeSide::Sell => {
+                let (out, used) = self.curve.swap(input * self.token_scale() * kept, true);
+                (out / self.quote_scale() * self.quote_price_usd, used / kept / self.token_scale())
+            }
+        }
+    }
+}
+
+/// `(usd, token_units)` moved by a fill
+fn legs(side: TradeSide, output: f64, used: f64) -> (f64, f64) {
+    match side {
+        TradeSide::Buy => (used, output),
+        TradeSide::Sell => (output, used),
+    }
+}
+
+fn execution_price(side: TradeSide, output: f64, used: f64) -> Option<f64> {
+    let (usd, tokens) = legs(side, output, used);
+    (tokens > 0.0).then(|| usd / tokens)
+}
+
+/// Percent by which `execution` is worse than `spot` for the order's side
+fn slippage(side: TradeSide, spot: f64, execution: f64) -> f64 {
+    match side {
+        TradeSide::Buy => (execution / spot - 1.0) * 100.0,
+        TradeSide::Sell => (1.0 - execution / spot) * 100.0,
+    }
+}
+
+/// Simulate an order of `amount_usd` against each pool on its own and split
+/// across all of them. Slippage is measured from the spot price of the deepest
+/// pool, which also sizes sell orders in tokens.
+pub fn price_impact(pools: &[SimPool], side: TradeSide, amount_usd: f64) -> PriceImpact {
+    let pools: Vec<&SimPool> = pools
+        .iter()
+        .filter(|p| p.spot_usd().is_finite() && p.spot_usd() > 0.0)
+        .collect();
+    let Some(spot) = pools
+        .iter()
+        .max_by(|a, b| a.depth_usd().total_cmp(&b.depth_usd()))
+        .map(|p| p.spot_usd())
+    else {
+        return PriceImpact {
+            side,
+            amount_usd,
+            spot_price_usd: None,
+            execution_price_usd: None,
+            slippage: None,
+            token_amount: 0.0,
+            filled: false,
+            best_pool: None,
+            pools: Vec::new(),
+        };
+    };
+    let input = match side {
+        TradeSide::Buy => amount_usd,
+        TradeSide::Sell => amount_usd / spot,
+    };
+
+    // Greedy split: each slice goes to the pool with the best marginal output
+    let step = input / ROUTE_STEPS as f64;
+    let mut allocated = vec![0.0; pools.len()];
+    for _ in 0..ROUTE_STEPS {
+        let best = (0..pools.len())
+            .map(|i| (i, pools[i].fill(side, allocated[i] + step).0 - pools[i].fill(side, allocated[i]).0))
+            .filter(|(_, marginal)| *marginal > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((i, _)) => allocated[i] += step,
+            None => break,
+        }
+    }
+    let (output, used) = pools
+        .iter()
+        .zip(&allocated)
+        .map(|(pool, amount)| pool.fill(side, *amount))
+        .fold((0.0, 0.0), |(output, used), (o, u)| (output + o, used + u));
+    let execution_price_usd = execution_price(side, output, used);
+
+    let mut quotes: Vec<PoolImpact> = pools
+        .iter()
+        .zip(&allocated)
+        .map(|(pool, amount)| {
+            let (output, used) = pool.fill(side, input);
+            let execution = execution_price(side, output, used);
+            PoolImpact {
+                address: pool.address.clone(),
+                dex: pool.dex.clone(),
+                spot_price_usd: pool.spot_usd(),
+                execution_price_usd: execution,
+                slippage: execution.map(|price| slippage(side, spot, price)),
+                token_amount: legs(side, output, used).1,
+                filled: used >= input * (1.0 - FILL_TOLERANCE),
+                route_share: amount / input * 100.0,
+            }
+        })
+        .collect();
+    // Filled pools first, best execution first
+    quotes.sort_by(|a, b| {
+        b.filled
+            .cmp(&a.filled)
+            .then(a.slippage.unwrap_or(f64::INFINITY).total_cmp(&b.slippage.unwrap_or(f64::INFINITY)))
+    });
+
+    PriceImpact {
+        side,
+        amount_usd,
+        spot_price_usd: Some(spot),
+        execution_price_usd,
+        slippage: execution_price_usd.map(|price| slippage(side, spot, price)),
+        token_amount: legs(side, output, used).1,
+        filled: used >= input * (1.0 - FILL_TOLERANCE),
+        best_pool: quotes.first().filter(|q| q.filled).cloned(),
+        pools: quotes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ETH/USDC position from the Uniswap V3 Development Book: both tokens have
+    /// 18 decimals, the price is 5000 USDC per ETH and liquidity spans ticks
+    /// 84222..86129
+    const SQRT_PRICE_X96: f64 = 5602277097478614198912276234240.0;
+    const LIQUIDITY: f64 = 1517882343751509868544.0;
+    const LOWER_TICK: i32 = 84222;
+    const UPPER_TICK: i32 = 86129;
+    const E18: f64 = 1e18;
+
+    fn sqrt_price() -> f64 {
+        SQRT_PRICE_X96 / 2f64.powi(96)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        let error = ((actual - expected) / expected).abs();
+        assert!(error < 1e-9, "{} differs from {} by {:e}", actual, expected, error);
+    }
+
+    #[test]
+    fn swap_concentrated_matches_v3_quote() {
+        // The book's first swap: 42 USDC in buys 0.008396714242162444 ETH
+        let ticks = [(LOWER_TICK, LIQUIDITY), (UPPER_TICK, -LIQUIDITY)];
+        let (out, used) = swap_concentrated(sqrt_price(), LIQUIDITY, &ticks, (84000, 87000), 42.0 * E18, false);
+
+        assert_close(out, 8396714242162444.0);
+        assert_eq!(used, 42.0 * E18);
+    }
+
+    #[test]
+    fn swap_concentrated_adds_liquidity_when_crossing_a_tick() {
+        // A second position starting at tick 85500 doubles the liquidity above it
+        let ticks = [
+            (LOWER_TICK, LIQUIDITY),
+            (85500, LIQUIDITY),
+            (UPPER_TICK, -2.0 * LIQUIDITY),
+        ];
+        let (out, used) = swap_concentrated(sqrt_price(), LIQUIDITY, &ticks, (84000, 87000), 5000.0 * E18, false);
+
+        assert_close(out, 964448542059205177.14);
+        assert_eq!(used, 5000.0 * E18);
+    }
+
+    #[test]
+    fn swap_concentrated_stops_at_the_read_tick_range() {
+        // Selling ETH past the lower tick leaves no liquidity before the edge
+        let ticks = [(LOWER_TICK, LIQUIDITY), (UPPER_TICK, -LIQUIDITY)];
+        let (out, used) = swap_concentrated(sqrt_price(), LIQUIDITY, &ticks, (84000, 87000), 10.0 * E18, true);
+
+        assert_close(out, 5000209190920489524099.15);
+        assert_close(used, 1048907319245180064.97);
+    }
+
+    #[test]
+    fn concentrated_curve_maps_token_side_to_swap_direction() {
+        let curve = Curve::Concentrated {
+            sqrt_price: sqrt_price(),
+            liquidity: LIQUIDITY,
+            ticks: vec![(LOWER_TICK, LIQUIDITY), (UPPER_TICK, -LIQUIDITY)],
+            tick_range: (84000, 87000),
+            token_is_token0: true,
+        };
+
+        // Buying the token (token0 = ETH) pays in USDC
+        let (out, _) = curve.swap(42.0 * E18, false);
+        assert_close(out, 8396714242162444.0);
+        assert_close(curve.spot(), sqrt_price() * sqrt_price());
+    }
+}
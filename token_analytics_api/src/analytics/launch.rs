@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use crate::models::{Chain, FundingCluster, LaunchBuyer, Trade, TradeSide};
+
+use super::{normalize_address, parse_amount};
+
+/// A token's first trade and the buys that followed it
+pub struct Launch {
+    pub block: u64,
+    pub timestamp: i64,
+    /// `(wallet, first_block, raw_units_bought)`, largest buy first
+    pub buyers: Vec<(String, u64, u128)>,
+}
+
+/// Wallets that bought within `window` blocks (slots on Solana) of the first
+/// trade, the first trade's own block included. `trades` must be in
+/// execution order.
+pub fn launch_buyers(chain: Chain, trades: &[Trade], window: u64) -> Option<Launch> {
+    let first = trades.first()?;
+    let end = first.block_number.saturating_add(window);
+
+    let mut bought: HashMap<String, (u64, u128)> = HashMap::new();
+    for trade in trades
+        .iter()
+        .take_while(|t| t.block_number < end)
+        .filter(|t| t.side == TradeSide::Buy)
+    {
+        let entry = bought
+            .entry(normalize_address(chain, &trade.trader))
+            .or_insert((trade.block_number, 0));
+        entry.1 += parse_amount(&trade.amount_out);
+    }
+
+    let mut buyers: Vec<_> = bought
+        .into_iter()
+        .map(|(wallet, (block, amount))| (wallet, block, amount))
+        .collect();
+    buyers.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+
+    Some(Launch { block: first.block_number, timestamp: first.timestamp, buyers })
+}
+
+/// Group buyers by funder, most wallets first. A funder of a single buyer
+/// does not form a cluster.
+pub fn funding_clusters(buyers: &[LaunchBuyer]) -> Vec<FundingCluster> {
+    let mut clusters: HashMap<&str, FundingCluster> = HashMap::new();
+    for buyer in buyers {
+        let Some(funder) = buyer.funder.as_deref() else { continue };
+        let cluster = clusters.entry(funder).or_insert_with(|| FundingCluster {
+            funder: funder.to_string(),
+            wallets: Vec::new(),
+            share: 0.0,
+        });
+        cluster.wallets.push(buyer.address.clone());
+        cluster.share += buyer.share;
+    }
+
+    let mut clusters: Vec<_> = clusters.into_values().filter(|c| c.wallets.len() > 1).collect();
+    clusters.sort_by(|a, b| {
+        b.wallets.len().cmp(&a.wallets.len()).then_with(|| b.share.total_cmp(&a.share))
+    });
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(block_number: u64, side: TradeSide, trader: &str, amount: u128) -> Trade {
+        let (amount_in, amount_out) = match side {
+            TradeSide::Buy => ("0".to_string(), amount.to_string()),
+            TradeSide::Sell => (amount.to_string(), "0".to_string()),
+        };
+        Trade {
+            tx_hash: String::new(),
+            block_number,
+            log_index: 0,
+            timestamp: block_number as i64 * 12,
+            pool: String::new(),
+            dex: String::new(),
+            side,
+            trader: trader.to_string(),
+            token_in: String::new(),
+            amount_in,
+            token_out: String::new(),
+            amount_out,
+            price_usd: None,
+            volume_usd: None,
+        }
+    }
+
+    fn buyer(address: &str, share: f64, funder: Option<&str>) -> LaunchBuyer {
+        LaunchBuyer {
+            address: address.to_string(),
+            first_block: 0,
+            bought: "0".to_string(),
+            balance: "0".to_string(),
+            share,
+            funder: funder.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn no_trades_no_launch() {
+        assert!(launch_buyers(Chain::Ethereum, &[], 5).is_none());
+    }
+
+    #[test]
+    fn buys_within_window_are_summed_per_wallet() {
+        let trades = [
+            trade(100, TradeSide::Buy, "0xAAA", 10),
+            trade(101, TradeSide::Sell, "0xbbb", 50),
+            trade(102, TradeSide::Buy, "0xbbb", 30),
+            trade(104, TradeSide::Buy, "0xaaa", 25),
+            // First block past the window
+            trade(105, TradeSide::Buy, "0xccc", 1000),
+        ];
+        let launch = launch_buyers(Chain::Ethereum, &trades, 5).unwrap();
+
+        assert_eq!((launch.block, launch.timestamp), (100, 1200));
+        assert_eq!(
+            launch.buyers,
+            [("0xaaa".to_string(), 100, 35), ("0xbbb".to_string(), 102, 30)]
+        );
+    }
+
+    #[test]
+    fn equal_buys_are_ordered_by_wallet() {
+        let trades = [
+            trade(7, TradeSide::Buy, "0xbbb", 5),
+            trade(7, TradeSide::Buy, "0xaaa", 5),
+        ];
+        let launch = launch_buyers(Chain::Ethereum, &trades, 1).unwrap();
+
+        let wallets: Vec<&str> = launch.buyers.iter().map(|(wallet, _, _)| wallet.as_str()).collect();
+        assert_eq!(wallets, ["0xaaa", "0xbbb"]);
+    }
+
+    #[test]
+    fn clusters_need_two_wallets_from_one_funder() {
+        let buyers = [
+            buyer("a", 1.0, Some("f1")),
+            buyer("b", 2.0, Some("f1")),
+            buyer("c", 5.0, Some("f2")),
+            buyer("d", 4.0, Some("f2")),
+            buyer("e", 0.5, Some("f2")),
+            buyer("f", 9.0, Some("f3")),
+            buyer("g", 9.0, None),
+        ];
+        let clusters = funding_clusters(&buyers);
+
+        let summary: Vec<(&str, usize, f64)> = clusters
+            .iter()
+            .map(|c| (c.funder.as_str(), c.wallets.len(), c.share))
+            .collect();
+        assert_eq!(summary, [("f2", 3, 9.5), ("f1", 2, 3.0)]);
+        assert_eq!(clusters[1].wallets, ["a", "b"]);
+    }
+
+    #[test]
+    fn equal_clusters_are_ordered_by_share() {
+        let buyers = [
+            buyer("a", 1.0, Some("small")),
+            buyer("b", 1.0, Some("small")),
+            buyer("c", 3.0, Some("large")),
+            buyer("d", 3.0, Some("large")),
+        ];
+        let funders: Vec<String> = funding_clusters(&buyers).into_iter().map(|c| c.funder).collect();
+
+        assert_eq!(funders, ["large", "small"]);
+    }
+}
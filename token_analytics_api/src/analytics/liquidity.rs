@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+
+use crate::models::{LiquidityLock, Transfer, TransferKind};
+
+use super::{holders::holder_balances, is_burn_address, parse_amount};
+
+/// Break down LP ownership from the LP token's transfer history, which must
+/// reach back to the pair's creation. `pair` and `lockers` must be lowercase.
+/// The pair burning its own balance removes liquidity and shrinks the supply,
+/// so only LP held by a burn address or sent straight to zero by anyone else
+/// counts as burned. `total_supply` is read on chain; what the replay does not
+/// account for is reported as `unaccounted`.
+pub fn liquidity_lock(
+    transfers: &[Transfer],
+    pair: &str,
+    total_supply: u128,
+    lockers: &HashSet<String>,
+) -> LiquidityLock {
+    let balances = holder_balances(transfers);
+    let share = |amount: u128| {
+        if total_supply > 0 {
+            amount as f64 / total_supply as f64 * 100.0
+        } else {
+            0.0
+        }
+    };
+
+    // The first LP mint to a real account is the initial liquidity provision
+    let deployer = transfers
+        .iter()
+        .find(|t| t.kind == TransferKind::Mint && !is_burn_address(&t.to))
+        .map(|t| t.to.to_lowercase());
+
+    let mut burned = transfers
+        .iter()
+        .filter(|t| t.kind == TransferKind::Burn && t.from.to_lowercase() != pair)
+        .fold(0u128, |sum, t| sum.saturating_add(parse_amount(&t.amount)));
+    let (mut held, mut locked, mut deployer_held, mut holder_count) = (0u128, 0u128, 0u128, 0);
+    for (holder, balance) in &balances {
+        let holder = holder.to_lowercase();
+        if is_burn_address(&holder) {
+            burned = burned.saturating_add(*balance);
+            continue;
+        }
+        held = held.saturating_add(*balance);
+        holder_count += 1;
+        if lockers.contains(&holder) {
+            locked = locked.saturating_add(*balance);
+        } else if deployer.as_deref() == Some(holder.as_str()) {
+            deployer_held = deployer_held.saturating_add(*balance);
+        }
+    }
+    let burned = burned.min(total_supply);
+    let secured = burned.saturating_add(locked).min(total_supply);
+    let unaccounted = total_supply.saturating_sub(burned.saturating_add(held));
+
+    LiquidityLock {
+        lp_supply: total_supply.to_string(),
+        burned: share(burned),
+        locked: share(locked),
+        deployer,
+        deployer_held: share(deployer_held),
+        unlocked: share(held.saturating_sub(locked)),
+        lock_percentage: share(secured),
+        unaccounted: share(unaccounted),
+        holder_count,
+    }
+}
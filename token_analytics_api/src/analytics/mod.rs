@@ -0,0 +1,57 @@
+//! Chain-agnostic analytics computed from indexed on-chain events
+
+pub mod approvals;
+pub mod candles;
+pub mod concentration;
+pub mod distribution;
+pub mod history;
+pub mod holders;
+pub mod impact;
+pub mod launch;
+pub mod liquidity;
+pub mod pnl;
+pub mod reputation;
+pub mod supply;
+pub mod wash;
+
+use crate::models::{Chain, TransferKind};
+
+pub const EVM_ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+pub const SOLANA_ZERO_ADDRESS: &str = "11111111111111111111111111111111";
+/// Conventional EVM burn address
+pub const EVM_DEAD_ADDRESS: &str = "0x000000000000000000000000000000000000dead";
+
+/// Canonical form for comparing addresses: EVM hex is case-insensitive,
+/// Solana base58 is not
+pub fn normalize_address(chain: Chain, address: &str) -> String {
+    match chain {
+        Chain::Solana => address.to_string(),
+        Chain::Ethereum | Chain::Bsc | Chain::Other => address.to_lowercase(),
+    }
+}
+
+/// Whether an address is the mint/burn counterparty on either chain
+pub fn is_zero_address(address: &str) -> bool {
+    address == EVM_ZERO_ADDRESS || address == SOLANA_ZERO_ADDRESS
+}
+
+/// Whether tokens sent to an address are effectively destroyed
+pub fn is_burn_address(address: &str) -> bool {
+    is_zero_address(address) || address == EVM_DEAD_ADDRESS
+}
+
+/// Classify a movement by its counterparties
+pub fn classify_transfer(from: &str, to: &str) -> TransferKind {
+    if is_zero_address(from) {
+        TransferKind::Mint
+    } else if is_zero_address(to) {
+        TransferKind::Burn
+    } else {
+        TransferKind::Transfer
+    }
+}
+
+/// Parse a raw token amount, treating malformed values as zero
+pub fn parse_amount(amount: &str) -> u128 {
+    amount.parse().unwrap_or_default()
+}
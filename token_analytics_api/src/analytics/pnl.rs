@@ -0,0 +1,243 @@
+use std::collections::VecDeque;
+
+use crate::models::{Chain, CostBasisMethod, TokenPnl, Trade, TradeSide};
+
+use super::parse_amount;
+
+/// Open position tracked under either costing method
+enum Book {
+    /// Open buy lots as `(units, usd_per_unit)`, oldest first
+    Fifo(VecDeque<(f64, f64)>),
+    /// Running `(units, total_cost_usd)`
+    Average(f64, f64),
+}
+
+impl Book {
+    fn new(method: CostBasisMethod) -> Self {
+        match method {
+            CostBasisMethod::Fifo => Book::Fifo(VecDeque::new()),
+            CostBasisMethod::AverageCost => Book::Average(0.0, 0.0),
+        }
+    }
+
+    fn buy(&mut self, units: f64, price: f64) {
+        match self {
+            Book::Fifo(lots) => lots.push_back((units, price)),
+            Book::Average(position, cost) => {
+                *position += units;
+                *cost += units * price;
+            }
+        }
+    }
+
+    /// Close up to `units` at `price`; returns `(realized_pnl, unmatched_units)`
+    fn sell(&mut self, mut units: f64, price: f64) -> (f64, f64) {
+        let mut realized = 0.0;
+        match self {
+            Book::Fifo(lots) => {
+                while units > 0.0 {
+                    let Some(lot) = lots.front_mut() else { break };
+                    let matched = units.min(lot.0);
+                    realized += matched * (price - lot.1);
+                    lot.0 -= matched;
+                    units -= matched;
+                    if lot.0 <= 0.0 {
+                        lots.pop_front();
+                    }
+                }
+            }
+            Book::Average(position, cost) => {
+                let matched = units.min(*position);
+                if matched > 0.0 {
+                    let average = *cost / *position;
+                    realized += matched * (price - average);
+                    *cost -= matched * average;
+                    *position -= matched;
+                    units -= matched;
+                }
+            }
+        }
+        (realized, units)
+    }
+
+    /// `(units, cost_usd)` still open
+    fn open(&self) -> (f64, f64) {
+        match self {
+            Book::Fifo(lots) => lots
+                .iter()
+                .fold((0.0, 0.0), |(units, cost), lot| (units + lot.0, cost + lot.0 * lot.1)),
+            Book::Average(position, cost) => (*position, *cost),
+        }
+    }
+}
+
+/// Realized and unrealized PnL of one wallet's trades in a token. `trades`
+/// must belong to the wallet and be in execution order; trades without a
+/// USD price are counted but not costed.
+pub fn token_pnl(
+    chain: Chain,
+    token: &str,
+    symbol: Option<String>,
+    decimals: u8,
+    trades: &[Trade],
+    method: CostBasisMethod,
+    current_price_usd: Option<f64>,
+) -> TokenPnl {
+    let scale = 10f64.powi(decimals as i32);
+    let mut book = Book::new(method);
+    let (mut buys, mut sells, mut unpriced_trades) = (0, 0, 0);
+    let (mut bought, mut sold, mut realized, mut unmatched_sold) = (0.0, 0.0, 0.0, 0.0);
+
+    for trade in trades {
+        let units = parse_amount(trade.token_amount()) as f64 / scale;
+        match trade.side {
+            TradeSide::Buy => {
+                buys += 1;
+                bought += units;
+            }
+            TradeSide::Sell => {
+                sells += 1;
+                sold += units;
+            }
+        }
+
+        let Some(price) = trade.price_usd else {
+            unpriced_trades += 1;
+            continue;
+        };
+        match trade.side {
+            TradeSide::Buy => book.buy(units, price),
+            TradeSide::Sell => {
+                let (pnl, unmatched) = book.sell(units, price);
+                realized += pnl;
+                unmatched_sold += unmatched;
+            }
+        }
+    }
+
+    let (position, cost_basis_usd) = book.open();
+    TokenPnl {
+        chain,
+        token: token.to_string(),
+        symbol,
+        buys,
+        sells,
+        bought,
+        sold,
+        position,
+        cost_basis_usd,
+        average_cost_usd: (position > 0.0).then(|| cost_basis_usd / position),
+        realized_pnl_usd: realized,
+        unrealized_pnl_usd: current_price_usd.map(|price| position * price - cost_basis_usd),
+        current_price_usd,
+        unpriced_trades,
+        unmatched_sold,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} differs from {}", actual, expected);
+    }
+
+    /// A whole-unit trade of the token against an unnamed quote asset
+    fn trade(side: TradeSide, units: u64, price_usd: Option<f64>) -> Trade {
+        let (amount_in, amount_out) = match side {
+            TradeSide::Buy => ("0".to_string(), units.to_string()),
+            TradeSide::Sell => (units.to_string(), "0".to_string()),
+        };
+        Trade {
+            tx_hash: String::new(),
+            block_number: 0,
+            log_index: 0,
+            timestamp: 0,
+            pool: String::new(),
+            dex: String::new(),
+            side,
+            trader: String::new(),
+            token_in: String::new(),
+            amount_in,
+            token_out: String::new(),
+            amount_out,
+            price_usd,
+            volume_usd: None,
+        }
+    }
+
+    fn pnl(trades: &[Trade], method: CostBasisMethod, current_price_usd: Option<f64>) -> TokenPnl {
+        token_pnl(Chain::Ethereum, "token", None, 0, trades, method, current_price_usd)
+    }
+
+    #[test]
+    fn fifo_sells_match_oldest_lots_first() {
+        let trades = [
+            trade(TradeSide::Buy, 10, Some(1.0)),
+            trade(TradeSide::Buy, 10, Some(3.0)),
+            trade(TradeSide::Sell, 15, Some(4.0)),
+        ];
+        let result = pnl(&trades, CostBasisMethod::Fifo, Some(5.0));
+
+        // The whole first lot and half of the second close: 10 * 3 + 5 * 1
+        assert_close(result.realized_pnl_usd, 35.0);
+        assert_close(result.position, 5.0);
+        assert_close(result.cost_basis_usd, 15.0);
+        assert_close(result.average_cost_usd.unwrap(), 3.0);
+        assert_close(result.unrealized_pnl_usd.unwrap(), 10.0);
+        assert_eq!((result.buys, result.sells), (2, 1));
+        assert_close(result.unmatched_sold, 0.0);
+    }
+
+    #[test]
+    fn average_cost_sells_at_running_average() {
+        let trades = [
+            trade(TradeSide::Buy, 10, Some(1.0)),
+            trade(TradeSide::Buy, 10, Some(3.0)),
+            trade(TradeSide::Sell, 15, Some(4.0)),
+        ];
+        let result = pnl(&trades, CostBasisMethod::AverageCost, Some(5.0));
+
+        assert_close(result.realized_pnl_usd, 15.0 * (4.0 - 2.0));
+        assert_close(result.position, 5.0);
+        assert_close(result.cost_basis_usd, 10.0);
+        assert_close(result.average_cost_usd.unwrap(), 2.0);
+        assert_close(result.unrealized_pnl_usd.unwrap(), 15.0);
+    }
+
+    #[test]
+    fn selling_more_than_bought_is_unmatched() {
+        let trades = [trade(TradeSide::Buy, 5, Some(2.0)), trade(TradeSide::Sell, 8, Some(3.0))];
+
+        for method in [CostBasisMethod::Fifo, CostBasisMethod::AverageCost] {
+            let result = pnl(&trades, method, None);
+
+            assert_close(result.realized_pnl_usd, 5.0);
+            assert_close(result.unmatched_sold, 3.0);
+            assert_close(result.sold, 8.0);
+            assert_close(result.position, 0.0);
+            assert_eq!(result.average_cost_usd, None);
+            assert_eq!(result.unrealized_pnl_usd, None);
+        }
+    }
+
+    #[test]
+    fn unpriced_trades_are_counted_but_not_costed() {
+        let trades = [
+            trade(TradeSide::Buy, 10, None),
+            trade(TradeSide::Buy, 10, Some(1.0)),
+            trade(TradeSide::Sell, 10, Some(2.0)),
+            trade(TradeSide::Sell, 4, None),
+        ];
+        let result = pnl(&trades, CostBasisMethod::Fifo, Some(2.0));
+
+        assert_eq!(result.unpriced_trades, 2);
+        assert_eq!((result.buys, result.sells), (2, 2));
+        assert_close(result.bought, 20.0);
+        assert_close(result.sold, 14.0);
+        assert_close(result.realized_pnl_usd, 10.0);
+        assert_close(result.position, 0.0);
+        assert_close(result.unmatched_sold, 0.0);
+    }
+}
@@ -0,0 +1,20 @@
+use crate::models::DeployedToken;
+
+/// Liquidity below which a token counts as abandoned or rugged
+pub const MIN_LIVE_LIQUIDITY_USD: f64 = 1_000.0;
+/// Deployments tolerated before each further one costs score
+const SERIAL_DEPLOY_THRESHOLD: usize = 5;
+const SERIAL_DEPLOY_PENALTY: f64 = 2.0;
+
+/// Score a deployer from 0 to 100: the share of its analyzed `tokens` that
+/// still have liquidity, less a penalty for every one of its `deployed` other
+/// tokens beyond the fifth. `None` when there is nothing to judge.
+pub fn reputation_score(tokens: &[DeployedToken], deployed: usize) -> Option<f64> {
+    if tokens.is_empty() {
+        return None;
+    }
+
+    let alive = tokens.iter().filter(|t| t.alive).count();
+    let penalty = deployed.saturating_sub(SERIAL_DEPLOY_THRESHOLD) as f64 * SERIAL_DEPLOY_PENALTY;
+    Some((alive as f64 / tokens.len() as f64 * 100.0 - penalty).clamp(0.0, 100.0))
+}
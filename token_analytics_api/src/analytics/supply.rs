@@ -0,0 +1,65 @@
+use crate::models::{SupplyChange, Transfer, TransferKind};
+
+use super::parse_amount;
+
+/// Sum mints and burns over trailing windows ending at `now`.
+///
+/// `transfers` must cover the token's full history so the supply at the
+/// start of each window can be derived for `net_change_pct`.
+pub fn supply_changes(transfers: &[Transfer], now: i64, windows: &[i64]) -> Vec<SupplyChange> {
+    let current_supply = net_minted(transfers.iter());
+
+    windows
+        .iter()
+        .map(|&window| {
+            let since = now - window;
+            let mut minted: u128 = 0;
+            let mut burned: u128 = 0;
+            let mut mint_count = 0;
+            let mut burn_count = 0;
+
+            for transfer in transfers.iter().filter(|t| t.timestamp > since && t.timestamp <= now) {
+                let amount = parse_amount(&transfer.amount);
+                match transfer.kind {
+                    TransferKind::Mint => {
+                        minted = minted.saturating_add(amount);
+                        mint_count += 1;
+                    }
+                    TransferKind::Burn => {
+                        burned = burned.saturating_add(amount);
+                        burn_count += 1;
+                    }
+                    TransferKind::Transfer => {}
+                }
+            }
+
+            let net = minted as i128 - burned as i128;
+            let starting_supply = current_supply - net;
+            let net_change_pct = (starting_supply > 0)
+                .then(|| net as f64 / starting_supply as f64 * 100.0);
+
+            SupplyChange {
+                window,
+                minted: minted.to_string(),
+                burned: burned.to_string(),
+                net: net.to_string(),
+                mint_count,
+                burn_count,
+                net_change_pct,
+            }
+        })
+        .collect()
+}
+
+fn net_minted<'a>(transfers: impl Iterator<Item = &'a Transfer>) -> i128 {
+    transfers.fold(0i128, |supply, transfer| match transfer.kind {
+        TransferKind::Mint => supply.saturating_add(parse_amount(&transfer.amount) as i128),
+        TransferKind::Burn => supply.saturating_sub(parse_amount(&transfer.amount) as i128),
+        TransferKind::Transfer => supply,
+    })
+}
+
+/// Supply implied by a full transfer history (mints minus burns)
+pub fn current_supply(transfers: &[Transfer]) -> u128 {
+    net_minted(transfers.iter()).max(0) as u128
+}
@@ -0,0 +1,405 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::models::{Chain, Trade, Transfer, WashCluster, WashTrading};
+
+use super::{is_burn_address, normalize_address, parse_amount};
+
+/// Longest transfer cycle looked for, in addresses
+const MAX_CYCLE_LENGTH: usize = 4;
+/// Counterparties beyond which an address is treated as infrastructure
+/// (router, exchange) that relays flows between unrelated wallets
+const HUB_DEGREE: usize = 50;
+/// Edges followed per transfer while looking for a cycle back to its sender
+const MAX_SEARCH_STEPS: usize = 1_000;
+const MAX_CLUSTERS: usize = 100;
+
+/// Token flows between addresses, each node's outgoing edges oldest first
+struct FlowGraph {
+    nodes: Vec<String>,
+    index: HashMap<String, usize>,
+    edges: Vec<Vec<(usize, i64)>>,
+}
+
+impl FlowGraph {
+    fn new(chain: Chain, transfers: &[Transfer]) -> Self {
+        let mut graph = FlowGraph { nodes: Vec::new(), index: HashMap::new(), edges: Vec::new() };
+        for transfer in transfers {
+            if is_burn_address(&transfer.from) || is_burn_address(&transfer.to) {
+                continue;
+            }
+            let from = graph.node(normalize_address(chain, &transfer.from));
+            let to = graph.node(normalize_address(chain, &transfer.to));
+            if from != to {
+                graph.edges[from].push((to, transfer.timestamp));
+            }
+        }
+        for edges in &mut graph.edges {
+            edges.sort_by_key(|&(_, timestamp)| timestamp);
+        }
+        graph
+    }
+
+    fn node(&mut self, address: String) -> usize {
+        if let Some(&node) = self.index.get(&address) {
+            return node;
+        }
+        self.index.insert(address.clone(), self.nodes.len());
+        self.nodes.push(address);
+        self.edges.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    /// Distinct counterparties of each node, in either direction
+    fn degrees(&self) -> Vec<usize> {
+        let mut counterparties: Vec<HashSet<usize>> = vec![HashSet::new(); self.nodes.len()];
+        for (from, edges) in self.edges.iter().enumerate() {
+            for &(to, _) in edges {
+                counterparties[from].insert(to);
+                counterparties[to].insert(from);
+            }
+        }
+        counterparties.iter().map(HashSet::len).collect()
+    }
+
+    /// Extend `path` with transfers no older than `after` and no newer than
+    /// `until` until one returns to `path[0]`
+    fn close_cycle(&self, path: &mut Vec<usize>, after: i64, until: i64, steps: &mut usize) -> bool {
+        let edges = &self.edges[*path.last().unwrap()];
+        let first = edges.partition_point(|&(_, timestamp)| timestamp < after);
+        for &(to, timestamp) in &edges[first..] {
+            if timestamp > until {
+                break;
+            }
+            *steps += 1;
+            if *steps > MAX_SEARCH_STEPS {
+                return false;
+            }
+            if to == path[0] {
+                return true;
+            }
+            if path.len() < MAX_CYCLE_LENGTH && !path.contains(&to) {
+                path.push(to);
+                if self.close_cycle(path, timestamp, until, steps) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        false
+    }
+}
+
+fn find(parent: &mut [usize], node: usize) -> usize {
+    let mut root = node;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut node = node;
+    while parent[node] != root {
+        node = std::mem::replace(&mut parent[node], root);
+    }
+    root
+}
+
+/// Share of each trade's token amount offset by an opposite trade of the
+/// same entity within `window` seconds. `trades` must be in execution order.
+fn reversed_shares(trades: &[Trade], entities: &[String], window: i64) -> Vec<f64> {
+    let mut matched = vec![0.0; trades.len()];
+    // Unmatched remainders as `(trade, units)`, oldest first; all on one side
+    let mut open: HashMap<&str, VecDeque<(usize, f64)>> = HashMap::new();
+
+    for (i, trade) in trades.iter().enumerate() {
+        let lots = open.entry(entities[i].as_str()).or_default();
+        while lots.front().is_some_and(|&(lot, _)| trades[lot].timestamp < trade.timestamp - window) {
+            lots.pop_front();
+        }
+
+        let mut units = parse_amount(trade.token_amount()) as f64;
+        while units > 0.0 {
+            let Some(lot) = lots.front_mut() else { break };
+            if trades[lot.0].side == trade.side {
+                break;
+            }
+            let amount = units.min(lot.1);
+            matched[lot.0] += amount;
+            matched[i] += amount;
+            lot.1 -= amount;
+            units -= amount;
+            if lot.1 <= 0.0 {
+                lots.pop_front();
+            }
+        }
+        if units > 0.0 {
+            lots.push_back((i, units));
+        }
+    }
+
+    trades
+        .iter()
+        .zip(matched)
+        .map(|(trade, matched)| {
+            let units = parse_amount(trade.token_amount()) as f64;
+            if units > 0.0 { (matched / units).min(1.0) } else { 0.0 }
+        })
+        .collect()
+}
+
+/// Wash trading among the token's wallets. Transfers that return to their
+/// sender within `window` seconds, through at most three other addresses,
+/// form cycles; the wallets on a cycle are merged into one cluster, leaving
+/// out infrastructure (pools, routers, exchanges) that only relays tokens.
+/// A trade counts as wash volume to the extent its cluster, or its trader
+/// alone, reversed it within `window`. Only trades from `since` on are
+/// counted, though earlier ones may offset them. `transfers` and `trades`
+/// must be in execution order.
+pub fn wash_trading(
+    chain: Chain,
+    transfers: &[Transfer],
+    trades: &[Trade],
+    is_infrastructure: impl Fn(&str) -> bool,
+    since: i64,
+    window: i64,
+) -> WashTrading {
+    let graph = FlowGraph::new(chain, transfers);
+    let mergeable: Vec<bool> = graph
+        .degrees()
+        .into_iter()
+        .zip(&graph.nodes)
+        .map(|(degree, address)| degree <= HUB_DEGREE && !is_infrastructure(address))
+        .collect();
+
+    let mut parent: Vec<usize> = (0..graph.nodes.len()).collect();
+    let mut cycles = Vec::new();
+    for (from, edges) in graph.edges.iter().enumerate() {
+        for &(to, timestamp) in edges {
+            let mut path = vec![from, to];
+            if !graph.close_cycle(&mut path, timestamp, timestamp + window, &mut 0) {
+                continue;
+            }
+            let members: Vec<usize> = path.into_iter().filter(|&node| mergeable[node]).collect();
+            let Some(&first) = members.first() else { continue };
+            for &member in &members[1..] {
+                let (a, b) = (find(&mut parent, first), find(&mut parent, member));
+                parent[a] = b;
+            }
+            cycles.push(first);
+        }
+    }
+
+    // Each wallet trades as its cluster, or as itself when it is on no cycle
+    let mut entity = |address: &str| match graph.index.get(address) {
+        Some(&node) if mergeable[node] => graph.nodes[find(&mut parent, node)].clone(),
+        _ => address.to_string(),
+    };
+    let mut clusters: HashMap<String, WashCluster> = HashMap::new();
+    for node in cycles {
+        clusters.entry(entity(&graph.nodes[node])).or_default().cycle_count += 1;
+    }
+    let entities: Vec<String> = trades
+        .iter()
+        .map(|t| entity(&normalize_address(chain, &t.trader)))
+        .collect();
+    for (node, address) in graph.nodes.iter().enumerate() {
+        if mergeable[node] {
+            let root = graph.nodes[find(&mut parent, node)].clone();
+            if let Some(cluster) = clusters.get_mut(&root) {
+                cluster.wallets.push(address.clone());
+            }
+        }
+    }
+
+    let (mut volume, mut wash_volume) = (0.0, 0.0);
+    for ((trade, entity), share) in trades.iter().zip(&entities).zip(reversed_shares(trades, &entities, window)) {
+        if trade.timestamp < since {
+            continue;
+        }
+        let trade_volume = trade.volume_usd.unwrap_or_default();
+        volume += trade_volume;
+        wash_volume += trade_volume * share;
+        if share > 0.0 {
+            let cluster = clusters.entry(entity.clone()).or_default();
+            cluster.trade_count += 1;
+            cluster.wash_volume_usd += trade_volume * share;
+        }
+    }
+
+    let mut clusters: Vec<WashCluster> = clusters
+        .into_iter()
+        .filter(|(_, c)| c.cycle_count > 0 || c.trade_count > 0)
+        .map(|(entity, mut c)| {
+            if c.wallets.is_empty() {
+                c.wallets.push(entity);
+            }
+            c.wallets.sort();
+            c
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.wash_volume_usd
+            .total_cmp(&a.wash_volume_usd)
+            .then_with(|| b.cycle_count.cmp(&a.cycle_count))
+    });
+    let cluster_count = clusters.len() as u32;
+    clusters.truncate(MAX_CLUSTERS);
+
+    WashTrading {
+        window_seconds: window,
+        volume_24h: volume,
+        wash_volume_24h: wash_volume,
+        adjusted_volume_24h: volume - wash_volume,
+        wash_share: if volume > 0.0 { wash_volume / volume * 100.0 } else { 0.0 },
+        cluster_count,
+        clusters,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TradeSide, TransferKind};
+
+    const WINDOW: i64 = 60;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} differs from {}", actual, expected);
+    }
+
+    fn transfer(from: &str, to: &str, timestamp: i64) -> Transfer {
+        Transfer {
+            tx_hash: String::new(),
+            from: from.to_string(),
+            to: to.to_string(),
+            amount: "1".to_string(),
+            timestamp,
+            block_number: 0,
+            log_index: 0,
+            kind: TransferKind::Transfer,
+            chain: Chain::Ethereum,
+        }
+    }
+
+    /// A trade of `units` tokens worth $1 each
+    fn trade(trader: &str, side: TradeSide, units: u64, timestamp: i64) -> Trade {
+        let (amount_in, amount_out) = match side {
+            TradeSide::Buy => ("0".to_string(), units.to_string()),
+            TradeSide::Sell => (units.to_string(), "0".to_string()),
+        };
+        Trade {
+            tx_hash: String::new(),
+            block_number: 0,
+            log_index: 0,
+            timestamp,
+            pool: String::new(),
+            dex: String::new(),
+            side,
+            trader: trader.to_string(),
+            token_in: String::new(),
+            amount_in,
+            token_out: String::new(),
+            amount_out,
+            price_usd: Some(1.0),
+            volume_usd: Some(units as f64),
+        }
+    }
+
+    /// Whether the first transfer of `transfers` returns to its sender by `until`
+    fn closes(transfers: &[Transfer], until: i64) -> bool {
+        let graph = FlowGraph::new(Chain::Ethereum, transfers);
+        let (from, to) = (graph.index[&transfers[0].from], graph.index[&transfers[0].to]);
+        let mut path = vec![from, to];
+        graph.close_cycle(&mut path, transfers[0].timestamp, until, &mut 0)
+    }
+
+    fn wash(transfers: &[Transfer], trades: &[Trade]) -> WashTrading {
+        wash_trading(Chain::Ethereum, transfers, trades, |_| false, 0, WINDOW)
+    }
+
+    #[test]
+    fn two_cycle_closes_within_window() {
+        let transfers = [transfer("a", "b", 0), transfer("b", "a", 30)];
+
+        assert!(closes(&transfers, WINDOW));
+        assert!(!closes(&transfers, 20));
+    }
+
+    #[test]
+    fn three_cycle_closes_in_time_order() {
+        let transfers = [transfer("a", "b", 0), transfer("b", "c", 10), transfer("c", "a", 20)];
+
+        assert!(closes(&transfers, WINDOW));
+        assert!(!closes(&transfers, 15));
+        // A hop back in time does not continue the cycle
+        assert!(!closes(&[transfer("a", "b", 10), transfer("b", "c", 20), transfer("c", "a", 5)], WINDOW));
+    }
+
+    #[test]
+    fn cycles_merge_their_wallets() {
+        let two = wash(&[transfer("a", "b", 0), transfer("b", "a", 30)], &[]);
+        assert_eq!(two.cluster_count, 1);
+        assert_eq!(two.clusters[0].wallets, ["a", "b"]);
+        assert_eq!(two.clusters[0].cycle_count, 1);
+
+        let three = wash(&[transfer("a", "b", 0), transfer("b", "c", 10), transfer("c", "a", 20)], &[]);
+        assert_eq!(three.cluster_count, 1);
+        assert_eq!(three.clusters[0].wallets, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn cycles_outside_window_are_ignored() {
+        assert_eq!(wash(&[transfer("a", "b", 0), transfer("b", "a", WINDOW + 1)], &[]).cluster_count, 0);
+        let three = [transfer("a", "b", 0), transfer("b", "c", 40), transfer("c", "a", WINDOW + 1)];
+        assert_eq!(wash(&three, &[]).cluster_count, 0);
+    }
+
+    #[test]
+    fn hubs_are_not_merged() {
+        // The hub relays between more counterparties than a wallet would
+        let mut transfers = vec![transfer("a", "hub", 0), transfer("hub", "b", 10), transfer("b", "a", 20)];
+        transfers.extend((0..HUB_DEGREE).map(|i| transfer("hub", &format!("x{}", i), 1_000)));
+        let result = wash(&transfers, &[]);
+
+        assert_eq!(result.cluster_count, 1);
+        assert_eq!(result.clusters[0].wallets, ["a", "b"]);
+
+        // Listed infrastructure is left out the same way
+        let pool = [transfer("a", "pool", 0), transfer("pool", "a", 10)];
+        let result = wash_trading(Chain::Ethereum, &pool, &[], |address| address == "pool", 0, WINDOW);
+        assert_eq!(result.clusters[0].wallets, ["a"]);
+    }
+
+    #[test]
+    fn self_trades_are_reversed_within_window() {
+        let trades = [
+            trade("t", TradeSide::Buy, 100, 0),
+            trade("t", TradeSide::Sell, 60, 30),
+            trade("u", TradeSide::Buy, 50, 40),
+            // Too late to offset the buy
+            trade("t", TradeSide::Sell, 40, 30 + WINDOW + 1),
+        ];
+        let entities: Vec<String> = trades.iter().map(|t| t.trader.clone()).collect();
+        let shares = reversed_shares(&trades, &entities, WINDOW);
+
+        for (share, expected) in shares.into_iter().zip([0.6, 1.0, 0.0, 0.0]) {
+            assert_close(share, expected);
+        }
+
+        let result = wash(&[], &trades);
+        assert_close(result.volume_24h, 250.0);
+        assert_close(result.wash_volume_24h, 120.0);
+        assert_close(result.adjusted_volume_24h, 130.0);
+        assert_eq!(result.cluster_count, 1);
+        assert_eq!(result.clusters[0].wallets, ["t"]);
+        assert_eq!(result.clusters[0].trade_count, 2);
+    }
+
+    #[test]
+    fn cluster_trades_offset_each_other() {
+        let transfers = [transfer("a", "b", 0), transfer("b", "a", 10)];
+        let trades = [trade("a", TradeSide::Buy, 10, 20), trade("b", TradeSide::Sell, 10, 30)];
+        let result = wash(&transfers, &trades);
+
+        assert_close(result.wash_share, 100.0);
+        assert_eq!(result.clusters[0].wallets, ["a", "b"]);
+        assert_eq!(result.clusters[0].trade_count, 2);
+    }
+}
@@ -34,18 +34,173 @@ pub async fn cache_get(client: &RedisPool, key: &str) -> Result<Option<String>>
     Ok(value)
 }
 
-pub async fn invalidate_cache(client: &RedisPool, pattern: &str) -> Result<()> {
+/// Fetch many keys in one `MGET`; the result lines up with `keys`
+pub async fn cache_get_many(client: &RedisPool, keys: &[String]) -> Result<Vec<Option<String>>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
     let mut conn = client.get_async_connection().await?;
-    let keys: Vec<String> = conn.keys(pattern).await?;
-    
-    if !keys.is_empty() {
-        // Convert Vec<String> to Vec<&str> for redis command
-        let key_refs: Vec<&str> = keys.iter().map(|s| s.as_str()).collect();
-        let _: i64 = redis::cmd("DEL")
-            .arg(key_refs)
+    // A single key would go out as GET, which replies with a bare value
+    let values: Vec<Option<String>> = redis::cmd("MGET").arg(keys).query_async(&mut conn).await?;
+    Ok(values)
+}
+
+/// Set many keys with the same TTL in one pipeline
+pub async fn cache_set_many(client: &RedisPool, entries: &[(String, String)], ttl_seconds: u64) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut conn = client.get_async_connection().await?;
+    let mut pipe = redis::pipe();
+    for (key, value) in entries {
+        if ttl_seconds > 0 {
+            pipe.set_ex(key, value, ttl_seconds).ignore();
+        } else {
+            pipe.set(key, value).ignore();
+        }
+    }
+    pipe.query_async::<_, ()>(&mut conn).await?;
+    Ok(())
+}
+
+pub async fn cache_delete(client: &RedisPool, key: &str) -> Result<()> {
+    let mut conn = client.get_async_connection().await?;
+    conn.del::<_, ()>(key).await?;
+    Ok(())
+}
+
+pub async fn sorted_set_add(client: &RedisPool, key: &str, members: &[(i64, String)]) -> Result<()> {
+    if members.is_empty() {
+        return Ok(());
+    }
+
+    let mut conn = client.get_async_connection().await?;
+    conn.zadd_multiple::<_, _, _, ()>(key, members).await?;
+    Ok(())
+}
+
+pub async fn sorted_set_range(client: &RedisPool, key: &str, min: i64, max: i64) -> Result<Vec<String>> {
+    let mut conn = client.get_async_connection().await?;
+    let members: Vec<String> = conn.zrangebyscore(key, min, max).await?;
+    Ok(members)
+}
+
+pub async fn sorted_set_len(client: &RedisPool, key: &str) -> Result<u64> {
+    let mut conn = client.get_async_connection().await?;
+    let len: u64 = conn.zcard(key).await?;
+    Ok(len)
+}
+
+pub async fn sorted_set_remove_range(client: &RedisPool, key: &str, min: i64, max: i64) -> Result<()> {
+    let mut conn = client.get_async_connection().await?;
+    conn.zrembyscore::<_, _, _, ()>(key, min, max).await?;
+    Ok(())
+}
+
+pub async fn set_add(client: &RedisPool, key: &str, member: &str) -> Result<()> {
+    let mut conn = client.get_async_connection().await?;
+    conn.sadd::<_, _, ()>(key, member).await?;
+    Ok(())
+}
+
+/// Add many members to a set in one `SADD`
+pub async fn set_add_many(client: &RedisPool, key: &str, members: &[String]) -> Result<()> {
+    if members.is_empty() {
+        return Ok(());
+    }
+
+    let mut conn = client.get_async_connection().await?;
+    conn.sadd::<_, _, ()>(key, members).await?;
+    Ok(())
+}
+
+pub async fn set_members(client: &RedisPool, key: &str) -> Result<Vec<String>> {
+    let mut conn = client.get_async_connection().await?;
+    let members: Vec<String> = conn.smembers(key).await?;
+    Ok(members)
+}
+
+pub async fn counter_incr(client: &RedisPool, key: &str) -> Result<u64> {
+    let mut conn = client.get_async_connection().await?;
+    let value: u64 = conn.incr(key, 1).await?;
+    Ok(value)
+}
+
+pub async fn hash_set(client: &RedisPool, key: &str, field: &str, value: &str) -> Result<()> {
+    let mut conn = client.get_async_connection().await?;
+    conn.hset::<_, _, _, ()>(key, field, value).await?;
+    Ok(())
+}
+
+pub async fn hash_get(client: &RedisPool, key: &str, field: &str) -> Result<Option<String>> {
+    let mut conn = client.get_async_connection().await?;
+    let value: Option<String> = conn.hget(key, field).await?;
+    Ok(value)
+}
+
+pub async fn hash_delete(client: &RedisPool, key: &str, field: &str) -> Result<bool> {
+    let mut conn = client.get_async_connection().await?;
+    let removed: i64 = conn.hdel(key, field).await?;
+    Ok(removed > 0)
+}
+
+/// Replace a hash field with `update(current value)` atomically: the field
+/// is read under WATCH and written in MULTI/EXEC, and the update is retried
+/// if another client changed the hash in between
+pub async fn hash_update(
+    client: &RedisPool,
+    key: &str,
+    field: &str,
+    mut update: impl FnMut(Option<String>) -> Result<String>,
+) -> Result<String> {
+    let mut conn = client.get_async_connection().await?;
+    loop {
+        redis::cmd("WATCH").arg(key).query_async::<_, ()>(&mut conn).await?;
+        let current: Option<String> = conn.hget(key, field).await?;
+        let value = update(current)?;
+
+        let committed: Option<()> = redis::pipe()
+            .atomic()
+            .hset(key, field, &value)
+            .ignore()
             .query_async(&mut conn)
             .await?;
+        if committed.is_some() {
+            return Ok(value);
+        }
     }
-    
+}
+
+pub async fn hash_values(client: &RedisPool, key: &str) -> Result<Vec<String>> {
+    let mut conn = client.get_async_connection().await?;
+    let values: Vec<String> = conn.hvals(key).await?;
+    Ok(values)
+}
+
+/// Push to the head of a list, keeping at most `max_len` entries
+pub async fn list_push_capped(client: &RedisPool, key: &str, value: &str, max_len: usize) -> Result<()> {
+    let mut conn = client.get_async_connection().await?;
+    conn.lpush::<_, _, ()>(key, value).await?;
+    conn.ltrim::<_, ()>(key, 0, max_len as isize - 1).await?;
     Ok(())
 }
+
+pub async fn list_range(client: &RedisPool, key: &str, start: isize, stop: isize) -> Result<Vec<String>> {
+    let mut conn = client.get_async_connection().await?;
+    let values: Vec<String> = conn.lrange(key, start, stop).await?;
+    Ok(values)
+}
+
+pub async fn list_remove(client: &RedisPool, key: &str, value: &str) -> Result<bool> {
+    let mut conn = client.get_async_connection().await?;
+    let removed: i64 = conn.lrem(key, 1, value).await?;
+    Ok(removed > 0)
+}
+
+pub async fn set_remove(client: &RedisPool, key: &str, member: &str) -> Result<bool> {
+    let mut conn = client.get_async_connection().await?;
+    let removed: i64 = conn.srem(key, member).await?;
+    Ok(removed > 0)
+}
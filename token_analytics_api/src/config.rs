@@ -1,13 +1,48 @@
 use std::env;
 use std::net::SocketAddr;
 
+/// LP locker contracts on Ethereum: Unicrypt V2, Team Finance and PinkLock V2
+const DEFAULT_LP_LOCKERS: [&str; 3] = [
+    "0x663A5C229c09b049E36dCc11a9B0d4a8Eb9db214",
+    "0xE2fE530C047f2d85298b07D9333C05737f1435fB",
+    "0x71B5759d73262FBb223956913ecF4ecC51057641",
+];
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub redis_url: String,
     pub server_addr: SocketAddr,
     pub eth_rpc_url: String,
     pub sol_rpc_url: String,
+    /// WebSocket endpoints feeding GraphQL subscriptions
+    pub eth_ws_url: Option<String>,
+    pub sol_ws_url: Option<String>,
     pub cache_ttl: u64, // in seconds
+    pub index_start_block: u64,
+    pub eth_log_chunk_size: u64,
+    /// Blocks one Ethereum index sync scans; longer backfills resume on the next sync
+    pub eth_sync_block_limit: u64,
+    pub sol_signature_limit: usize,
+    /// Solana token accounts whose signatures one transfer sync follows
+    pub sol_account_sync_limit: usize,
+    pub allowance_verify_limit: usize,
+    /// Addresses treated as non-circulating (pools, bridges, vaults) by analytics
+    pub known_contracts: Vec<String>,
+    pub labels_path: Option<String>,
+    pub alert_poll_seconds: u64,
+    pub webhook_max_attempts: u32,
+    pub webhook_backoff_ms: u64,
+    pub webhook_timeout_ms: u64,
+    /// Keys accepted for mutations; mutations are disabled when empty
+    pub api_keys: Vec<String>,
+    /// ERC-20 tokens always checked by wallet queries, on top of tracked tokens
+    pub wallet_tokens: Vec<String>,
+    /// Contracts whose LP token balances count as locked liquidity
+    pub lp_lockers: Vec<String>,
+    /// Nonces (Ethereum) or transactions (Solana) scanned for a deployer's other tokens
+    pub deployer_history_limit: usize,
+    /// Largest early buyers whose funding source is traced by launch analysis
+    pub launch_funder_limit: usize,
 }
 
 impl Config {
@@ -24,10 +59,85 @@ impl Config {
             server_addr: format!("{}:{}", server_host, server_port).parse().unwrap(),
             eth_rpc_url: env::var("ETH_RPC_URL").expect("ETH_RPC_URL must be set"),
             sol_rpc_url: env::var("SOL_RPC_URL").expect("SOL_RPC_URL must be set"),
+            eth_ws_url: env::var("ETH_WS_URL").ok(),
+            sol_ws_url: env::var("SOL_WS_URL").ok(),
             cache_ttl: env::var("CACHE_TTL_SECONDS")
                 .unwrap_or_else(|_| "300".to_string())
                 .parse()
                 .unwrap_or(300),
+            index_start_block: env::var("INDEX_START_BLOCK")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            eth_log_chunk_size: env::var("ETH_LOG_CHUNK_SIZE")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            eth_sync_block_limit: env::var("ETH_SYNC_BLOCK_LIMIT")
+                .unwrap_or_else(|_| "200000".to_string())
+                .parse()
+                .unwrap_or(200_000),
+            sol_signature_limit: env::var("SOL_SIGNATURE_LIMIT")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+            sol_account_sync_limit: env::var("SOL_ACCOUNT_SYNC_LIMIT")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            allowance_verify_limit: env::var("ALLOWANCE_VERIFY_LIMIT")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200),
+            known_contracts: env::var("KNOWN_CONTRACTS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect(),
+            labels_path: env::var("LABELS_PATH").ok(),
+            alert_poll_seconds: env::var("ALERT_POLL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            webhook_max_attempts: env::var("WEBHOOK_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            webhook_backoff_ms: env::var("WEBHOOK_BACKOFF_MS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500),
+            webhook_timeout_ms: env::var("WEBHOOK_TIMEOUT_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            api_keys: env::var("API_KEYS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect(),
+            wallet_tokens: env::var("WALLET_TOKENS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect(),
+            lp_lockers: env::var("LP_LOCKERS")
+                .unwrap_or_else(|_| DEFAULT_LP_LOCKERS.join(","))
+                .split(',')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect(),
+            deployer_history_limit: env::var("DEPLOYER_HISTORY_LIMIT")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200),
+            launch_funder_limit: env::var("LAUNCH_FUNDER_LIMIT")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50),
         })
     }
 }
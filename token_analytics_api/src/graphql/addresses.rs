@@ -0,0 +1,36 @@
+//! Field resolvers that enrich holder and transfer addresses
+
+use async_graphql::{dataloader::DataLoader, ComplexObject, Context, FieldResult};
+
+use super::loaders::HolderKindLoader;
+use crate::labels::{AddressLabel, LabelRegistry};
+use crate::models::{Holder, HolderKind, Transfer};
+
+#[ComplexObject]
+impl Holder {
+    /// Known labels for the holder address
+    async fn labels(&self, ctx: &Context<'_>) -> FieldResult<Vec<AddressLabel>> {
+        Ok(ctx.data::<LabelRegistry>()?.lookup(&self.address, Some(self.chain)))
+    }
+
+    /// Whether the holder is a wallet, multisig, contract or program-owned account
+    async fn kind(&self, ctx: &Context<'_>) -> FieldResult<HolderKind> {
+        let loader = ctx.data::<DataLoader<HolderKindLoader>>()?;
+        Ok(loader.load_one(self.address.clone()).await?.unwrap_or(HolderKind::Unknown))
+    }
+}
+
+#[ComplexObject]
+impl Transfer {
+    /// Known labels for the sender
+    #[graphql(name = "fromLabels")]
+    async fn sender_labels(&self, ctx: &Context<'_>) -> FieldResult<Vec<AddressLabel>> {
+        Ok(ctx.data::<LabelRegistry>()?.lookup(&self.from, Some(self.chain)))
+    }
+
+    /// Known labels for the recipient
+    #[graphql(name = "toLabels")]
+    async fn recipient_labels(&self, ctx: &Context<'_>) -> FieldResult<Vec<AddressLabel>> {
+        Ok(ctx.data::<LabelRegistry>()?.lookup(&self.to, Some(self.chain)))
+    }
+}
@@ -0,0 +1,39 @@
+//! API key authentication for mutations and webhook queries
+
+use async_graphql::{Context, Guard, Result};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::config::Config;
+
+/// API key sent by the client, extracted from `Authorization: Bearer <key>`
+/// or `X-Api-Key` and attached to each request
+#[derive(Debug, Clone)]
+pub struct ApiKey(pub String);
+
+/// Allows a field only when the request carries one of the configured `API_KEYS`.
+/// With no keys configured the guarded fields are disabled entirely.
+pub struct ApiKeyGuard;
+
+#[async_trait::async_trait]
+impl Guard for ApiKeyGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        let config = ctx.data::<Config>()?;
+        if config.api_keys.is_empty() {
+            return Err("Mutations and webhook queries are disabled: no API_KEYS configured".into());
+        }
+
+        // Compare in constant time so response timing does not leak key prefixes
+        let matches = |key: &str| {
+            config
+                .api_keys
+                .iter()
+                .fold(Choice::from(0), |found, k| found | k.as_bytes().ct_eq(key.as_bytes()))
+                .into()
+        };
+        match ctx.data_opt::<ApiKey>() {
+            Some(ApiKey(key)) if matches(key) => Ok(()),
+            Some(_) => Err("Invalid API key".into()),
+            None => Err("Missing API key".into()),
+        }
+    }
+}
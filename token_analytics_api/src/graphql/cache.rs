@@ -1,11 +1,11 @@
 use async_graphql::Result;
 use serde_json;
-use crate::models::{Token, TokenAnalytics};
-use crate::cache::{cache_get, cache_set, RedisPool};
+use crate::models::{Chain, LiquidityLock, LiquidityPool};
+use crate::cache::{cache_delete, cache_get, cache_set, RedisPool};
 
 const CACHE_TTL: u64 = 300; // 5 minutes
 
-pub async fn get_token(redis: &RedisPool, key: &str) -> Result<Option<Token>> {
+pub async fn get_pools(redis: &RedisPool, key: &str) -> Result<Option<Vec<LiquidityPool>>> {
     if let Some(cached) = cache_get(redis, key).await? {
         Ok(serde_json::from_str(&cached)?)
     } else {
@@ -13,13 +13,17 @@ pub async fn get_token(redis: &RedisPool, key: &str) -> Result<Option<Token>> {
     }
 }
 
-pub async fn cache_token(redis: &RedisPool, key: &str, token: &Token) -> Result<()> {
-    let serialized = serde_json::to_string(token)?;
+pub async fn cache_pools(redis: &RedisPool, key: &str, pools: &[LiquidityPool]) -> Result<()> {
+    let serialized = serde_json::to_string(pools)?;
     cache_set(redis, key, &serialized, CACHE_TTL).await?;
     Ok(())
 }
 
-pub async fn get_analytics(redis: &RedisPool, key: &str) -> Result<Option<TokenAnalytics>> {
+pub fn lp_lock_cache_key(chain: Chain, pool: &str) -> String {
+    format!("lplock:{}:{}", chain, pool)
+}
+
+pub async fn get_lock(redis: &RedisPool, key: &str) -> Result<Option<LiquidityLock>> {
     if let Some(cached) = cache_get(redis, key).await? {
         Ok(serde_json::from_str(&cached)?)
     } else {
@@ -27,14 +31,14 @@ pub async fn get_analytics(redis: &RedisPool, key: &str) -> Result<Option<TokenA
     }
 }
 
-pub async fn cache_analytics(redis: &RedisPool, key: &str, analytics: &TokenAnalytics) -> Result<()> {
-    let serialized = serde_json::to_string(analytics)?;
+pub async fn cache_lock(redis: &RedisPool, key: &str, lock: &LiquidityLock) -> Result<()> {
+    let serialized = serde_json::to_string(lock)?;
     cache_set(redis, key, &serialized, CACHE_TTL).await?;
     Ok(())
 }
 
 pub async fn invalidate(redis: &RedisPool, key: &str) -> Result<()> {
-    // Invalidate by setting a very short TTL
-    cache_set(redis, key, "", 1).await?;
+    // Delete outright; an empty placeholder would fail to deserialize on read
+    cache_delete(redis, key).await?;
     Ok(())
 }
@@ -0,0 +1,209 @@
+//! Field resolvers for token deployment info
+
+use std::collections::HashSet;
+
+use async_graphql::{dataloader::DataLoader, ComplexObject, Context, FieldResult};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::loaders::{DeploymentKey, TokenKey, TokenLoader};
+use super::resolvers::token::get_liquidity_pools;
+use crate::analytics::reputation::{reputation_score, MIN_LIVE_LIQUIDITY_USD};
+use crate::analytics::{is_burn_address, normalize_address};
+use crate::cache::{cache_get, cache_set, RedisPool};
+use crate::config::Config;
+use crate::indexer;
+use crate::models::{Chain, DeployedToken, DeployerHistory, Deployment, Token};
+use crate::rpc::{EthereumClient, SolanaClient};
+
+impl Token {
+    async fn load_deployment(&self, ctx: &Context<'_>) -> FieldResult<Option<Deployment>> {
+        let chain: Chain = self.chain.parse()?;
+        let loader = ctx.data::<DataLoader<TokenLoader>>()?;
+        Ok(loader.load_one(DeploymentKey(chain, self.address.clone())).await?.transpose()?)
+    }
+}
+
+/// Most recent tokens of a deployer analyzed for liquidity and holders
+const MAX_ANALYZED_TOKENS: usize = 20;
+/// Tokens analyzed at once
+const ANALYSIS_CONCURRENCY: usize = 4;
+
+/// Tokens of one deployer, shared by every token it created
+pub fn deployer_cache_key(chain: Chain, creator: &str) -> String {
+    format!("deployer:{}:{}", chain, normalize_address(chain, creator))
+}
+
+/// A deployer's tokens, newest first, cached for every token it created
+#[derive(Serialize, Deserialize)]
+struct DeployerTokens {
+    tokens: Vec<String>,
+    analyzed: Vec<DeployedToken>,
+}
+
+/// Current liquidity and holder count of a token from the deployer's history
+async fn deployed_token(ctx: &Context<'_>, chain: Chain, token: &Token) -> FieldResult<DeployedToken> {
+    let liquidity_usd: f64 = get_liquidity_pools(ctx, &token.address, chain)
+        .await?
+        .iter()
+        .map(|p| p.reserve_usd)
+        .sum();
+
+    Ok(DeployedToken {
+        address: token.address.clone(),
+        name: token.name.clone(),
+        symbol: token.symbol.clone(),
+        liquidity_usd,
+        holder_count: holder_count(ctx, chain, &token.address).await?,
+        alive: liquidity_usd >= MIN_LIVE_LIQUIDITY_USD,
+    })
+}
+
+/// Solana counts token accounts directly. EVM chains only read a transfer
+/// index that is already complete: syncing one for every deployed token
+/// would be too slow for a single request.
+async fn holder_count(ctx: &Context<'_>, chain: Chain, address: &str) -> FieldResult<Option<u32>> {
+    let config = ctx.data::<Config>()?;
+    if chain == Chain::Solana {
+        return Ok(Some(SolanaClient::new(&config.sol_rpc_url).get_holder_count(address).await?));
+    }
+
+    let redis = ctx.data::<RedisPool>()?;
+    if !indexer::transfers_complete(redis, chain, address).await? {
+        return Ok(None);
+    }
+    let state = indexer::load_holder_state(redis, chain, address, config.cache_ttl).await?;
+    Ok(Some(
+        state
+            .balances
+            .iter()
+            .filter(|(holder, _)| !is_burn_address(holder))
+            .count() as u32,
+    ))
+}
+
+#[ComplexObject]
+impl Token {
+    /// The transaction that created the token, when it can be found
+    async fn deployment(&self, ctx: &Context<'_>) -> FieldResult<Option<Deployment>> {
+        self.load_deployment(ctx).await
+    }
+
+    /// Account that deployed the contract or created the mint
+    async fn creator(&self, ctx: &Context<'_>) -> FieldResult<Option<String>> {
+        Ok(self.load_deployment(ctx).await?.map(|d| d.creator))
+    }
+
+    /// Unix timestamp of the creating transaction
+    async fn created_at(&self, ctx: &Context<'_>) -> FieldResult<Option<i64>> {
+        Ok(self.load_deployment(ctx).await?.map(|d| d.timestamp))
+    }
+
+    /// Seconds since the token was created
+    async fn age_seconds(&self, ctx: &Context<'_>) -> FieldResult<Option<i64>> {
+        let now = chrono::Utc::now().timestamp();
+        Ok(self.load_deployment(ctx).await?.map(|d| now - d.timestamp))
+    }
+
+    /// Other tokens created by the same deployer, with their remaining
+    /// liquidity and holders, and a reputation score derived from them
+    async fn deployer_history(&self, ctx: &Context<'_>) -> FieldResult<Option<DeployerHistory>> {
+        let chain: Chain = self.chain.parse()?;
+        let Some(deployment) = self.load_deployment(ctx).await? else {
+            return Ok(None);
+        };
+        let redis = ctx.data::<RedisPool>()?;
+        let config = ctx.data::<Config>()?;
+
+        let cache_key = deployer_cache_key(chain, &deployment.creator);
+        let deployed = match cache_get(redis, &cache_key).await? {
+            Some(cached) => serde_json::from_str::<DeployerTokens>(&cached)?,
+            None => {
+                let deployed = deployer_tokens(ctx, chain, &deployment.creator).await?;
+                cache_set(redis, &cache_key, &serde_json::to_string(&deployed)?, config.cache_ttl).await?;
+                deployed
+            }
+        };
+
+        // The cache covers every token of the deployer, the queried one included
+        let this = normalize_address(chain, &self.address);
+        let token_count = deployed.tokens.iter().filter(|a| normalize_address(chain, a) != this).count();
+        let tokens: Vec<DeployedToken> = deployed
+            .analyzed
+            .into_iter()
+            .filter(|t| normalize_address(chain, &t.address) != this)
+            .take(MAX_ANALYZED_TOKENS)
+            .collect();
+
+        Ok(Some(DeployerHistory {
+            deployer: deployment.creator,
+            chain,
+            dead_count: tokens.iter().filter(|t| !t.alive).count() as u32,
+            reputation_score: reputation_score(&tokens, token_count),
+            token_count: token_count as u32,
+            tokens,
+        }))
+    }
+}
+
+/// Every token `deployer` created, newest first, with the most recent ones
+/// analyzed. Tokens whose analysis fails are logged and left out.
+async fn deployer_tokens(ctx: &Context<'_>, chain: Chain, deployer: &str) -> FieldResult<DeployerTokens> {
+    let config = ctx.data::<Config>()?;
+    let created: Vec<String> = match chain {
+        // Derived in nonce order, oldest first
+        Chain::Ethereum => EthereumClient::new(&config.eth_rpc_url)
+            .await?
+            .get_created_contracts(deployer, config.deployer_history_limit as u64)
+            .await?
+            .iter()
+            .rev()
+            .map(|a| format!("{:?}", a))
+            .collect(),
+        Chain::Solana => SolanaClient::new(&config.sol_rpc_url)
+            .get_created_mints(deployer, config.deployer_history_limit)
+            .await?,
+        Chain::Bsc | Chain::Other => Vec::new(),
+    };
+
+    let mut seen = HashSet::new();
+    let created: Vec<String> = created
+        .into_iter()
+        .filter(|a| seen.insert(normalize_address(chain, a)))
+        .collect();
+
+    // Contracts that are not tokens drop out here
+    let loader = ctx.data::<DataLoader<TokenLoader>>()?;
+    let tokens = loader
+        .load_many(created.iter().map(|a| TokenKey(chain, a.clone())))
+        .await?;
+    let tokens: Vec<&Token> = created
+        .iter()
+        .filter_map(|a| tokens.get(&TokenKey(chain, a.clone())))
+        .collect();
+
+    // One extra, in case the queried token is among the most recent
+    let lookups: Vec<_> = tokens
+        .iter()
+        .take(MAX_ANALYZED_TOKENS + 1)
+        .map(|token| deployed_token(ctx, chain, token))
+        .collect();
+    let results: Vec<_> = stream::iter(lookups).buffered(ANALYSIS_CONCURRENCY).collect().await;
+    let analyzed = tokens
+        .iter()
+        .zip(results)
+        .filter_map(|(token, result)| match result {
+            Ok(deployed) => Some(deployed),
+            Err(e) => {
+                warn!("Skipping deployed token {} on {}: {}", token.address, chain, e.message);
+                None
+            }
+        })
+        .collect();
+
+    Ok(DeployerTokens {
+        tokens: tokens.iter().map(|t| t.address.clone()).collect(),
+        analyzed,
+    })
+}
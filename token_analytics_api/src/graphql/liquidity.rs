@@ -0,0 +1,70 @@
+//! Field resolvers for liquidity pools
+
+use std::collections::HashSet;
+
+use async_graphql::{ComplexObject, Context, FieldResult};
+use tracing::debug;
+
+use crate::analytics::liquidity::liquidity_lock;
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::graphql::cache as pool_cache;
+use crate::indexer;
+use crate::models::{Chain, LiquidityLock, LiquidityPool};
+use crate::rpc::EthereumClient;
+
+impl LiquidityPool {
+    /// LP ownership of a Uniswap V2 pair, cached like the pool list and
+    /// computed at most once per query. Other pool types have no fungible LP
+    /// token to analyze, and a pair whose LP history is still being indexed
+    /// has none yet.
+    async fn lp_lock(&self, ctx: &Context<'_>) -> FieldResult<Option<LiquidityLock>> {
+        let lock = self.lock.get_or_try_init(|| self.load_lp_lock(ctx)).await?;
+        Ok(lock.clone())
+    }
+
+    async fn load_lp_lock(&self, ctx: &Context<'_>) -> FieldResult<Option<LiquidityLock>> {
+        if self.chain != Chain::Ethereum || self.exchange != "uniswap_v2" {
+            return Ok(None);
+        }
+
+        let redis = ctx.data::<RedisPool>()?;
+        let config = ctx.data::<Config>()?;
+        let cache_key = pool_cache::lp_lock_cache_key(self.chain, &self.address);
+        if let Some(lock) = pool_cache::get_lock(redis, &cache_key).await? {
+            return Ok(Some(lock));
+        }
+
+        // The pair contract is itself the LP token. One sync scans at most
+        // `eth_sync_block_limit` blocks, so an old pair catches up over
+        // several requests rather than within this one.
+        indexer::sync_transfers(redis, config, self.chain, &self.address).await?;
+        if !indexer::transfers_complete(redis, self.chain, &self.address).await? {
+            debug!("LP transfers of {} are still being indexed", self.address);
+            return Ok(None);
+        }
+        let transfers = indexer::load_complete_transfers(redis, self.chain, &self.address).await?;
+        let client = EthereumClient::new(&config.eth_rpc_url).await?;
+        let total_supply = client.get_token_supply(&self.address).await?;
+        let lockers: HashSet<String> = config.lp_lockers.iter().map(|a| a.to_lowercase()).collect();
+
+        let lock = liquidity_lock(&transfers, &self.address.to_lowercase(), total_supply.low_u128(), &lockers);
+        pool_cache::cache_lock(redis, &cache_key, &lock).await?;
+        Ok(Some(lock))
+    }
+}
+
+#[ComplexObject]
+impl LiquidityPool {
+    /// Who holds the pool's LP tokens: burned, locked, deployer and the rest.
+    /// Only Uniswap V2 pairs are analyzed; other pools, and pairs whose LP
+    /// history is still being indexed, return null.
+    async fn liquidity_lock(&self, ctx: &Context<'_>) -> FieldResult<Option<LiquidityLock>> {
+        self.lp_lock(ctx).await
+    }
+
+    /// Percent of LP tokens burned or held by lockers
+    async fn lock_percentage(&self, ctx: &Context<'_>) -> FieldResult<Option<f64>> {
+        Ok(self.lp_lock(ctx).await?.map(|lock| lock.lock_percentage))
+    }
+}
@@ -0,0 +1,560 @@
+//! DataLoaders that coalesce per-field lookups into batched RPC calls
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_graphql::dataloader::Loader;
+use futures::future::join_all;
+use serde::{de::DeserializeOwned, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use tracing::warn;
+use web3::types::{Address, H160, U256};
+
+use crate::cache::{cache_get_many, cache_set_many, RedisPool};
+use crate::config::Config;
+use crate::models::{Chain, Deployment, HolderKind, Token};
+use crate::rpc::solana::HistoryTruncated;
+use crate::rpc::{EthereumClient, SolanaClient};
+use super::resolvers::token::token_cache_key;
+
+const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+
+/// Gnosis Safe singletons (v1.1.1, v1.2.0, v1.3.0, v1.3.0 L2, v1.4.1, v1.4.1 L2).
+/// Safe proxies keep their singleton address in storage slot 0.
+const SAFE_SINGLETONS: [&str; 6] = [
+    "0x34cfac646f301356faa8b21e94227e3583fe3f5f",
+    "0x6851d6fdfafd08c0295c392436245e5bc78b0185",
+    "0xd9db270c1b5e3bd161e8c8503c55ceabee709552",
+    "0x3e5c63644e683549055b9be8653de26e0b4cd36e",
+    "0x41675c099f32341bf84bfc5382af534df5c7461a",
+    "0x29fcb43b46531bca003ddc8fcb67ffe91900c762",
+];
+
+/// Classifies holder addresses as wallets, multisigs, contracts or program-owned
+/// accounts. EVM (`0x`) addresses are checked with batched `eth_getCode`,
+/// everything else with Solana `getMultipleAccounts`.
+pub struct HolderKindLoader {
+    config: Config,
+}
+
+impl HolderKindLoader {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    async fn load_evm(&self, keys: &[&String]) -> anyhow::Result<HashMap<String, HolderKind>> {
+        let client = EthereumClient::new(&self.config.eth_rpc_url).await?;
+        let addresses = keys
+            .iter()
+            .map(|k| k.parse::<Address>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let codes = client.get_codes(&addresses).await?;
+        let contracts: Vec<Address> = addresses
+            .iter()
+            .zip(&codes)
+            .filter(|(_, code)| !code.is_empty())
+            .map(|(address, _)| *address)
+            .collect();
+        let singletons = client.get_storage_slots(&contracts, U256::zero()).await?;
+        let safes: Vec<Address> = contracts
+            .iter()
+            .zip(&singletons)
+            .filter(|(_, slot)| {
+                let singleton = format!("{:?}", H160::from_slice(&slot.as_bytes()[12..]));
+                SAFE_SINGLETONS.contains(&singleton.as_str())
+            })
+            .map(|(address, _)| *address)
+            .collect();
+
+        Ok(keys
+            .iter()
+            .zip(addresses.iter().zip(&codes))
+            .map(|(key, (address, code))| {
+                let kind = if code.is_empty() {
+                    HolderKind::Wallet
+                } else if safes.contains(address) {
+                    HolderKind::Multisig
+                } else {
+                    HolderKind::Contract
+                };
+                ((*key).clone(), kind)
+            })
+            .collect())
+    }
+
+    async fn load_solana(&self, keys: &[&String]) -> anyhow::Result<HashMap<String, HolderKind>> {
+        let client = SolanaClient::new(&self.config.sol_rpc_url);
+        let addresses: Vec<String> = keys.iter().map(|k| (*k).clone()).collect();
+        let owners = client.get_account_owners(&addresses).await?;
+
+        Ok(addresses
+            .into_iter()
+            .zip(owners)
+            .map(|(address, owner)| {
+                let kind = match owner {
+                    Some(owner) if owner.executable => HolderKind::Contract,
+                    Some(owner) if owner.owner == SYSTEM_PROGRAM => HolderKind::Wallet,
+                    Some(_) => HolderKind::ProgramOwned,
+                    // Unfunded accounts can still own token accounts
+                    None => HolderKind::Wallet,
+                };
+                (address, kind)
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<String> for HolderKindLoader {
+    type Value = HolderKind;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Self::Value>, Self::Error> {
+        let (evm, solana): (Vec<&String>, Vec<&String>) = keys.iter().partition(|k| k.starts_with("0x"));
+
+        let mut kinds = HashMap::with_capacity(keys.len());
+        if !evm.is_empty() {
+            kinds.extend(self.load_evm(&evm).await.map_err(Arc::new)?);
+        }
+        if !solana.is_empty() {
+            kinds.extend(self.load_solana(&solana).await.map_err(Arc::new)?);
+        }
+        Ok(kinds)
+    }
+}
+
+/// Token metadata for `(chain, address)`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenKey(pub Chain, pub String);
+
+/// Raw total supply for `(chain, address)`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SupplyKey(pub Chain, pub String);
+
+/// Raw balance of `holder` in `token`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BalanceKey {
+    pub chain: Chain,
+    pub token: String,
+    pub holder: String,
+}
+
+/// Creation info for the token `(chain, address)`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeploymentKey(pub Chain, pub String);
+
+/// Funding source of the wallet `(chain, address)` as of a block or slot
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FunderKey(pub Chain, pub String, pub u64);
+
+/// Token metadata, supply and balance lookups. Each batch is answered from
+/// Redis with one `MGET` where possible; misses go out as one Multicall3
+/// `eth_call` on Ethereum or `getMultipleAccounts` on Solana. Invalid
+/// addresses and non-token contracts are left out of the result.
+pub struct TokenLoader {
+    redis: RedisPool,
+    config: Config,
+}
+
+impl TokenLoader {
+    pub fn new(redis: RedisPool, config: Config) -> Self {
+        Self { redis, config }
+    }
+
+    /// Serve `keys` from the Redis cache and fetch the rest with `fetch`,
+    /// writing what it returns back to the cache for `ttl` seconds (0 keeps it)
+    async fn cached<K, V, F, Fut>(
+        &self,
+        keys: &[K],
+        cache_key: impl Fn(&K) -> String,
+        ttl: u64,
+        fetch: F,
+    ) -> anyhow::Result<HashMap<K, V>>
+    where
+        K: Clone + Eq + Hash,
+        V: Serialize + DeserializeOwned,
+        F: FnOnce(Vec<K>) -> Fut,
+        Fut: Future<Output = anyhow::Result<HashMap<K, V>>>,
+    {
+        let cache_keys: Vec<String> = keys.iter().map(&cache_key).collect();
+        let cached = cache_get_many(&self.redis, &cache_keys).await?;
+
+        let mut values = HashMap::with_capacity(keys.len());
+        let mut misses = Vec::new();
+        for (key, value) in keys.iter().zip(cached) {
+            match value.and_then(|v| serde_json::from_str(&v).ok()) {
+                Some(value) => {
+                    values.insert(key.clone(), value);
+                }
+                None => misses.push(key.clone()),
+            }
+        }
+
+        if !misses.is_empty() {
+            let fetched = fetch(misses).await?;
+            let entries = fetched
+                .iter()
+                .map(|(key, value)| Ok((cache_key(key), serde_json::to_string(value)?)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            cache_set_many(&self.redis, &entries, ttl).await?;
+            values.extend(fetched);
+        }
+
+        Ok(values)
+    }
+
+    /// [`Self::cached`] for lookups that can come back empty, such as a
+    /// deployment on a node without archive state. Keys that `fetch` leaves
+    /// out are remembered for `cache_ttl` seconds instead of being retried on
+    /// every request. Keys it returns an error for are passed on uncached, so
+    /// a lookup that gave up early is not taken for a miss.
+    async fn cached_with_misses<K, V, F, Fut>(
+        &self,
+        keys: &[K],
+        cache_key: impl Fn(&K) -> String,
+        ttl: u64,
+        fetch: F,
+    ) -> anyhow::Result<HashMap<K, Result<V, String>>>
+    where
+        K: Clone + Eq + Hash,
+        V: Serialize + DeserializeOwned,
+        F: FnOnce(Vec<K>) -> Fut,
+        Fut: Future<Output = anyhow::Result<HashMap<K, Result<V, String>>>>,
+    {
+        let miss_keys: Vec<String> = keys.iter().map(|k| format!("{}:miss", cache_key(k))).collect();
+        let known_misses = cache_get_many(&self.redis, &miss_keys).await?;
+        let keys: Vec<K> = keys
+            .iter()
+            .zip(&known_misses)
+            .filter(|(_, miss)| miss.is_none())
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let mut failed = HashMap::new();
+        let failures = &mut failed;
+        let values = self
+            .cached(&keys, &cache_key, ttl, |misses| async move {
+                let mut found = HashMap::new();
+                for (key, value) in fetch(misses).await? {
+                    match value {
+                        Ok(value) => {
+                            found.insert(key, value);
+                        }
+                        Err(e) => {
+                            failures.insert(key, e);
+                        }
+                    }
+                }
+                Ok(found)
+            })
+            .await?;
+        let misses: Vec<(String, String)> = keys
+            .iter()
+            .filter(|key| !values.contains_key(key) && !failed.contains_key(key))
+            .map(|key| (format!("{}:miss", cache_key(key)), "1".to_string()))
+            .collect();
+        cache_set_many(&self.redis, &misses, self.config.cache_ttl.max(1)).await?;
+
+        Ok(values
+            .into_iter()
+            .map(|(key, value)| (key, Ok(value)))
+            .chain(failed.into_iter().map(|(key, e)| (key, Err(e))))
+            .collect())
+    }
+
+    async fn fetch_tokens(&self, keys: Vec<TokenKey>) -> anyhow::Result<HashMap<TokenKey, Token>> {
+        let mut tokens = HashMap::with_capacity(keys.len());
+
+        let evm = parsed_evm(keys.iter().filter(|k| k.0 == Chain::Ethereum).map(|k| &k.1));
+        if !evm.is_empty() {
+            let client = EthereumClient::new(&self.config.eth_rpc_url).await?;
+            let addresses: Vec<Address> = evm.iter().map(|(_, address)| *address).collect();
+            let infos = client.get_token_infos(&addresses).await?;
+            for ((key, _), info) in evm.into_iter().zip(infos) {
+                if let Some((name, symbol, decimals, supply)) = info {
+                    let mut token = empty_token(&key, Chain::Ethereum);
+                    token.name = Some(name);
+                    token.symbol = Some(symbol);
+                    token.decimals = Some(decimals);
+                    token.total_supply = Some(supply.to_string());
+                    tokens.insert(TokenKey(Chain::Ethereum, key), token);
+                }
+            }
+        }
+
+        let solana = parsed_solana(keys.iter().filter(|k| k.0 == Chain::Solana).map(|k| &k.1));
+        if !solana.is_empty() {
+            let client = SolanaClient::new(&self.config.sol_rpc_url);
+            let mints = client.get_mints(&solana).await?;
+            let metadata = client.get_token_metadata(&solana).await?;
+            for ((key, mint), metadata) in solana.into_iter().zip(mints).zip(metadata) {
+                if let Some(mint) = mint {
+                    let (name, symbol) = metadata.unzip();
+                    let mut token = empty_token(&key, Chain::Solana);
+                    token.name = name;
+                    token.symbol = symbol;
+                    token.decimals = Some(mint.decimals);
+                    token.total_supply = Some(mint.supply.to_string());
+                    tokens.insert(TokenKey(Chain::Solana, key), token);
+                }
+            }
+        }
+
+        // Unsupported chains have no RPC; return a default token
+        for key in keys.into_iter().filter(|k| matches!(k.0, Chain::Bsc | Chain::Other)) {
+            let token = empty_token(&key.1, key.0);
+            tokens.insert(key, token);
+        }
+
+        Ok(tokens)
+    }
+
+    async fn fetch_supplies(&self, keys: Vec<SupplyKey>) -> anyhow::Result<HashMap<SupplyKey, String>> {
+        let mut supplies = HashMap::with_capacity(keys.len());
+
+        let evm = parsed_evm(keys.iter().filter(|k| k.0 == Chain::Ethereum).map(|k| &k.1));
+        if !evm.is_empty() {
+            let client = EthereumClient::new(&self.config.eth_rpc_url).await?;
+            let addresses: Vec<Address> = evm.iter().map(|(_, address)| *address).collect();
+            let results = client.get_token_supplies(&addresses).await?;
+            for ((key, _), supply) in evm.into_iter().zip(results) {
+                if let Some(supply) = supply {
+                    supplies.insert(SupplyKey(Chain::Ethereum, key), supply.to_string());
+                }
+            }
+        }
+
+        let solana = parsed_solana(keys.iter().filter(|k| k.0 == Chain::Solana).map(|k| &k.1));
+        if !solana.is_empty() {
+            let client = SolanaClient::new(&self.config.sol_rpc_url);
+            let mints = client.get_mints(&solana).await?;
+            for (key, mint) in solana.into_iter().zip(mints) {
+                if let Some(mint) = mint {
+                    supplies.insert(SupplyKey(Chain::Solana, key), mint.supply.to_string());
+                }
+            }
+        }
+
+        Ok(supplies)
+    }
+
+    /// Deployment lookups take many RPC calls each, so tokens are looked up
+    /// concurrently. A failed lookup is logged and left out rather than
+    /// failing the whole batch; one that gave up on a long history is
+    /// reported for its key.
+    async fn fetch_deployments(
+        &self,
+        keys: Vec<DeploymentKey>,
+    ) -> anyhow::Result<HashMap<DeploymentKey, Result<Deployment, String>>> {
+        let evm_client = EthereumClient::new(&self.config.eth_rpc_url).await?;
+        let sol_client = SolanaClient::new(&self.config.sol_rpc_url);
+
+        let lookups = keys.into_iter().map(|key| {
+            let (evm_client, sol_client) = (&evm_client, &sol_client);
+            async move {
+                let deployment = match key.0 {
+                    Chain::Ethereum if key.1.parse::<Address>().is_ok() => evm_client.get_deployment(&key.1).await,
+                    Chain::Solana if Pubkey::from_str(&key.1).is_ok() => sol_client.get_deployment(&key.1).await,
+                    _ => Ok(None),
+                };
+                match deployment {
+                    Ok(deployment) => deployment.map(|d| (key, Ok(d))),
+                    Err(e) if e.is::<HistoryTruncated>() => Some((key, Err(e.to_string()))),
+                    Err(e) => {
+                        warn!("Failed to find deployment of {} on {}: {}", key.1, key.0, e);
+                        None
+                    }
+                }
+            }
+        });
+
+        Ok(join_all(lookups).await.into_iter().flatten().collect())
+    }
+
+    /// Funders are traced concurrently like deployments. Solana wallets are
+    /// traced from their oldest transaction, so the slot is not needed there.
+    async fn fetch_funders(&self, keys: Vec<FunderKey>) -> anyhow::Result<HashMap<FunderKey, Result<String, String>>> {
+        let evm_client = EthereumClient::new(&self.config.eth_rpc_url).await?;
+        let sol_client = SolanaClient::new(&self.config.sol_rpc_url);
+
+        let lookups = keys.into_iter().map(|key| {
+            let (evm_client, sol_client) = (&evm_client, &sol_client);
+            async move {
+                let funder = match key.0 {
+                    Chain::Ethereum if key.1.parse::<Address>().is_ok() => evm_client
+                        .get_funder(&key.1, key.2)
+                        .await
+                        .map(|funder| funder.map(|f| format!("{:?}", f))),
+                    Chain::Solana if Pubkey::from_str(&key.1).is_ok() => sol_client.get_funder(&key.1).await,
+                    _ => Ok(None),
+                };
+                match funder {
+                    Ok(funder) => funder.map(|f| (key, Ok(f))),
+                    Err(e) if e.is::<HistoryTruncated>() => Some((key, Err(e.to_string()))),
+                    Err(e) => {
+                        warn!("Failed to find funder of {} on {}: {}", key.1, key.0, e);
+                        None
+                    }
+                }
+            }
+        });
+
+        Ok(join_all(lookups).await.into_iter().flatten().collect())
+    }
+
+    async fn fetch_balances(&self, keys: Vec<BalanceKey>) -> anyhow::Result<HashMap<BalanceKey, String>> {
+        let mut balances = HashMap::with_capacity(keys.len());
+
+        let evm: Vec<(BalanceKey, (Address, Address))> = keys
+            .iter()
+            .filter(|k| k.chain == Chain::Ethereum)
+            .filter_map(|k| Some((k.clone(), (k.token.parse().ok()?, k.holder.parse().ok()?))))
+            .collect();
+        if !evm.is_empty() {
+            let client = EthereumClient::new(&self.config.eth_rpc_url).await?;
+            let pairs: Vec<(Address, Address)> = evm.iter().map(|(_, pair)| *pair).collect();
+            let results = client.get_token_balances(&pairs).await?;
+            for ((key, _), balance) in evm.into_iter().zip(results) {
+                if let Some(balance) = balance {
+                    balances.insert(key, balance.to_string());
+                }
+            }
+        }
+
+        let solana: Vec<BalanceKey> = keys
+            .into_iter()
+            .filter(|k| k.chain == Chain::Solana)
+            .filter(|k| Pubkey::from_str(&k.token).is_ok() && Pubkey::from_str(&k.holder).is_ok())
+            .collect();
+        if !solana.is_empty() {
+            let client = SolanaClient::new(&self.config.sol_rpc_url);
+            let pairs: Vec<(String, String)> = solana
+                .iter()
+                .map(|k| (k.token.clone(), k.holder.clone()))
+                .collect();
+            let results = client.get_token_balances(&pairs).await?;
+            for (key, balance) in solana.into_iter().zip(results) {
+                balances.insert(key, balance.to_string());
+            }
+        }
+
+        Ok(balances)
+    }
+}
+
+/// Keep the EVM addresses that parse, alongside their parsed form
+fn parsed_evm<'a>(addresses: impl Iterator<Item = &'a String>) -> Vec<(String, Address)> {
+    addresses
+        .filter_map(|a| Some((a.clone(), a.parse::<Address>().ok()?)))
+        .collect()
+}
+
+/// Keep the Solana addresses that are valid pubkeys
+fn parsed_solana<'a>(addresses: impl Iterator<Item = &'a String>) -> Vec<String> {
+    addresses
+        .filter(|a| Pubkey::from_str(a).is_ok())
+        .cloned()
+        .collect()
+}
+
+fn empty_token(address: &str, chain: Chain) -> Token {
+    Token {
+        address: address.to_string(),
+        chain: chain.to_string(),
+        name: None,
+        symbol: None,
+        decimals: None,
+        total_supply: None,
+        price_usd: None,
+        price_change_24h: None,
+        volume_24h: None,
+        market_cap: None,
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<TokenKey> for TokenLoader {
+    type Value = Token;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[TokenKey]) -> Result<HashMap<TokenKey, Self::Value>, Self::Error> {
+        self.cached(
+            keys,
+            |k| token_cache_key(&k.1, k.0),
+            self.config.cache_ttl,
+            |misses| self.fetch_tokens(misses),
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<SupplyKey> for TokenLoader {
+    type Value = String;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[SupplyKey]) -> Result<HashMap<SupplyKey, Self::Value>, Self::Error> {
+        self.cached(
+            keys,
+            |k| format!("supply:{}:{}", k.0, k.1),
+            self.config.cache_ttl,
+            |misses| self.fetch_supplies(misses),
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<BalanceKey> for TokenLoader {
+    type Value = String;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[BalanceKey]) -> Result<HashMap<BalanceKey, Self::Value>, Self::Error> {
+        self.cached(
+            keys,
+            |k| format!("balance:{}:{}:{}", k.chain, k.token, k.holder),
+            self.config.cache_ttl,
+            |misses| self.fetch_balances(misses),
+        )
+        .await
+        .map_err(Arc::new)
+    }
+}
+
+pub fn deployment_cache_key(chain: Chain, address: &str) -> String {
+    format!("deployment:{}:{}", chain, address)
+}
+
+#[async_trait::async_trait]
+impl Loader<DeploymentKey> for TokenLoader {
+    /// An error when the lookup gave up before finding the deployment
+    type Value = Result<Deployment, String>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[DeploymentKey]) -> Result<HashMap<DeploymentKey, Self::Value>, Self::Error> {
+        // A deployment never changes, so it is cached without expiry
+        self.cached_with_misses(keys, |k| deployment_cache_key(k.0, &k.1), 0, |misses| self.fetch_deployments(misses))
+            .await
+            .map_err(Arc::new)
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<FunderKey> for TokenLoader {
+    /// An error when the lookup gave up before finding the funder
+    type Value = Result<String, String>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[FunderKey]) -> Result<HashMap<FunderKey, Self::Value>, Self::Error> {
+        // Past funding does not change either
+        self.cached_with_misses(keys, |k| format!("funder:{}:{}:{}", k.0, k.1, k.2), 0, |misses| self.fetch_funders(misses))
+            .await
+            .map_err(Arc::new)
+    }
+}
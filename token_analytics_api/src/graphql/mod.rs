@@ -1,6 +1,13 @@
+pub mod addresses;
+pub mod auth;
 pub mod cache;
+pub mod deployments;
+pub mod liquidity;
+pub mod loaders;
+pub mod pagination;
 pub mod resolvers;
 pub mod schema;
+pub mod token_analytics;
 
 // Re-export the schema and resolvers for easier access
-pub use schema::QueryRoot;
+pub use schema::{MutationRoot, QueryRoot, SubscriptionRoot};
@@ -0,0 +1,127 @@
+//! Relay-style cursor pagination over pre-sorted result sets
+
+use async_graphql::{connection::CursorType, SimpleObject};
+
+/// Extra fields on every connection
+#[derive(SimpleObject)]
+pub struct ConnectionFields {
+    pub total_count: usize,
+}
+
+/// Cursors are hex-encoded `:`-separated sort keys so they stay opaque but stable
+fn encode(parts: &[String]) -> String {
+    hex::encode(parts.join(":"))
+}
+
+fn decode(cursor: &str, len: usize) -> Result<Vec<String>, String> {
+    let bytes = hex::decode(cursor).map_err(|_| "Invalid cursor".to_string())?;
+    let raw = String::from_utf8(bytes).map_err(|_| "Invalid cursor".to_string())?;
+    let parts: Vec<String> = raw.splitn(len, ':').map(str::to_string).collect();
+    if parts.len() != len {
+        return Err("Invalid cursor".to_string());
+    }
+    Ok(parts)
+}
+
+/// Position of a holder in balance-descending order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HolderCursor {
+    pub balance: u128,
+    pub address: String,
+}
+
+impl CursorType for HolderCursor {
+    type Error = String;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let parts = decode(s, 2)?;
+        Ok(Self {
+            balance: parts[0].parse().map_err(|_| "Invalid cursor".to_string())?,
+            address: parts[1].clone(),
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        encode(&[self.balance.to_string(), self.address.clone()])
+    }
+}
+
+/// Position of a transfer or trade in newest-first order. The transaction hash
+/// breaks ties where a chain's log index is only unique per transaction (Solana).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventCursor {
+    pub block_number: u64,
+    pub log_index: u64,
+    pub tx_hash: String,
+}
+
+impl CursorType for EventCursor {
+    type Error = String;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let parts = decode(s, 3)?;
+        Ok(Self {
+            block_number: parts[0].parse().map_err(|_| "Invalid cursor".to_string())?,
+            log_index: parts[1].parse().map_err(|_| "Invalid cursor".to_string())?,
+            tx_hash: parts[2].clone(),
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        encode(&[self.block_number.to_string(), self.log_index.to_string(), self.tx_hash.clone()])
+    }
+}
+
+/// A page cut from `items`, which must be sorted ascending by `key`
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+}
+
+/// Page size used when neither `first` nor `last` is given
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+/// Upper bound on `first`/`last`
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Apply Relay `after`/`before`/`first`/`last` semantics to sorted items
+pub fn paginate<T, K: Ord>(
+    items: Vec<T>,
+    key: impl Fn(&T) -> K,
+    after: Option<K>,
+    before: Option<K>,
+    first: Option<usize>,
+    last: Option<usize>,
+) -> Page<T> {
+    let (has_after, has_before) = (after.is_some(), before.is_some());
+    let first = match (first, last) {
+        (None, None) => Some(DEFAULT_PAGE_SIZE),
+        (first, _) => first.map(|first| first.min(MAX_PAGE_SIZE)),
+    };
+    let last = last.map(|last| last.min(MAX_PAGE_SIZE));
+    let start = after
+        .map(|after| items.partition_point(|item| key(item) <= after))
+        .unwrap_or(0);
+    let end = before
+        .map(|before| items.partition_point(|item| key(item) < before))
+        .unwrap_or(items.len())
+        .max(start);
+
+    let mut start_index = start;
+    let mut end_index = end;
+    if let Some(first) = first {
+        end_index = end_index.min(start_index + first);
+    }
+    if let Some(last) = last {
+        start_index = start_index.max(end_index.saturating_sub(last));
+    }
+
+    let has_previous_page = start_index > start || (has_after && start > 0);
+    let has_next_page = end_index < end || (has_before && end < items.len());
+
+    Page {
+        items: items.into_iter().skip(start_index).take(end_index - start_index).collect(),
+        has_previous_page,
+        has_next_page,
+    }
+}
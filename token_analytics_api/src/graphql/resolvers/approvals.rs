@@ -0,0 +1,160 @@
+use std::collections::{BTreeSet, HashMap};
+
+use async_graphql::{Context, FieldResult};
+use futures::{stream, StreamExt};
+use web3::types::{Address, U256};
+
+use crate::analytics::{approvals::latest_approvals, normalize_address};
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::indexer;
+use crate::models::{Allowance, Approval, Chain, SpenderExposure, TokenError, WalletApprovals};
+use crate::rpc::ethereum::EthereumClient;
+
+fn is_unlimited(amount: U256) -> bool {
+    amount >= U256::one() << 255
+}
+
+fn event_amount(approval: &Approval) -> U256 {
+    U256::from_dec_str(&approval.amount).unwrap_or_default()
+}
+
+/// Re-read allowances on-chain in one multicall; approvals can be spent
+/// down by `transferFrom` without a new `Approval` event. Approvals whose
+/// allowance is now zero, or could not be read, are dropped.
+async fn verify(client: &EthereumClient, token: &str, approvals: &[Approval]) -> FieldResult<Vec<(Allowance, U256)>> {
+    let pairs = approvals
+        .iter()
+        .map(|a| Ok((a.owner.parse::<Address>()?, a.spender.parse::<Address>()?)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let amounts = client.get_allowances(token.parse()?, &pairs).await?;
+
+    Ok(approvals
+        .iter()
+        .zip(amounts)
+        .filter_map(|(approval, amount)| {
+            let amount = amount.filter(|amount| !amount.is_zero())?;
+            Some((
+                Allowance {
+                    token: token.to_string(),
+                    owner: approval.owner.clone(),
+                    spender: approval.spender.clone(),
+                    amount: amount.to_string(),
+                    unlimited: is_unlimited(amount),
+                    approved_at: approval.timestamp,
+                },
+                amount,
+            ))
+        })
+        .collect())
+}
+
+pub async fn get_spender_exposure(
+    ctx: &Context<'_>,
+    address: String,
+    chain: Chain,
+    limit: usize,
+) -> FieldResult<Vec<SpenderExposure>> {
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+
+    indexer::sync_approvals(redis, config, chain, &address).await?;
+    let mut candidates = latest_approvals(&indexer::load_approvals(redis, chain, &address).await?);
+
+    // Only the largest approvals are verified to bound the number of RPC reads
+    candidates.sort_by_key(|a| std::cmp::Reverse(event_amount(a)));
+    candidates.truncate(config.allowance_verify_limit);
+
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+    let verified = verify(&client, &address, &candidates).await?;
+    let token = address.parse::<Address>()?;
+    let owners = verified
+        .iter()
+        .map(|(allowance, _)| Ok((token, allowance.owner.parse::<Address>()?)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let balances = client.get_token_balances(&owners).await?;
+
+    let mut by_spender: HashMap<String, (Vec<Allowance>, U256, U256)> = HashMap::new();
+    for ((allowance, amount), balance) in verified.into_iter().zip(balances) {
+        let entry = by_spender.entry(allowance.spender.clone()).or_default();
+        entry.1 = entry.1.saturating_add(amount);
+        entry.2 = entry.2.saturating_add(amount.min(balance.unwrap_or_default()));
+        entry.0.push(allowance);
+    }
+
+    let mut exposures: Vec<(SpenderExposure, U256)> = by_spender
+        .into_iter()
+        .map(|(spender, (allowances, total, exposed))| {
+            let owner_count = allowances.len() as u64;
+            let unlimited_count = allowances.iter().filter(|a| a.unlimited).count() as u64;
+            (
+                SpenderExposure {
+                    spender,
+                    total_allowance: total.to_string(),
+                    exposed_balance: exposed.to_string(),
+                    owner_count,
+                    unlimited_count,
+                    allowances,
+                },
+                exposed,
+            )
+        })
+        .collect();
+
+    exposures.sort_by_key(|exposure| std::cmp::Reverse(exposure.1));
+    Ok(exposures.into_iter().take(limit).map(|(exposure, _)| exposure).collect())
+}
+
+/// Tracked tokens synced at once for a wallet-wide query
+const SYNC_CONCURRENCY: usize = 4;
+
+/// Sync one token's approvals and verify those granted by `owner`
+async fn token_approvals(
+    redis: &RedisPool,
+    config: &Config,
+    client: &EthereumClient,
+    chain: Chain,
+    token: &str,
+    owner: &str,
+) -> FieldResult<Vec<Allowance>> {
+    indexer::sync_approvals(redis, config, chain, token).await?;
+    let approvals = indexer::load_approvals(redis, chain, token).await?;
+
+    let owned: Vec<Approval> = approvals.into_iter().filter(|a| a.owner == owner).collect();
+    let verified = verify(client, token, &latest_approvals(&owned)).await?;
+    Ok(verified.into_iter().map(|(allowance, _)| allowance).collect())
+}
+
+pub async fn get_wallet_approvals(
+    ctx: &Context<'_>,
+    owner: String,
+    chain: Chain,
+) -> FieldResult<WalletApprovals> {
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+    let owner = normalize_address(chain, &owner);
+
+    let tokens: BTreeSet<String> = indexer::tracked_tokens(redis, chain)
+        .await?
+        .iter()
+        .map(|token| normalize_address(chain, token))
+        .collect();
+    let lookups: Vec<_> = tokens
+        .iter()
+        .map(|token| token_approvals(redis, config, &client, chain, token, &owner))
+        .collect();
+    let results: Vec<_> = stream::iter(lookups).buffered(SYNC_CONCURRENCY).collect().await;
+
+    let mut allowances = Vec::new();
+    let mut errors = Vec::new();
+    for (token, result) in tokens.into_iter().zip(results) {
+        match result {
+            Ok(found) => allowances.extend(found),
+            Err(e) => errors.push(TokenError { token, error: e.message }),
+        }
+    }
+
+    allowances.sort_by(|a, b| b.unlimited.cmp(&a.unlimited).then(b.approved_at.cmp(&a.approved_at)));
+    Ok(WalletApprovals { allowances, errors })
+}
@@ -0,0 +1,41 @@
+use async_graphql::{dataloader::DataLoader, Context, FieldResult};
+
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::graphql::loaders::{TokenKey, TokenLoader};
+use crate::indexer;
+use crate::models::{Candle, Chain, Interval};
+
+// Guards against accidentally requesting years of one-minute candles
+const MAX_BUCKETS: i64 = 10_000;
+
+pub async fn get_candles(
+    ctx: &Context<'_>,
+    address: String,
+    chain: Chain,
+    interval: Interval,
+    from: i64,
+    to: i64,
+) -> FieldResult<Vec<Candle>> {
+    if from > to {
+        return Err("`from` must not be after `to`".into());
+    }
+    if to.saturating_sub(from) / interval.seconds() > MAX_BUCKETS {
+        return Err(format!("Range spans more than {} candles, use a wider interval", MAX_BUCKETS).into());
+    }
+
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+    let loader = ctx.data::<DataLoader<TokenLoader>>()?;
+    let decimals = loader
+        .load_one(TokenKey(chain, address.clone()))
+        .await?
+        .and_then(|token| token.decimals)
+        .ok_or_else(|| format!("{} is not a token on {}", address, chain))?;
+
+    indexer::sync_candles(redis, config, chain, &address, decimals, interval).await?;
+
+    // Include the bucket that contains `from`
+    let start = from - from.rem_euclid(interval.seconds());
+    Ok(indexer::load_candles(redis, chain, &address, interval, start, to).await?)
+}
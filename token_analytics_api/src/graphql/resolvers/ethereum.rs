@@ -1,62 +1,77 @@
-use async_graphql::{Context, Result, FieldResult};
-use web3::types::U256;
+use std::collections::HashMap;
 
-use crate::models::{Chain, Token, TokenAnalytics};
-use crate::rpc::ethereum::EthereumClient;
+use async_graphql::{Context, FieldResult};
+use web3::types::{Address, U256};
 
-pub async fn get_token_info(
-    _ctx: &Context<'_>,
-    address: String,
-) -> FieldResult<Token> {
-    let rpc_url = "https://mainnet.infura.io/v3/YOUR-PROJECT-ID";
-    let client = EthereumClient::new(rpc_url).await?;
-    let (name, symbol, decimals) = client.get_token_info(&address).await?;
-    let total_supply = client.get_token_supply(&address).await?.as_u128() as f64;
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::indexer::{self, trades::evm_pools};
+use crate::models::{Chain, LiquidityPool};
+use crate::pricing::native_price_usd;
+use crate::rpc::ethereum::USDC;
+use crate::rpc::EthereumClient;
 
-    Ok(Token {
-        address,
-        name: Some(name),
-        symbol: Some(symbol),
-        chain: Chain::Ethereum.to_string(),
-        decimals: Some(decimals),
-        total_supply: Some(total_supply.to_string()),
-        price_usd: None,
-        price_change_24h: None,
-        volume_24h: None,
-        market_cap: None,
-    })
-}
+/// The token's Uniswap V2 and V3 pools against USDC and WETH. Reserves are the
+/// pools' live token balances; `reserveUsd` is twice the quote side and
+/// `volume24h` sums trades already indexed for the token.
+pub async fn get_liquidity_pools(
+    ctx: &Context<'_>,
+    address: &str,
+) -> FieldResult<Vec<LiquidityPool>> {
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+
+    let pools = evm_pools(&client, address).await?;
+    if pools.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let token: Address = address.parse()?;
+    let mut pairs = Vec::with_capacity(pools.len() * 2);
+    for pool in &pools {
+        pairs.push((token, pool.address));
+        pairs.push((pool.quote.parse::<Address>()?, pool.address));
+    }
+    let balances = client.get_token_balances(&pairs).await?;
+    let eth_usd = native_price_usd(config, Chain::Ethereum).await?.unwrap_or_default();
+
+    let now = chrono::Utc::now().timestamp();
+    let mut volumes: HashMap<String, f64> = HashMap::new();
+    for trade in indexer::load_trades(redis, Chain::Ethereum, address, now - 86_400, now).await? {
+        *volumes.entry(trade.pool.to_lowercase()).or_default() += trade.volume_usd.unwrap_or_default();
+    }
+
+    Ok(pools
+        .iter()
+        .zip(balances.chunks(2))
+        .map(|(pool, reserves)| {
+            let token_reserve = reserves[0].unwrap_or_default();
+            let quote_reserve = reserves[1].unwrap_or_default();
+            let quote_usd = if pool.quote == USDC { 1.0 } else { eth_usd };
+            let address = format!("{:?}", pool.address);
+            let quote = pool.quote.to_lowercase();
+            let token = format!("{:?}", token);
+            let ((token0, reserve0), (token1, reserve1)) = if pool.token_is_token0 {
+                ((token, token_reserve), (quote, quote_reserve))
+            } else {
+                ((quote, quote_reserve), (token, token_reserve))
+            };
 
-pub async fn get_token_analytics(
-    _ctx: &Context<'_>,
-    address: String,
-) -> FieldResult<TokenAnalytics> {
-    let rpc_url = "https://mainnet.infura.io/v3/YOUR-PROJECT-ID";
-    let client = EthereumClient::new(rpc_url).await?;
-    let (name, symbol, decimals) = client.get_token_info(&address).await?;
-    let total_supply = client.get_token_supply(&address).await?.as_u128() as f64;
-    
-    let token = Token {
-        address: address.clone(),
-        name: Some(name),
-        symbol: Some(symbol),
-        chain: Chain::Ethereum.to_string(),
-        decimals: Some(decimals),
-        total_supply: Some(total_supply.to_string()),
-        price_usd: None,
-        price_change_24h: None,
-        volume_24h: None,
-        market_cap: None,
-    };
-    
-    // In a real implementation, fetch holders, transfers, and liquidity data
-    // This is a simplified version
-    Ok(TokenAnalytics {
-        token,
-        holders: vec![],
-        transfers: vec![],
-        liquidity_pools: vec![],
-    })
+            LiquidityPool {
+                volume_24h: volumes.get(&address).copied().unwrap_or_default(),
+                address,
+                chain: Chain::Ethereum,
+                exchange: pool.dex.to_string(),
+                token0,
+                token1,
+                reserve0: reserve0.to_string(),
+                reserve1: reserve1.to_string(),
+                reserve_usd: 2.0 * format_amount(quote_reserve, pool.quote_decimals) * quote_usd,
+                lock: Default::default(),
+            }
+        })
+        .collect())
 }
 
 // Helper function to convert U256 to f64 with decimals
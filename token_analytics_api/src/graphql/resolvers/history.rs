@@ -0,0 +1,40 @@
+use async_graphql::{Context, FieldResult};
+
+use crate::analytics::history::bucket_history;
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::indexer;
+use crate::models::{Chain, Interval, TokenHistoryPoint};
+
+// Guards against accidentally requesting years of one-minute buckets
+const MAX_BUCKETS: i64 = 10_000;
+
+pub async fn get_token_history(
+    ctx: &Context<'_>,
+    address: String,
+    chain: Chain,
+    from: i64,
+    to: i64,
+    interval: Interval,
+) -> FieldResult<Vec<TokenHistoryPoint>> {
+    if from > to {
+        return Err("`from` must not be after `to`".into());
+    }
+    if to.saturating_sub(from) / interval.seconds() > MAX_BUCKETS {
+        return Err(format!("Range spans more than {} buckets, use a wider interval", MAX_BUCKETS).into());
+    }
+
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+
+    indexer::sync_transfers(redis, config, chain, &address).await?;
+
+    // Balances and supply are rebuilt from the first indexed event
+    let transfers: Vec<_> = indexer::load_complete_transfers(redis, chain, &address)
+        .await?
+        .into_iter()
+        .filter(|t| t.timestamp <= to)
+        .collect();
+
+    Ok(bucket_history(&transfers, from, to, interval))
+}
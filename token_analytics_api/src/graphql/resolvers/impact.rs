@@ -0,0 +1,148 @@
+use anyhow::Result;
+use async_graphql::{Context, FieldResult};
+use tracing::debug;
+
+use crate::analytics::impact::{price_impact, Curve, SimPool};
+use crate::config::Config;
+use crate::indexer::trades::evm_pools;
+use crate::models::{Chain, PriceImpact, TradeSide};
+use crate::pricing::{native_price_usd, to_units};
+use crate::rpc::ethereum::USDC;
+use crate::rpc::solana::{RAYDIUM_AMM_V4, SOL_DECIMALS, USDC_DECIMALS, USDC_MINT, WSOL_MINT};
+use crate::rpc::{EthereumClient, SolanaClient};
+use super::token::{get_token, validate_address};
+
+/// Simulate buying or selling `amount_usd` of a token against its live pools
+pub async fn get_price_impact(
+    ctx: &Context<'_>,
+    address: String,
+    chain: Chain,
+    amount_usd: f64,
+    side: TradeSide,
+) -> FieldResult<PriceImpact> {
+    if !(amount_usd.is_finite() && amount_usd > 0.0) {
+        return Err("`amountUsd` must be a positive number".into());
+    }
+    validate_address(&address, chain)?;
+    let config = ctx.data::<Config>()?;
+    let decimals = get_token(ctx, &address, chain)
+        .await?
+        .decimals
+        .ok_or_else(|| format!("{} is not a token on {}", address, chain))?;
+
+    let pools = match chain {
+        Chain::Ethereum => ethereum_pools(config, &address, decimals).await?,
+        Chain::Solana => solana_pools(config, &address, decimals).await?,
+        Chain::Bsc | Chain::Other => Vec::new(),
+    };
+
+    Ok(price_impact(&pools, side, amount_usd))
+}
+
+/// The token's Uniswap V2 and V3 pools against USDC and WETH, with live reserves
+/// or V3 tick state. WETH pools are skipped when ETH cannot be priced.
+async fn ethereum_pools(config: &Config, address: &str, decimals: u8) -> Result<Vec<SimPool>> {
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+    let eth_usd = native_price_usd(config, Chain::Ethereum).await.unwrap_or_else(|e| {
+        debug!("No ETH/USD price: {}", e);
+        None
+    });
+
+    let mut pools = Vec::new();
+    for pool in evm_pools(&client, address).await? {
+        let quote_price_usd = if pool.quote == USDC { Some(1.0) } else { eth_usd };
+        let Some(quote_price_usd) = quote_price_usd else {
+            continue;
+        };
+
+        let curve = match pool.dex {
+            "uniswap_v3" => {
+                let Some(state) = client.get_v3_pool_state(pool.address).await? else {
+                    continue;
+                };
+                Curve::Concentrated {
+                    sqrt_price: to_units(state.sqrt_price_x96, 0) / 2f64.powi(96),
+                    liquidity: state.liquidity as f64,
+                    ticks: state.ticks.iter().map(|(tick, net)| (*tick, *net as f64)).collect(),
+                    tick_range: state.tick_range,
+                    token_is_token0: pool.token_is_token0,
+                }
+            }
+            _ => {
+                let (_, _, reserve0, reserve1) = client.get_v2_reserves(&format!("{:?}", pool.address)).await?;
+                let (token_reserve, quote_reserve) = if pool.token_is_token0 {
+                    (reserve0, reserve1)
+                } else {
+                    (reserve1, reserve0)
+                };
+                Curve::ConstantProduct {
+                    token_reserve: to_units(token_reserve, 0),
+                    quote_reserve: to_units(quote_reserve, 0),
+                }
+            }
+        };
+
+        pools.push(SimPool {
+            address: format!("{:?}", pool.address),
+            dex: pool.dex.to_string(),
+            fee: pool.fee as f64 / 1_000_000.0,
+            token_decimals: decimals,
+            quote_decimals: pool.quote_decimals,
+            quote_price_usd,
+            curve,
+        });
+    }
+    Ok(pools)
+}
+
+/// The token's Raydium AMM v4 pools against USDC and wSOL. Orca Whirlpools are
+/// concentrated-liquidity pools whose tick arrays are not read yet, so they are left out.
+/// wSOL pools are skipped when SOL cannot be priced.
+async fn solana_pools(config: &Config, address: &str, decimals: u8) -> Result<Vec<SimPool>> {
+    let client = SolanaClient::new(&config.sol_rpc_url);
+    let pools: Vec<_> = client
+        .get_dex_pools(address)
+        .await?
+        .into_iter()
+        .filter(|p| p.program == RAYDIUM_AMM_V4 && matches!(p.other_mint(address), USDC_MINT | WSOL_MINT))
+        .collect();
+    if pools.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sol_usd = native_price_usd(config, Chain::Solana).await.unwrap_or_else(|e| {
+        debug!("No SOL/USD price: {}", e);
+        None
+    });
+    let addresses: Vec<String> = pools.iter().map(|p| p.address.clone()).collect();
+    let reserves = client.get_raydium_reserves(&addresses).await?;
+
+    Ok(pools
+        .iter()
+        .zip(reserves)
+        .filter_map(|(pool, reserves)| {
+            let reserves = reserves?;
+            let (quote_price_usd, quote_decimals) = match pool.other_mint(address) {
+                USDC_MINT => (1.0, USDC_DECIMALS),
+                _ => (sol_usd?, SOL_DECIMALS),
+            };
+            let (token_reserve, quote_reserve) = if pool.mint_a == address {
+                (reserves.reserve_a, reserves.reserve_b)
+            } else {
+                (reserves.reserve_b, reserves.reserve_a)
+            };
+            Some(SimPool {
+                address: pool.address.clone(),
+                dex: pool.dex.to_string(),
+                fee: reserves.fee,
+                token_decimals: decimals,
+                quote_decimals,
+                quote_price_usd,
+                curve: Curve::ConstantProduct {
+                    token_reserve: token_reserve as f64,
+                    quote_reserve: quote_reserve as f64,
+                },
+            })
+        })
+        .collect())
+}
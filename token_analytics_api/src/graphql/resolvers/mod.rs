@@ -1,4 +1,12 @@
 //! GraphQL resolvers for different blockchain networks
 
+pub mod approvals;
+pub mod candles;
 pub mod ethereum;
+pub mod history;
+pub mod impact;
+pub mod pnl;
 pub mod solana;
+pub mod supply;
+pub mod token;
+pub mod wallet;
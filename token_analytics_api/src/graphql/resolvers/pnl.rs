@@ -0,0 +1,127 @@
+use async_graphql::{dataloader::DataLoader, Context, FieldResult};
+use futures::{stream, StreamExt, TryStreamExt};
+
+use crate::analytics::{normalize_address, pnl::token_pnl};
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::graphql::loaders::{TokenKey, TokenLoader};
+use crate::indexer;
+use crate::models::{Chain, CostBasisMethod, TokenPnl, WalletPnl};
+use crate::pricing::token_price_usd;
+use crate::rpc::SolanaClient;
+use super::token::validate_address;
+use super::wallet::wallet_erc20s;
+
+/// Most tokens one PnL query syncs and costs
+const MAX_PNL_TOKENS: usize = 50;
+/// Tokens whose trades are synced at once
+const SYNC_CONCURRENCY: usize = 4;
+
+/// PnL of `address` across `tokens` (default: the tokens `wallet` checks),
+/// costed from the wallet's decoded DEX trades
+pub async fn get_wallet_pnl(
+    ctx: &Context<'_>,
+    address: String,
+    chain: Chain,
+    method: CostBasisMethod,
+    tokens: Option<Vec<String>>,
+) -> FieldResult<WalletPnl> {
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+    validate_address(&address, chain)?;
+
+    let tokens = match tokens {
+        Some(tokens) => tokens,
+        None if chain == Chain::Solana => {
+            // Every mint the wallet has a token account for, emptied ones included
+            SolanaClient::new(&config.sol_rpc_url)
+                .get_token_holdings(&address)
+                .await?
+                .into_iter()
+                .map(|holding| holding.mint)
+                .collect()
+        }
+        None => wallet_erc20s(redis, config).await?,
+    };
+    if tokens.len() > MAX_PNL_TOKENS {
+        return Err(format!(
+            "PnL covers at most {} tokens, found {}; pass `tokens` to choose which",
+            MAX_PNL_TOKENS,
+            tokens.len()
+        )
+        .into());
+    }
+    for token in &tokens {
+        validate_address(token, chain)?;
+    }
+
+    let wallet = normalize_address(chain, &address);
+    let lookups: Vec<_> = tokens
+        .iter()
+        .map(|token| token_pnl_for(ctx, chain, token, &wallet, method))
+        .collect();
+    let mut tokens = stream::iter(lookups)
+        .buffered(SYNC_CONCURRENCY)
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    tokens.sort_by(|a, b| {
+        let total = |p: &TokenPnl| p.realized_pnl_usd + p.unrealized_pnl_usd.unwrap_or(0.0);
+        total(b).total_cmp(&total(a))
+    });
+
+    let realized_pnl_usd = tokens.iter().map(|t| t.realized_pnl_usd).sum::<f64>();
+    let unrealized_pnl_usd = tokens.iter().filter_map(|t| t.unrealized_pnl_usd).sum::<f64>();
+    Ok(WalletPnl {
+        address,
+        chain,
+        method,
+        tokens,
+        realized_pnl_usd,
+        unrealized_pnl_usd,
+        total_pnl_usd: realized_pnl_usd + unrealized_pnl_usd,
+    })
+}
+
+/// PnL in one token, or `None` when the wallet never traded it or the
+/// token's decimals are unknown
+async fn token_pnl_for(
+    ctx: &Context<'_>,
+    chain: Chain,
+    token: &str,
+    wallet: &str,
+    method: CostBasisMethod,
+) -> FieldResult<Option<TokenPnl>> {
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+
+    indexer::sync_trades(redis, config, chain, token).await?;
+    let trades: Vec<_> = indexer::load_trades(redis, chain, token, i64::MIN, i64::MAX)
+        .await?
+        .into_iter()
+        .filter(|trade| normalize_address(chain, &trade.trader) == wallet)
+        .collect();
+    if trades.is_empty() {
+        return Ok(None);
+    }
+
+    let loader = ctx.data::<DataLoader<TokenLoader>>()?;
+    let metadata = loader.load_one(TokenKey(chain, token.to_string())).await?;
+    let Some(decimals) = metadata.as_ref().and_then(|t| t.decimals) else {
+        tracing::warn!("Skipping PnL of {} on {}: decimals unknown", token, chain);
+        return Ok(None);
+    };
+    let current_price = token_price_usd(config, chain, token, decimals).await.unwrap_or_default();
+
+    Ok(Some(token_pnl(
+        chain,
+        token,
+        metadata.and_then(|t| t.symbol),
+        decimals,
+        &trades,
+        method,
+        current_price,
+    )))
+}
@@ -1,76 +1,66 @@
-use async_graphql::Result;
-use crate::models::{Token, Holder, Transfer, TokenAnalytics};
-use crate::rpc::solana::SolanaClient;
-use solana_sdk::pubkey::Pubkey;
-use std::str::FromStr;
+use std::collections::HashMap;
 
-pub async fn get_token(address: &str) -> Result<Token> {
-    // In a real implementation, you would get the RPC URL from config
-    let client = SolanaClient::new("https://api.mainnet-beta.solana.com");
-    
-    // Validate the address is a valid Solana public key
-    Pubkey::from_str(address)
-        .map_err(|e| async_graphql::Error::new(format!("Invalid Solana address: {}", e)))?;
-    
-    let (name, symbol, decimals) = client.get_token_info(address).await
-        .map_err(|e| async_graphql::Error::new(e.to_string()))?;
-    
-    // Get token supply
-    let total_supply = client.get_token_supply(address).await
-        .map(|supply| supply.to_string())
-        .ok();
-    
-    Ok(Token {
-        address: address.to_string(),
-        chain: "solana".to_string(),
-        name: Some(name),
-        symbol: Some(symbol),
-        decimals: Some(decimals as u8),
-        total_supply,
-        price_usd: None,
-        price_change_24h: None,
-        volume_24h: None,
-        market_cap: None,
-    })
-}
+use async_graphql::{Context, Result};
+
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::indexer;
+use crate::models::{Chain, LiquidityPool};
+use crate::pricing::native_price_usd;
+use crate::rpc::solana::{RAYDIUM_AMM_V4, SOL_DECIMALS, USDC_DECIMALS, USDC_MINT, WSOL_MINT};
+use crate::rpc::SolanaClient;
+
+/// The token's Raydium AMM v4 pools against USDC and wSOL, with live reserves.
+/// `reserveUsd` is twice the quote side and `volume24h` sums trades already
+/// indexed for the token. Orca Whirlpools are not listed yet.
+pub async fn get_liquidity_pools(ctx: &Context<'_>, address: &str) -> Result<Vec<LiquidityPool>> {
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+    let client = SolanaClient::new(&config.sol_rpc_url);
+
+    let pools: Vec<_> = client
+        .get_dex_pools(address)
+        .await?
+        .into_iter()
+        .filter(|p| p.program == RAYDIUM_AMM_V4 && matches!(p.other_mint(address), USDC_MINT | WSOL_MINT))
+        .collect();
+    if pools.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let addresses: Vec<String> = pools.iter().map(|p| p.address.clone()).collect();
+    let reserves = client.get_raydium_reserves(&addresses).await?;
+    let sol_usd = native_price_usd(config, Chain::Solana).await?.unwrap_or_default();
+
+    let now = chrono::Utc::now().timestamp();
+    let mut volumes: HashMap<String, f64> = HashMap::new();
+    for trade in indexer::load_trades(redis, Chain::Solana, address, now - 86_400, now).await? {
+        *volumes.entry(trade.pool).or_default() += trade.volume_usd.unwrap_or_default();
+    }
+
+    Ok(pools
+        .into_iter()
+        .zip(reserves)
+        .filter_map(|(pool, reserves)| {
+            let reserves = reserves?;
+            let (quote_usd, quote_decimals) = match pool.other_mint(address) {
+                USDC_MINT => (1.0, USDC_DECIMALS),
+                _ => (sol_usd, SOL_DECIMALS),
+            };
+            let quote_reserve = if pool.mint_a == address { reserves.reserve_b } else { reserves.reserve_a };
 
-pub async fn get_token_analytics(address: &str) -> Result<TokenAnalytics> {
-    let token = get_token(address).await?;
-    
-    // In a real implementation, you would fetch:
-    // 1. Top token holders
-    // 2. Recent transfers
-    // 3. Liquidity pools (e.g., from Raydium, Orca, etc.)
-    
-    // Placeholder data
-    let holders = vec![
-        Holder {
-            address: "Holder1...".to_string(),
-            balance: "1000".to_string(),
-            value_usd: Some(1000.0),
-            share: Some(10.0),
-        },
-        // Add more holders as needed
-    ];
-    
-    let transfers = vec![
-        Transfer {
-            tx_hash: "TxHash1...".to_string(),
-            from: "FromAddress...".to_string(),
-            to: "ToAddress...".to_string(),
-            amount: "100".to_string(),
-            timestamp: 1234567890,
-            block_number: 12345678,
-        },
-        // Add more transfers as needed
-    ];
-    
-    let liquidity_pools = vec![]; // Populate from DEX data
-    
-    Ok(TokenAnalytics {
-        token,
-        holders,
-        transfers,
-        liquidity_pools,
-    })
+            Some(LiquidityPool {
+                volume_24h: volumes.get(&pool.address).copied().unwrap_or_default(),
+                reserve_usd: 2.0 * quote_reserve as f64 / 10f64.powi(quote_decimals as i32) * quote_usd,
+                address: pool.address,
+                chain: Chain::Solana,
+                exchange: pool.dex.to_string(),
+                token0: pool.mint_a,
+                token1: pool.mint_b,
+                reserve0: reserves.reserve_a.to_string(),
+                reserve1: reserves.reserve_b.to_string(),
+                lock: Default::default(),
+            })
+        })
+        .collect())
 }
@@ -0,0 +1,54 @@
+use async_graphql::{Context, FieldResult};
+
+use crate::analytics::supply::supply_changes;
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::indexer;
+use crate::models::{Chain, SupplyChange, Transfer, TransferKind};
+
+/// 1 hour, 24 hours and 7 days
+const DEFAULT_WINDOWS: [i64; 3] = [3_600, 86_400, 604_800];
+
+pub async fn get_supply_changes(
+    ctx: &Context<'_>,
+    address: String,
+    chain: Chain,
+    windows: Option<Vec<i64>>,
+) -> FieldResult<Vec<SupplyChange>> {
+    let windows = windows.unwrap_or_else(|| DEFAULT_WINDOWS.to_vec());
+    if windows.iter().any(|w| *w <= 0) {
+        return Err("Windows must be positive durations in seconds".into());
+    }
+
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+
+    // Starting supplies are worked back from the current one
+    indexer::sync_transfers(redis, config, chain, &address).await?;
+    let transfers = indexer::load_complete_transfers(redis, chain, &address).await?;
+
+    Ok(supply_changes(&transfers, chrono::Utc::now().timestamp(), &windows))
+}
+
+pub async fn get_supply_events(
+    ctx: &Context<'_>,
+    address: String,
+    chain: Chain,
+    from: i64,
+    to: i64,
+    kind: Option<TransferKind>,
+) -> FieldResult<Vec<Transfer>> {
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+
+    indexer::sync_transfers(redis, config, chain, &address).await?;
+    let transfers = indexer::load_transfers(redis, chain, &address, from, to).await?;
+
+    Ok(transfers
+        .into_iter()
+        .filter(|t| match kind {
+            Some(kind) => t.kind == kind,
+            None => t.kind != TransferKind::Transfer,
+        })
+        .collect())
+}
@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+use async_graphql::{dataloader::DataLoader, Context, FieldResult};
+use futures::future::join_all;
+use solana_sdk::pubkey::Pubkey;
+use web3::types::Address;
+
+use crate::cache::RedisPool;
+use crate::graphql::cache as token_cache;
+use crate::labels::{LabelKind, LabelRegistry};
+use crate::models::{
+    Chain, LiquidityPool, Token, TokenAnalytics, TokenAnalyticsResult, TokenRef, TokenResult,
+};
+use crate::graphql::loaders::{TokenKey, TokenLoader};
+
+pub fn token_cache_key(address: &str, chain: Chain) -> String {
+    format!("token:{}:{}", chain, address)
+}
+
+pub fn pools_cache_key(address: &str, chain: Chain) -> String {
+    format!("pools:{}:{}", chain, address)
+}
+
+/// Token metadata, batched and cached through the [`TokenLoader`]
+pub async fn get_token(ctx: &Context<'_>, address: &str, chain: Chain) -> FieldResult<Token> {
+    let loader = ctx.data::<DataLoader<TokenLoader>>()?;
+    loader
+        .load_one(TokenKey(chain, address.to_string()))
+        .await?
+        .ok_or_else(|| format!("{} is not a token on {}", address, chain).into())
+}
+
+/// Most tokens a single batch query may ask for
+pub const MAX_BATCH_SIZE: usize = 100;
+
+fn check_batch_size(inputs: &[TokenRef]) -> FieldResult<()> {
+    if inputs.len() > MAX_BATCH_SIZE {
+        return Err(format!("At most {} tokens can be requested at once", MAX_BATCH_SIZE).into());
+    }
+    Ok(())
+}
+
+/// Reject addresses that cannot belong to `chain`
+pub fn validate_address(address: &str, chain: Chain) -> Result<(), String> {
+    let valid = match chain {
+        Chain::Ethereum | Chain::Bsc => address.parse::<Address>().is_ok(),
+        Chain::Solana => Pubkey::from_str(address).is_ok(),
+        Chain::Other => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid {} address: {}", chain, address))
+    }
+}
+
+/// Tokens for each input, in input order. Lookups share one loader batch and
+/// a failure only affects its own entry.
+pub async fn get_tokens(ctx: &Context<'_>, inputs: Vec<TokenRef>) -> FieldResult<Vec<TokenResult>> {
+    check_batch_size(&inputs)?;
+
+    let lookups = inputs.iter().map(|input| async move {
+        validate_address(&input.address, input.chain)?;
+        get_token(ctx, &input.address, input.chain)
+            .await
+            .map_err(|e| e.message)
+    });
+    let results = join_all(lookups).await;
+
+    Ok(inputs
+        .into_iter()
+        .zip(results)
+        .map(|(input, result)| {
+            let (token, error) = match result {
+                Ok(token) => (Some(token), None),
+                Err(error) => (None, Some(error)),
+            };
+            TokenResult { address: input.address, chain: input.chain, token, error }
+        })
+        .collect())
+}
+
+/// Lazy analytics for each input, in input order. Only address validation
+/// happens up front; selected fields resolve per entry as in `tokenAnalytics`.
+pub fn get_token_analytics_batch(inputs: Vec<TokenRef>) -> FieldResult<Vec<TokenAnalyticsResult>> {
+    check_batch_size(&inputs)?;
+
+    Ok(inputs
+        .into_iter()
+        .map(|input| {
+            let (analytics, error) = match validate_address(&input.address, input.chain) {
+                Ok(()) => {
+                    let analytics = TokenAnalytics::new(input.address.clone(), input.chain);
+                    (Some(analytics), None)
+                }
+                Err(error) => (None, Some(error)),
+            };
+            TokenAnalyticsResult { address: input.address, chain: input.chain, analytics, error }
+        })
+        .collect())
+}
+
+/// Liquidity pools trading the token, served from cache when fresh
+pub async fn get_liquidity_pools(
+    ctx: &Context<'_>,
+    address: &str,
+    chain: Chain,
+) -> FieldResult<Vec<LiquidityPool>> {
+    let redis = ctx.data::<RedisPool>()?;
+    let cache_key = pools_cache_key(address, chain);
+
+    if let Some(cached) = token_cache::get_pools(redis, &cache_key).await? {
+        return Ok(cached);
+    }
+
+    let pools = match chain {
+        Chain::Ethereum => super::ethereum::get_liquidity_pools(ctx, address).await?,
+        Chain::Solana => super::solana::get_liquidity_pools(ctx, address).await?,
+        Chain::Bsc | Chain::Other => Vec::new(),
+    };
+
+    token_cache::cache_pools(redis, &cache_key, &pools).await?;
+
+    // Pools seen here label their addresses for later holder/transfer lookups
+    let labels = ctx.data::<LabelRegistry>()?;
+    for pool in &pools {
+        labels
+            .detect(redis, chain, &pool.address, LabelKind::DexPool, Some(pool.exchange.clone()))
+            .await?;
+    }
+
+    Ok(pools)
+}
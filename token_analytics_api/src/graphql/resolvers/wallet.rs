@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+
+use async_graphql::{dataloader::DataLoader, Context, FieldResult};
+use futures::future::join_all;
+
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::graphql::loaders::{BalanceKey, TokenKey, TokenLoader};
+use crate::indexer;
+use crate::models::{Chain, ChainError, Wallet, WalletBalance};
+use crate::pricing::{native_price_usd, token_price_usd};
+use crate::rpc::{EthereumClient, SolanaClient};
+use crate::rpc::solana::SOL_DECIMALS;
+use super::token::validate_address;
+
+const ETH_DECIMALS: u8 = 18;
+
+fn value_usd(balance: &str, decimals: u8, price_usd: Option<f64>) -> Option<f64> {
+    let units = balance.parse::<f64>().ok()? / 10f64.powi(decimals as i32);
+    price_usd.map(|price| units * price)
+}
+
+/// Token and native balances of `address` on each requested chain. Without
+/// `chains`, every supported chain the address is valid on is checked. A chain
+/// whose RPC fails is reported in `errors` rather than failing the query.
+pub async fn get_wallet(
+    ctx: &Context<'_>,
+    address: String,
+    chains: Option<Vec<Chain>>,
+) -> FieldResult<Wallet> {
+    let chains: Vec<Chain> = match chains {
+        Some(chains) => {
+            for chain in &chains {
+                if !matches!(chain, Chain::Ethereum | Chain::Solana) {
+                    return Err(format!("Wallet queries are not supported on {}", chain).into());
+                }
+                validate_address(&address, *chain)?;
+            }
+            let mut seen = HashSet::new();
+            chains.into_iter().filter(|chain| seen.insert(*chain)).collect()
+        }
+        None => [Chain::Ethereum, Chain::Solana]
+            .into_iter()
+            .filter(|chain| validate_address(&address, *chain).is_ok())
+            .collect(),
+    };
+    if chains.is_empty() {
+        return Err(format!("{} is not a valid address on any supported chain", address).into());
+    }
+
+    let mut native_balances = Vec::new();
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    for chain in &chains {
+        let balances = match chain {
+            Chain::Ethereum => ethereum_balances(ctx, &address).await,
+            Chain::Solana => solana_balances(ctx, &address).await,
+            Chain::Bsc | Chain::Other => unreachable!("rejected above"),
+        };
+        match balances {
+            Ok((native, held)) => {
+                native_balances.push(native);
+                tokens.extend(held);
+            }
+            Err(e) => {
+                tracing::warn!("Wallet balances on {} failed for {}: {}", chain, address, e.message);
+                errors.push(ChainError { chain: *chain, error: e.message });
+            }
+        }
+    }
+    tokens.sort_by(|a, b| {
+        b.value_usd
+            .unwrap_or(0.0)
+            .total_cmp(&a.value_usd.unwrap_or(0.0))
+    });
+
+    let total_value_usd = native_balances
+        .iter()
+        .chain(&tokens)
+        .filter_map(|balance| balance.value_usd)
+        .sum();
+
+    Ok(Wallet { address, chains, native_balances, tokens, total_value_usd, errors })
+}
+
+/// ERC-20s a wallet is checked for: tracked tokens plus `WALLET_TOKENS`
+pub async fn wallet_erc20s(redis: &RedisPool, config: &Config) -> anyhow::Result<Vec<String>> {
+    let mut seen = HashSet::new();
+    Ok(indexer::tracked_tokens(redis, Chain::Ethereum)
+        .await?
+        .into_iter()
+        .chain(config.wallet_tokens.iter().cloned())
+        .filter(|token| seen.insert(token.to_lowercase()))
+        .collect())
+}
+
+/// ERC-20 balances cover tracked tokens and `WALLET_TOKENS`, read in one
+/// Multicall3 batch through the token loader
+async fn ethereum_balances(
+    ctx: &Context<'_>,
+    address: &str,
+) -> FieldResult<(WalletBalance, Vec<WalletBalance>)> {
+    let redis = ctx.data::<RedisPool>()?;
+    let config = ctx.data::<Config>()?;
+    let loader = ctx.data::<DataLoader<TokenLoader>>()?;
+
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+    let native = client.get_native_balance(address).await?.to_string();
+    let eth_price = native_price_usd(config, Chain::Ethereum).await.unwrap_or_default();
+    let native = WalletBalance {
+        chain: Chain::Ethereum,
+        token: None,
+        name: Some("Ether".to_string()),
+        symbol: Some("ETH".to_string()),
+        decimals: Some(ETH_DECIMALS),
+        value_usd: value_usd(&native, ETH_DECIMALS, eth_price),
+        balance: native,
+        price_usd: eth_price,
+    };
+
+    let candidates = wallet_erc20s(redis, config).await?;
+    let balances = loader
+        .load_many(candidates.iter().map(|token| BalanceKey {
+            chain: Chain::Ethereum,
+            token: token.clone(),
+            holder: address.to_string(),
+        }))
+        .await?;
+    let held: Vec<(String, String)> = balances
+        .into_iter()
+        .filter(|(_, balance)| balance != "0")
+        .map(|(key, balance)| (key.token, balance))
+        .collect();
+
+    let metadata = loader
+        .load_many(held.iter().map(|(token, _)| TokenKey(Chain::Ethereum, token.clone())))
+        .await?;
+
+    // Tokens that are not priceable (or whose pool lookups fail) stay unpriced
+    let tokens = join_all(held.into_iter().map(|(token, balance)| {
+        let metadata = metadata.get(&TokenKey(Chain::Ethereum, token.clone())).cloned();
+        async move {
+            let decimals = metadata.as_ref().and_then(|t| t.decimals);
+            let price_usd = match decimals {
+                Some(decimals) => token_price_usd(config, Chain::Ethereum, &token, decimals)
+                    .await
+                    .unwrap_or_default(),
+                None => None,
+            };
+            WalletBalance {
+                chain: Chain::Ethereum,
+                name: metadata.as_ref().and_then(|t| t.name.clone()),
+                symbol: metadata.as_ref().and_then(|t| t.symbol.clone()),
+                decimals,
+                value_usd: decimals.and_then(|decimals| value_usd(&balance, decimals, price_usd)),
+                token: Some(token),
+                balance,
+                price_usd,
+            }
+        }
+    }))
+    .await;
+
+    Ok((native, tokens))
+}
+
+/// SPL balances come from every spl-token and Token-2022 account the wallet
+/// owns. SOL and SPL tokens are priced from Raydium pools.
+async fn solana_balances(
+    ctx: &Context<'_>,
+    address: &str,
+) -> FieldResult<(WalletBalance, Vec<WalletBalance>)> {
+    let config = ctx.data::<Config>()?;
+    let loader = ctx.data::<DataLoader<TokenLoader>>()?;
+
+    let client = SolanaClient::new(&config.sol_rpc_url);
+    let native = client.get_native_balance(address).await?.to_string();
+    let sol_price = native_price_usd(config, Chain::Solana).await.unwrap_or_default();
+    let native = WalletBalance {
+        chain: Chain::Solana,
+        token: None,
+        name: Some("Solana".to_string()),
+        symbol: Some("SOL".to_string()),
+        decimals: Some(SOL_DECIMALS),
+        value_usd: value_usd(&native, SOL_DECIMALS, sol_price),
+        balance: native,
+        price_usd: sol_price,
+    };
+
+    let holdings: Vec<_> = client
+        .get_token_holdings(address)
+        .await?
+        .into_iter()
+        .filter(|holding| holding.amount > 0)
+        .collect();
+    let metadata = loader
+        .load_many(holdings.iter().map(|h| TokenKey(Chain::Solana, h.mint.clone())))
+        .await?;
+
+    // Mints without a Raydium pool (or whose lookups fail) stay unpriced
+    let tokens = join_all(holdings.into_iter().map(|holding| {
+        let metadata = metadata.get(&TokenKey(Chain::Solana, holding.mint.clone())).cloned();
+        async move {
+            let price_usd = token_price_usd(config, Chain::Solana, &holding.mint, holding.decimals)
+                .await
+                .unwrap_or_default();
+            let balance = holding.amount.to_string();
+            WalletBalance {
+                chain: Chain::Solana,
+                name: metadata.as_ref().and_then(|t| t.name.clone()),
+                symbol: metadata.as_ref().and_then(|t| t.symbol.clone()),
+                decimals: Some(holding.decimals),
+                value_usd: value_usd(&balance, holding.decimals, price_usd),
+                token: Some(holding.mint),
+                balance,
+                price_usd,
+            }
+        }
+    }))
+    .await;
+
+    Ok((native, tokens))
+}
@@ -1,6 +1,23 @@
-use async_graphql::{Object, Context, FieldResult};
-use crate::models::{Token, TokenAnalytics, Chain};
-use crate::cache::RedisPool;
+use async_graphql::{Object, Subscription, Context, FieldResult};
+use futures::{Stream, StreamExt};
+use crate::models::{
+    Candle, Chain, CostBasisMethod, Deployment, Interval, LiquidityPool, PriceImpact, PriceUpdate, SpenderExposure,
+    SupplyChange, Token, TokenAnalytics, TokenAnalyticsResult, TokenHistoryPoint, TokenRef, TokenResult, TradeSide,
+    Transfer, TransferKind, Wallet, WalletApprovals, WalletPnl,
+};
+use crate::alerts::{self, Alert, AlertRule, AlertRuleInput};
+use crate::alerts::webhooks::{self, DeadLetter, WebhookDelivery, WebhookDispatcher, WebhookEndpoint};
+use crate::cache::{cache_get, RedisPool};
+use crate::config::Config;
+use crate::indexer;
+use crate::labels::{AddressLabel, LabelRegistry};
+use crate::streams::{self, FeedHub};
+use crate::watchlists::{self, Watchlist};
+use super::auth::ApiKeyGuard;
+use super::cache::lp_lock_cache_key;
+use super::deployments::deployer_cache_key;
+use super::loaders::deployment_cache_key;
+use super::resolvers::token::{pools_cache_key, token_cache_key, validate_address};
 
 pub struct QueryRoot;
 
@@ -13,85 +30,212 @@ impl QueryRoot {
         address: String,
         chain: Chain,
     ) -> FieldResult<Token> {
-        let redis = ctx.data::<RedisPool>()?;
-        let cache_key = format!("token:{}:{}", chain, address);
-        
-        // Try to get from cache first
-        if let Some(cached) = super::cache::get_token(redis, &cache_key).await? {
-            return Ok(cached);
-        }
-        
-        // If not in cache, fetch from RPC
-        let token = match chain {
-            Chain::Ethereum => super::resolvers::ethereum::get_token_info(&ctx, address.clone()).await?,
-            Chain::Solana => super::resolvers::solana::get_token(&ctx, address.clone()).await?,
-            Chain::Bsc | Chain::Other => {
-                // Return a default token for unsupported chains
-                Token {
-                    address: address.clone(),
-                    chain: chain.to_string(),
-                    name: None,
-                    symbol: None,
-                    decimals: None,
-                    total_supply: None,
-                    price_usd: None,
-                    price_change_24h: None,
-                    volume_24h: None,
-                    market_cap: None,
-                }
-            }
-        };
-        
-        // Cache the result
-        super::cache::cache_token(redis, &cache_key, &token).await?;
-        
-        Ok(token)
+        super::resolvers::token::get_token(ctx, &address, chain).await
     }
     
+    /// Analytics for a token. The address is checked up front; nothing is fetched
+    /// until a field is selected.
     async fn token_analytics(
         &self,
-        ctx: &Context<'_>,
         address: String,
         chain: Chain,
     ) -> FieldResult<TokenAnalytics> {
+        validate_address(&address, chain)?;
+        Ok(TokenAnalytics::new(address, chain))
+    }
+
+    /// Get many tokens at once. Results follow input order and carry their own errors.
+    async fn tokens(&self, ctx: &Context<'_>, inputs: Vec<TokenRef>) -> FieldResult<Vec<TokenResult>> {
+        super::resolvers::token::get_tokens(ctx, inputs).await
+    }
+
+    /// Analytics for many tokens at once. Results follow input order and carry their own errors.
+    async fn token_analytics_batch(&self, inputs: Vec<TokenRef>) -> FieldResult<Vec<TokenAnalyticsResult>> {
+        super::resolvers::token::get_token_analytics_batch(inputs)
+    }
+
+    /// Native and token balances of a wallet with USD values. Defaults to every
+    /// supported chain the address is valid on.
+    async fn wallet(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chains: Option<Vec<Chain>>,
+    ) -> FieldResult<Wallet> {
+        super::resolvers::wallet::get_wallet(ctx, address, chains).await
+    }
+
+    /// Realized and unrealized PnL of a wallet per token, costed from its DEX trades.
+    /// Without `tokens`, the tokens checked by `wallet` are used. At most 50 tokens.
+    async fn wallet_pnl(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        #[graphql(default_with = "Chain::Ethereum")] chain: Chain,
+        #[graphql(default_with = "CostBasisMethod::Fifo")] method: CostBasisMethod,
+        tokens: Option<Vec<String>>,
+    ) -> FieldResult<WalletPnl> {
+        super::resolvers::pnl::get_wallet_pnl(ctx, address, chain, method, tokens).await
+    }
+
+    /// Get supply, holder count, transfer count and volume over time, bucketed by `interval`.
+    /// `from` and `to` are unix timestamps in seconds.
+    async fn token_history(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Chain,
+        from: i64,
+        to: i64,
+        interval: Interval,
+    ) -> FieldResult<Vec<TokenHistoryPoint>> {
+        super::resolvers::history::get_token_history(ctx, address, chain, from, to, interval).await
+    }
+
+    /// Simulate buying or selling `amountUsd` worth of a token against its live DEX
+    /// pools, each on its own and split across all of them
+    async fn price_impact(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Chain,
+        amount_usd: f64,
+        side: TradeSide,
+    ) -> FieldResult<PriceImpact> {
+        super::resolvers::impact::get_price_impact(ctx, address, chain, amount_usd, side).await
+    }
+
+    /// OHLCV candles built from decoded DEX trades across all of the token's pools.
+    /// `from` and `to` are unix timestamps in seconds; buckets without trades are omitted.
+    async fn candles(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Chain,
+        interval: Interval,
+        from: i64,
+        to: i64,
+    ) -> FieldResult<Vec<Candle>> {
+        super::resolvers::candles::get_candles(ctx, address, chain, interval, from, to).await
+    }
+
+    /// Get minted and burned totals over trailing windows (in seconds, defaults to 1h, 24h and 7d)
+    async fn supply_changes(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Chain,
+        windows: Option<Vec<i64>>,
+    ) -> FieldResult<Vec<SupplyChange>> {
+        super::resolvers::supply::get_supply_changes(ctx, address, chain, windows).await
+    }
+
+    /// Get mint and burn events between two unix timestamps, optionally restricted to one kind
+    async fn supply_events(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Chain,
+        from: i64,
+        to: i64,
+        kind: Option<TransferKind>,
+    ) -> FieldResult<Vec<Transfer>> {
+        super::resolvers::supply::get_supply_events(ctx, address, chain, from, to, kind).await
+    }
+
+    /// Get the spenders holding the largest outstanding allowances on a token
+    async fn spender_exposure(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Chain,
+        #[graphql(default = 10)] limit: usize,
+    ) -> FieldResult<Vec<SpenderExposure>> {
+        super::resolvers::approvals::get_spender_exposure(ctx, address, chain, limit).await
+    }
+
+    /// Get every live approval granted by a wallet across tracked tokens,
+    /// with the tokens that could not be checked
+    async fn wallet_approvals(
+        &self,
+        ctx: &Context<'_>,
+        owner: String,
+        chain: Chain,
+    ) -> FieldResult<WalletApprovals> {
+        super::resolvers::approvals::get_wallet_approvals(ctx, owner, chain).await
+    }
+
+    /// Get the known labels for an address
+    async fn address_labels(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Option<Chain>,
+    ) -> FieldResult<Vec<AddressLabel>> {
+        Ok(ctx.data::<LabelRegistry>()?.lookup(&address, chain))
+    }
+
+    /// List configured alert rules
+    async fn alert_rules(&self, ctx: &Context<'_>) -> FieldResult<Vec<AlertRule>> {
         let redis = ctx.data::<RedisPool>()?;
-        let cache_key = format!("analytics:{}:{}", chain, address);
-        
-        // Try to get from cache first
-        if let Some(cached) = super::cache::get_analytics(redis, &cache_key).await? {
-            return Ok(cached);
-        }
-        
-        // If not in cache, fetch from RPC
-        let analytics = match chain {
-            Chain::Ethereum => super::resolvers::ethereum::get_token_analytics(&address).await?,
-            Chain::Solana => super::resolvers::solana::get_token_analytics(&address).await?,
-            Chain::Bsc | Chain::Other => {
-                // Return empty analytics for unsupported chains
-                TokenAnalytics {
-                    token: Token {
-                        address: address.clone(),
-                        chain: chain.to_string(),
-                        name: None,
-                        symbol: None,
-                        decimals: None,
-                        total_supply: None,
-                        price_usd: None,
-                        price_change_24h: None,
-                        volume_24h: None,
-                        market_cap: None,
-                    },
-                    holders: Vec::new(),
-                    transfers: Vec::new(),
-                    liquidity_pools: Vec::new(),
-                }
-            }
-        };
-        
-        // Cache the result
-        super::cache::cache_analytics(redis, &cache_key, &analytics).await?;
-        
-        Ok(analytics)
+        Ok(alerts::list_rules(redis).await?)
+    }
+
+    /// Get the most recently fired alerts, newest first
+    async fn alerts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 50)] limit: usize,
+    ) -> FieldResult<Vec<Alert>> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(alerts::recent_alerts(redis, limit).await?)
+    }
+
+    /// List registered alert webhook endpoints
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn webhook_endpoints(&self, ctx: &Context<'_>) -> FieldResult<Vec<WebhookEndpoint>> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(webhooks::list_endpoints(redis).await?)
+    }
+
+    /// Get the webhook delivery log, newest first
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn webhook_deliveries(
+        &self,
+        ctx: &Context<'_>,
+        endpoint_id: Option<u64>,
+        #[graphql(default = 50)] limit: usize,
+    ) -> FieldResult<Vec<WebhookDelivery>> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(webhooks::list_deliveries(redis, endpoint_id, limit).await?)
+    }
+
+    /// Get deliveries that exhausted their retries, newest first
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn webhook_dead_letters(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 50)] limit: usize,
+    ) -> FieldResult<Vec<DeadLetter>> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(webhooks::list_dead_letters(redis, limit).await?)
+    }
+
+    /// List all watchlists
+    async fn watchlists(&self, ctx: &Context<'_>) -> FieldResult<Vec<Watchlist>> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(watchlists::list(redis).await?)
+    }
+
+    async fn watchlist(&self, ctx: &Context<'_>, name: String) -> FieldResult<Option<Watchlist>> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(watchlists::get(redis, &name).await?)
+    }
+
+    /// List tokens tracked for indexing and wallet-level queries
+    async fn tracked_tokens(&self, ctx: &Context<'_>, chain: Chain) -> FieldResult<Vec<String>> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(indexer::tracked_tokens(redis, chain).await?)
     }
 }
 
@@ -102,6 +246,10 @@ pub struct MutationRoot;
 
 #[Object]
 impl MutationRoot {
+    /// Drop cached token metadata and pools, and the analytics cached from
+    /// them (replayed holder balances, LP locks of its pools, its deployer's
+    /// history), so the next query refetches them
+    #[graphql(guard = "ApiKeyGuard")]
     async fn refresh_token_data(
         &self,
         ctx: &Context<'_>,
@@ -109,18 +257,188 @@ impl MutationRoot {
         chain: Chain,
     ) -> FieldResult<bool> {
         let redis = ctx.data::<RedisPool>()?;
-        let cache_key = format!("token:{}:{}", chain, address);
-        
-        // Invalidate cache
-        super::cache::invalidate(redis, &cache_key).await?;
-        
+
+        let pools_key = pools_cache_key(&address, chain);
+        for pool in super::cache::get_pools(redis, &pools_key).await?.unwrap_or_default() {
+            super::cache::invalidate(redis, &lp_lock_cache_key(pool.chain, &pool.address)).await?;
+        }
+
+        // The deployment itself never changes, but a failed lookup is retried
+        let deployment_key = deployment_cache_key(chain, &address);
+        if let Some(cached) = cache_get(redis, &deployment_key).await? {
+            let deployment: Deployment = serde_json::from_str(&cached)?;
+            super::cache::invalidate(redis, &deployer_cache_key(chain, &deployment.creator)).await?;
+        }
+        super::cache::invalidate(redis, &format!("{}:miss", deployment_key)).await?;
+
+        indexer::invalidate_holder_state(redis, chain, &address).await?;
+        indexer::invalidate_trade_pools(redis, chain, &address).await?;
+        super::cache::invalidate(redis, &token_cache_key(&address, chain)).await?;
+        super::cache::invalidate(redis, &pools_key).await?;
+
+        Ok(true)
+    }
+
+    /// Start tracking a token; its transfers are indexed in the background
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn track_token(&self, ctx: &Context<'_>, address: String, chain: Chain) -> FieldResult<bool> {
+        let redis = ctx.data::<RedisPool>()?;
+        let config = ctx.data::<Config>()?;
+        indexer::track_token(redis, chain, &address).await?;
+
+        let (redis, config) = (redis.clone(), config.clone());
+        tokio::spawn(async move {
+            if let Err(e) = indexer::sync_transfers(&redis, &config, chain, &address).await {
+                tracing::warn!("Initial index of {} on {} failed: {}", address, chain, e);
+            }
+        });
+
         Ok(true)
     }
+
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn untrack_token(&self, ctx: &Context<'_>, address: String, chain: Chain) -> FieldResult<bool> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(indexer::untrack_token(redis, chain, &address).await?)
+    }
+
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn create_watchlist(&self, ctx: &Context<'_>, name: String) -> FieldResult<Watchlist> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(watchlists::create(redis, name).await?)
+    }
+
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn delete_watchlist(&self, ctx: &Context<'_>, name: String) -> FieldResult<bool> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(watchlists::delete(redis, &name).await?)
+    }
+
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn add_to_watchlist(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        address: String,
+        chain: Chain,
+    ) -> FieldResult<Watchlist> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(watchlists::add_token(redis, &name, address, chain).await?)
+    }
+
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn remove_from_watchlist(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        address: String,
+        chain: Chain,
+    ) -> FieldResult<Watchlist> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(watchlists::remove_token(redis, &name, &address, chain).await?)
+    }
+
+    /// Create an alert rule; the watcher starts evaluating it on its next pass
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn create_alert_rule(
+        &self,
+        ctx: &Context<'_>,
+        input: AlertRuleInput,
+    ) -> FieldResult<AlertRule> {
+        if input.threshold_pct.is_some_and(|pct| !(pct > 0.0 && pct <= 100.0)) {
+            return Err("thresholdPct must be within (0, 100]".into());
+        }
+        validate_address(&input.token, input.chain)?;
+
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(alerts::create_rule(redis, input).await?)
+    }
+
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn set_alert_rule_enabled(
+        &self,
+        ctx: &Context<'_>,
+        id: u64,
+        enabled: bool,
+    ) -> FieldResult<AlertRule> {
+        let redis = ctx.data::<RedisPool>()?;
+        let mut rule = alerts::get_rule(redis, id)
+            .await?
+            .ok_or_else(|| format!("Alert rule {} not found", id))?;
+
+        rule.enabled = enabled;
+        alerts::save_rule(redis, &rule).await?;
+        Ok(rule)
+    }
+
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn delete_alert_rule(&self, ctx: &Context<'_>, id: u64) -> FieldResult<bool> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(alerts::delete_rule(redis, id).await?)
+    }
+
+    /// Register a webhook that receives every fired alert, signed with `secret`
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn create_webhook_endpoint(
+        &self,
+        ctx: &Context<'_>,
+        url: String,
+        secret: String,
+        description: Option<String>,
+    ) -> FieldResult<WebhookEndpoint> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(webhooks::create_endpoint(redis, url, secret, description).await?)
+    }
+
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn delete_webhook_endpoint(&self, ctx: &Context<'_>, id: u64) -> FieldResult<bool> {
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(webhooks::delete_endpoint(redis, id).await?)
+    }
+
+    /// Retry a dead-lettered delivery, returning the new delivery record
+    #[graphql(guard = "ApiKeyGuard")]
+    async fn redeliver_webhook(&self, ctx: &Context<'_>, delivery_id: u64) -> FieldResult<WebhookDelivery> {
+        let dispatcher = ctx.data::<WebhookDispatcher>()?;
+        Ok(dispatcher.redeliver(delivery_id).await?)
+    }
 }
 
-// Subscription root for real-time updates
-// This is a placeholder - implement as needed
+// Subscription root for real-time updates, served over graphql-ws
 #[derive(Default)]
 pub struct SubscriptionRoot;
 
-// Implement subscriptions here when needed
+#[Subscription]
+impl SubscriptionRoot {
+    /// New transfers of a token as they land on chain
+    async fn transfers(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Chain,
+    ) -> FieldResult<impl Stream<Item = FieldResult<Transfer>>> {
+        let config = ctx.data::<Config>()?.clone();
+        Ok(ctx.data::<FeedHub>()?.transfers(config, chain, address).map(|r| r.map_err(Into::into)))
+    }
+
+    /// Spot USD price of a token, emitted on each new block where it changed
+    async fn price_updates(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain: Chain,
+    ) -> FieldResult<impl Stream<Item = FieldResult<PriceUpdate>>> {
+        let config = ctx.data::<Config>()?.clone();
+        Ok(ctx.data::<FeedHub>()?.price_updates(config, chain, address).map(|r| r.map_err(Into::into)))
+    }
+
+    /// Liquidity pools as they are created
+    async fn new_pools(
+        &self,
+        ctx: &Context<'_>,
+        chain: Chain,
+    ) -> FieldResult<impl Stream<Item = FieldResult<LiquidityPool>>> {
+        let config = ctx.data::<Config>()?.clone();
+        Ok(streams::new_pools(config, chain).map(|r| r.map_err(Into::into)))
+    }
+}
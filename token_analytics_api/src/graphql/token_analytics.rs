@@ -0,0 +1,526 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use async_graphql::{
+    connection::{query, Connection, Edge, EmptyFields},
+    dataloader::DataLoader,
+    Context, FieldResult, Object,
+};
+
+use crate::analytics::{
+    concentration::concentration,
+    distribution::{bucket_holders, DEFAULT_BALANCE_EDGES, DEFAULT_USD_EDGES},
+    holders::HolderState,
+    is_burn_address,
+    launch::{funding_clusters, launch_buyers},
+    normalize_address,
+    wash::wash_trading,
+};
+use crate::cache::RedisPool;
+use crate::config::Config;
+use crate::indexer;
+use crate::labels::{LabelKind, LabelRegistry};
+use crate::models::{
+    Chain, Concentration, Holder, HolderDistribution, LaunchAnalysis, LaunchBuyer, LiquidityPool,
+    Token, TokenAnalytics, Trade, TradeSide, Transfer, WashTrading,
+};
+use crate::pricing::token_price_usd;
+use crate::rpc::solana::RAYDIUM_AUTHORITY;
+
+use super::loaders::{DeploymentKey, FunderKey, TokenLoader};
+use super::pagination::{paginate, ConnectionFields, EventCursor, HolderCursor};
+use super::resolvers::token::{get_liquidity_pools, get_token};
+
+impl TokenAnalytics {
+    /// Sync the token's transfer index, at most once per query
+    async fn sync_transfers(&self, ctx: &Context<'_>) -> FieldResult<()> {
+        let redis = ctx.data::<RedisPool>()?;
+        let config = ctx.data::<Config>()?;
+        self.synced
+            .get_or_try_init(|| indexer::sync_transfers(redis, config, self.chain, &self.address))
+            .await?;
+        Ok(())
+    }
+
+    /// Sync the token's trade index, at most once per query
+    async fn sync_trades(&self, ctx: &Context<'_>) -> FieldResult<()> {
+        let redis = ctx.data::<RedisPool>()?;
+        let config = ctx.data::<Config>()?;
+        self.trades_synced
+            .get_or_try_init(|| indexer::sync_trades(redis, config, self.chain, &self.address))
+            .await?;
+        Ok(())
+    }
+
+    /// Supply and holder balances replayed from the complete transfer index,
+    /// loaded at most once per query; fails while the backfill is incomplete
+    async fn holder_state(&self, ctx: &Context<'_>) -> FieldResult<&HolderState> {
+        self.sync_transfers(ctx).await?;
+        let redis = ctx.data::<RedisPool>()?;
+        let config = ctx.data::<Config>()?;
+        Ok(self
+            .holder_state
+            .get_or_try_init(|| indexer::load_holder_state(redis, self.chain, &self.address, config.cache_ttl))
+            .await?)
+    }
+
+    /// Sync the token's transfers and load those timestamped within `[from, to]`
+    async fn indexed_transfers_between(
+        &self,
+        ctx: &Context<'_>,
+        from: i64,
+        to: i64,
+    ) -> FieldResult<Vec<Transfer>> {
+        self.sync_transfers(ctx).await?;
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(indexer::load_transfers(redis, self.chain, &self.address, from, to).await?)
+    }
+}
+
+/// Widest launch window, in blocks or slots
+const MAX_LAUNCH_WINDOW: u64 = 1000;
+
+/// Widest wash-trading window, in seconds
+const MAX_WASH_WINDOW: i64 = 86_400;
+
+fn validate_edges(edges: &[f64]) -> FieldResult<()> {
+    if edges.is_empty() || edges.windows(2).any(|w| w[0] >= w[1]) {
+        return Err("Bucket edges must be non-empty and strictly ascending".into());
+    }
+    Ok(())
+}
+
+#[Object]
+impl TokenAnalytics {
+    /// Token metadata
+    async fn token(&self, ctx: &Context<'_>) -> FieldResult<Token> {
+        get_token(ctx, &self.address, self.chain).await
+    }
+
+    /// Whether the transfer index reaches back to the token's creation.
+    /// Holder and concentration figures return errors until it does.
+    async fn index_complete(&self, ctx: &Context<'_>) -> FieldResult<bool> {
+        self.sync_transfers(ctx).await?;
+        let redis = ctx.data::<RedisPool>()?;
+        Ok(indexer::transfers_complete(redis, self.chain, &self.address).await?)
+    }
+
+    /// Liquidity pools trading the token
+    async fn liquidity_pools(&self, ctx: &Context<'_>) -> FieldResult<Vec<LiquidityPool>> {
+        get_liquidity_pools(ctx, &self.address, self.chain).await
+    }
+
+    /// Holder concentration computed from indexed balances. By default burn
+    /// addresses, the token's liquidity pools and configured `KNOWN_CONTRACTS`
+    /// are left out of the denominator; `exclude` adds more addresses.
+    async fn concentration(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = true)] exclude_known_contracts: bool,
+        exclude: Option<Vec<String>>,
+    ) -> FieldResult<Concentration> {
+        let config = ctx.data::<Config>()?;
+        let (chain, address) = (self.chain, &self.address);
+        let state = self.holder_state(ctx).await?;
+
+        let mut excluded: HashSet<String> = exclude
+            .unwrap_or_default()
+            .iter()
+            .map(|a| normalize_address(chain, a))
+            .collect();
+        if exclude_known_contracts {
+            excluded.extend(config.known_contracts.iter().map(|a| normalize_address(chain, a)));
+            let pools = get_liquidity_pools(ctx, address, chain).await?;
+            excluded.extend(pools.iter().map(|p| normalize_address(chain, &p.address)));
+            excluded.insert(normalize_address(chain, address));
+        }
+
+        let balances: Vec<u128> = state
+            .balances
+            .iter()
+            .filter(|(holder, _)| {
+                let burned = exclude_known_contracts && is_burn_address(holder);
+                !burned && !excluded.contains(&normalize_address(chain, holder))
+            })
+            .map(|(_, balance)| *balance)
+            .collect();
+
+        Ok(concentration(&balances))
+    }
+
+    /// Holders bucketed by token balance and by USD value at the current
+    /// on-chain price. Edges are lower bounds in token units / USD.
+    async fn holder_distribution(
+        &self,
+        ctx: &Context<'_>,
+        balance_edges: Option<Vec<f64>>,
+        usd_edges: Option<Vec<f64>>,
+    ) -> FieldResult<HolderDistribution> {
+        let balance_edges = balance_edges.unwrap_or_else(|| DEFAULT_BALANCE_EDGES.to_vec());
+        let usd_edges = usd_edges.unwrap_or_else(|| DEFAULT_USD_EDGES.to_vec());
+        validate_edges(&balance_edges)?;
+        validate_edges(&usd_edges)?;
+
+        let config = ctx.data::<Config>()?;
+        let token = get_token(ctx, &self.address, self.chain).await?;
+        let decimals = token.decimals.unwrap_or(0);
+        let scale = 10f64.powi(decimals as i32);
+
+        let balances: Vec<f64> = self
+            .holder_state(ctx)
+            .await?
+            .balances
+            .iter()
+            .filter(|(holder, _)| !is_burn_address(holder))
+            .map(|(_, balance)| *balance as f64 / scale)
+            .collect();
+
+        let price_usd = match token.price_usd {
+            Some(price) => Some(price),
+            None => token_price_usd(config, self.chain, &self.address, decimals).await?,
+        };
+
+        Ok(HolderDistribution {
+            by_balance: bucket_holders(&balances, &balance_edges, |balance| balance),
+            by_usd: price_usd
+                .map(|price| bucket_holders(&balances, &usd_edges, |balance| balance * price))
+                .unwrap_or_default(),
+            price_usd,
+        })
+    }
+
+    /// Current holders, largest balance first. `minBalance` is in raw token units.
+    #[allow(clippy::too_many_arguments)]
+    async fn holders(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+        min_balance: Option<String>,
+    ) -> FieldResult<Connection<HolderCursor, Holder, ConnectionFields, EmptyFields>> {
+        let token = get_token(ctx, &self.address, self.chain).await?;
+        let state = self.holder_state(ctx).await?;
+        let supply = state.supply;
+        let min_balance = match min_balance.as_deref() {
+            Some(raw) => raw
+                .parse::<u128>()
+                .map_err(|_| "`minBalance` must be a non-negative integer in raw token units")?,
+            None => 0,
+        };
+        let decimals = token.decimals.unwrap_or(0);
+
+        let key = |(address, balance): &(String, u128)| (Reverse(*balance), address.clone());
+        let mut holders: Vec<(String, u128)> = state
+            .balances
+            .iter()
+            .filter(|(holder, balance)| !is_burn_address(holder) && *balance >= min_balance)
+            .cloned()
+            .collect();
+        holders.sort_by_key(key);
+        let total_count = holders.len();
+        let price_usd = token.price_usd;
+        let chain = self.chain;
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<HolderCursor>, before: Option<HolderCursor>, first, last| async move {
+                let cursor_key = |c: HolderCursor| (Reverse(c.balance), c.address);
+                let page = paginate(
+                    holders,
+                    key,
+                    after.map(cursor_key),
+                    before.map(cursor_key),
+                    first,
+                    last,
+                );
+
+                let mut connection = Connection::with_additional_fields(
+                    page.has_previous_page,
+                    page.has_next_page,
+                    ConnectionFields { total_count },
+                );
+                connection.edges.extend(page.items.into_iter().map(|(address, balance)| {
+                    let units = balance as f64 / 10f64.powi(decimals as i32);
+                    let cursor = HolderCursor { balance, address: address.clone() };
+                    Edge::new(
+                        cursor,
+                        Holder {
+                            address,
+                            balance: balance.to_string(),
+                            value_usd: price_usd.map(|price| units * price),
+                            share: (supply > 0).then(|| balance as f64 / supply as f64 * 100.0),
+                            chain,
+                        },
+                    )
+                }));
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Indexed transfers, newest first. `from`/`to` bound the unix timestamp.
+    #[allow(clippy::too_many_arguments)]
+    async fn transfers(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> FieldResult<Connection<EventCursor, Transfer, ConnectionFields, EmptyFields>> {
+        let mut transfers = self
+            .indexed_transfers_between(ctx, from.unwrap_or(i64::MIN), to.unwrap_or(i64::MAX))
+            .await?;
+        let key = |t: &Transfer| Reverse((t.block_number, t.log_index, t.tx_hash.clone()));
+        transfers.sort_by_key(key);
+        let total_count = transfers.len();
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<EventCursor>, before: Option<EventCursor>, first, last| async move {
+                let cursor_key =
+                    |c: EventCursor| Reverse((c.block_number, c.log_index, c.tx_hash));
+                let page = paginate(
+                    transfers,
+                    key,
+                    after.map(cursor_key),
+                    before.map(cursor_key),
+                    first,
+                    last,
+                );
+
+                let mut connection = Connection::with_additional_fields(
+                    page.has_previous_page,
+                    page.has_next_page,
+                    ConnectionFields { total_count },
+                );
+                connection.edges.extend(page.items.into_iter().map(|transfer| {
+                    let cursor = EventCursor {
+                        block_number: transfer.block_number,
+                        log_index: transfer.log_index,
+                        tx_hash: transfer.tx_hash.clone(),
+                    };
+                    Edge::new(cursor, transfer)
+                }));
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Decoded DEX trades, newest first. `from`/`to` bound the unix timestamp;
+    /// `side` and `trader` narrow the feed.
+    #[allow(clippy::too_many_arguments)]
+    async fn trades(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+        from: Option<i64>,
+        to: Option<i64>,
+        side: Option<TradeSide>,
+        trader: Option<String>,
+    ) -> FieldResult<Connection<EventCursor, Trade, ConnectionFields, EmptyFields>> {
+        let redis = ctx.data::<RedisPool>()?;
+        let (chain, address) = (self.chain, &self.address);
+
+        self.sync_trades(ctx).await?;
+        let trader = trader.map(|t| normalize_address(chain, &t));
+        let mut trades: Vec<Trade> = indexer::load_trades(
+            redis,
+            chain,
+            address,
+            from.unwrap_or(i64::MIN),
+            to.unwrap_or(i64::MAX),
+        )
+        .await?
+        .into_iter()
+        .filter(|t| side.is_none_or(|side| t.side == side))
+        .filter(|t| trader.as_ref().is_none_or(|trader| normalize_address(chain, &t.trader) == *trader))
+        .collect();
+        let key = |t: &Trade| Reverse((t.block_number, t.log_index, t.tx_hash.clone()));
+        trades.sort_by_key(key);
+        let total_count = trades.len();
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<EventCursor>, before: Option<EventCursor>, first, last| async move {
+                let cursor_key =
+                    |c: EventCursor| Reverse((c.block_number, c.log_index, c.tx_hash));
+                let page = paginate(
+                    trades,
+                    key,
+                    after.map(cursor_key),
+                    before.map(cursor_key),
+                    first,
+                    last,
+                );
+
+                let mut connection = Connection::with_additional_fields(
+                    page.has_previous_page,
+                    page.has_next_page,
+                    ConnectionFields { total_count },
+                );
+                connection.edges.extend(page.items.into_iter().map(|trade| {
+                    let cursor = EventCursor {
+                        block_number: trade.block_number,
+                        log_index: trade.log_index,
+                        tx_hash: trade.tx_hash.clone(),
+                    };
+                    Edge::new(cursor, trade)
+                }));
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Wallets that bought within `window` blocks (slots on Solana) of the
+    /// first indexed trade, which of them share a funder and how much supply
+    /// they still hold. `null` until the token has traded, and while the
+    /// trade index does not yet reach back to the token's first trade.
+    async fn launch_analysis(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 3)] window: u64,
+    ) -> FieldResult<Option<LaunchAnalysis>> {
+        if window == 0 || window > MAX_LAUNCH_WINDOW {
+            return Err(format!("window must be between 1 and {}", MAX_LAUNCH_WINDOW).into());
+        }
+
+        let redis = ctx.data::<RedisPool>()?;
+        let config = ctx.data::<Config>()?;
+        let (chain, address) = (self.chain, &self.address);
+
+        self.sync_trades(ctx).await?;
+        // A partial index would mistake a later trade for the launch
+        if !indexer::trades_complete(redis, chain, address).await? {
+            return Ok(None);
+        }
+        let trades = indexer::load_trades(redis, chain, address, i64::MIN, i64::MAX).await?;
+        let Some(launch) = launch_buyers(chain, &trades, window) else {
+            return Ok(None);
+        };
+
+        let state = self.holder_state(ctx).await?;
+        let supply = state.supply;
+        let balances: HashMap<String, u128> = state
+            .balances
+            .iter()
+            .map(|(holder, balance)| (normalize_address(chain, holder), *balance))
+            .collect();
+
+        // Tracing a funder takes many RPC calls, so only the largest buyers are traced
+        let loader = ctx.data::<DataLoader<TokenLoader>>()?;
+        let funders = loader
+            .load_many(
+                launch
+                    .buyers
+                    .iter()
+                    .take(config.launch_funder_limit)
+                    .map(|(wallet, block, _)| FunderKey(chain, wallet.clone(), *block)),
+            )
+            .await?;
+        let deployer = loader
+            .load_one(DeploymentKey(chain, address.clone()))
+            .await?
+            .and_then(Result::ok)
+            .map(|d| normalize_address(chain, &d.creator));
+
+        let buyers: Vec<LaunchBuyer> = launch
+            .buyers
+            .into_iter()
+            .map(|(wallet, block, bought)| {
+                let balance = balances.get(&wallet).copied().unwrap_or(0);
+                LaunchBuyer {
+                    funder: funders
+                        .get(&FunderKey(chain, wallet.clone(), block))
+                        .and_then(|f| f.as_ref().ok())
+                        .map(|f| normalize_address(chain, f)),
+                    address: wallet,
+                    first_block: block,
+                    bought: bought.to_string(),
+                    balance: balance.to_string(),
+                    share: if supply > 0 { balance as f64 / supply as f64 * 100.0 } else { 0.0 },
+                }
+            })
+            .collect();
+        let clusters = funding_clusters(&buyers);
+
+        Ok(Some(LaunchAnalysis {
+            launch_block: launch.block,
+            launch_timestamp: launch.timestamp,
+            window,
+            buyer_count: buyers.len() as u32,
+            common_funded_count: clusters.iter().map(|c| c.wallets.len() as u32).sum(),
+            deployer_funded_count: buyers
+                .iter()
+                .filter(|b| b.funder.is_some() && b.funder == deployer)
+                .count() as u32,
+            held_share: buyers.iter().map(|b| b.share).sum(),
+            clusters,
+            buyers,
+        }))
+    }
+
+    /// Raw trading volume over the last 24h and how much of it is wash
+    /// trading: trades reversed within `windowSeconds` by the same wallet or
+    /// by wallets linked through transfer cycles
+    async fn wash_trading(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 600)] window_seconds: i64,
+    ) -> FieldResult<WashTrading> {
+        if window_seconds <= 0 || window_seconds > MAX_WASH_WINDOW {
+            return Err(format!("windowSeconds must be between 1 and {}", MAX_WASH_WINDOW).into());
+        }
+
+        let redis = ctx.data::<RedisPool>()?;
+        let config = ctx.data::<Config>()?;
+        let labels = ctx.data::<LabelRegistry>()?;
+        let (chain, address) = (self.chain, &self.address);
+
+        // Trades and transfers just before the period can still close a cycle or offset a trade in it
+        let now = chrono::Utc::now().timestamp();
+        let since = now - 86_400;
+        self.sync_trades(ctx).await?;
+        let trades = indexer::load_trades(redis, chain, address, since - window_seconds, now).await?;
+        let transfers = self.indexed_transfers_between(ctx, since - window_seconds, now).await?;
+
+        let mut infrastructure: HashSet<String> = config
+            .known_contracts
+            .iter()
+            .map(|a| normalize_address(chain, a))
+            .collect();
+        let pools = get_liquidity_pools(ctx, address, chain).await?;
+        infrastructure.extend(pools.iter().map(|p| normalize_address(chain, &p.address)));
+        infrastructure.insert(normalize_address(chain, address));
+        if chain == Chain::Solana {
+            infrastructure.insert(RAYDIUM_AUTHORITY.to_string());
+        }
+        let is_infrastructure = |address: &str| {
+            infrastructure.contains(address)
+                || [LabelKind::DexPool, LabelKind::CexHotWallet, LabelKind::Bridge]
+                    .into_iter()
+                    .any(|kind| labels.has_kind(address, Some(chain), kind))
+        };
+
+        Ok(wash_trading(chain, &transfers, &trades, is_infrastructure, since, window_seconds))
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::{anyhow, Result};
+use tracing::debug;
+
+use crate::analytics::normalize_address;
+use crate::cache::{cache_get, cache_set, sorted_set_add, sorted_set_range, RedisPool};
+use crate::config::Config;
+use crate::models::{Approval, Chain};
+use crate::rpc::EthereumClient;
+
+use super::{first_block, last_block, track_token};
+
+fn events_key(chain: Chain, address: &str) -> String {
+    format!("approvals:{}:{}", chain, normalize_address(chain, address))
+}
+
+fn cursor_key(chain: Chain, address: &str) -> String {
+    format!("approvals:{}:{}:cursor", chain, normalize_address(chain, address))
+}
+
+/// Bring the `Approval` index for an ERC-20 token up to the chain head.
+/// Indexed tokens are registered as tracked.
+pub async fn sync_approvals(
+    redis: &RedisPool,
+    config: &Config,
+    chain: Chain,
+    address: &str,
+) -> Result<()> {
+    if chain != Chain::Ethereum {
+        return Err(anyhow!("Approval indexing is only supported on Ethereum"));
+    }
+
+    let key = events_key(chain, address);
+    let cursor = cursor_key(chain, address);
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+    let head = client.get_block_number().await?;
+
+    // No approvals predate the token, so a first sync starts at its creation
+    // block, and long backfills are spread over several syncs
+    let mut from = match cache_get(redis, &cursor).await? {
+        Some(last) if !last.is_empty() => last.parse::<u64>()? + 1,
+        _ => first_block(&client, config, address).await?.0,
+    };
+    let last = last_block(config, from, head);
+
+    while from <= last {
+        let to = (from + config.eth_log_chunk_size.max(1) - 1).min(last);
+        let approvals = client.get_approval_logs(address, from, to).await?;
+        debug!("Indexed {} approvals for {} in blocks {}..={}", approvals.len(), address, from, to);
+
+        let members = approvals
+            .iter()
+            .map(|a| Ok((a.timestamp, serde_json::to_string(a)?)))
+            .collect::<Result<Vec<_>>>()?;
+        sorted_set_add(redis, &key, &members).await?;
+        cache_set(redis, &cursor, &to.to_string(), 0).await?;
+        from = to + 1;
+    }
+
+    track_token(redis, chain, address).await
+}
+
+/// Load every indexed approval for a token, oldest first
+pub async fn load_approvals(redis: &RedisPool, chain: Chain, address: &str) -> Result<Vec<Approval>> {
+    let members = sorted_set_range(redis, &events_key(chain, address), i64::MIN, i64::MAX).await?;
+    let mut approvals = members
+        .iter()
+        .map(|m| serde_json::from_str::<Approval>(m))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    approvals.sort_by_key(|a| (a.block_number, a.log_index));
+    Ok(approvals)
+}
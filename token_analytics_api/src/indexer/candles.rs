@@ -0,0 +1,83 @@
+use anyhow::Result;
+use tracing::debug;
+
+use crate::analytics::{candles::build_candles, normalize_address};
+use crate::cache::{cache_get, cache_set, sorted_set_add, sorted_set_range, sorted_set_remove_range, RedisPool};
+use crate::config::Config;
+use crate::models::{Candle, Chain, Interval};
+
+use super::trades::{load_trades, stored_since, sync_trades};
+
+// Candles are JSON members scored by bucket start, one sorted set per interval
+fn candles_key(chain: Chain, address: &str, interval: Interval) -> String {
+    format!("candles:{}:{}:{}", chain, normalize_address(chain, address), interval.seconds())
+}
+
+fn cursor_key(chain: Chain, address: &str, interval: Interval) -> String {
+    format!("{}:cursor", candles_key(chain, address, interval))
+}
+
+/// Bring the trade index up to date and fold new trades into the stored
+/// candles. The cursor holds the last stored bucket and the last trade batch
+/// folded in. Candles are rebuilt from the earlier of that bucket, which may
+/// have been persisted while still open, and the bucket of the oldest trade
+/// stored since, which can predate it when Solana backfills older trades.
+pub async fn sync_candles(
+    redis: &RedisPool,
+    config: &Config,
+    chain: Chain,
+    address: &str,
+    decimals: u8,
+    interval: Interval,
+) -> Result<()> {
+    sync_trades(redis, config, chain, address).await?;
+
+    let key = candles_key(chain, address, interval);
+    let cursor = cursor_key(chain, address, interval);
+    // Cursors from before batches were tracked hold only a timestamp and rebuild everything
+    let (last_bucket, seen) = match cache_get(redis, &cursor).await?.as_deref().and_then(|c| c.split_once(':')) {
+        Some((bucket, seq)) => (Some(bucket.parse::<i64>()?), seq.parse::<i64>()?),
+        None => (None, 0),
+    };
+    let (latest, oldest_new) = stored_since(redis, chain, address, seen).await?;
+    let since = match (last_bucket, oldest_new) {
+        (Some(_), None) => return Ok(()),
+        (Some(bucket), Some(oldest)) => bucket.min(oldest - oldest.rem_euclid(interval.seconds())),
+        (None, _) => i64::MIN,
+    };
+
+    let trades = load_trades(redis, chain, address, since, i64::MAX).await?;
+    let candles = build_candles(&trades, decimals, interval);
+    let Some(last) = candles.last().map(|c| c.timestamp) else {
+        return Ok(());
+    };
+    debug!("Rebuilt {} candles for {} from {} trades", candles.len(), address, trades.len());
+
+    let members = candles
+        .iter()
+        .map(|c| Ok((c.timestamp, serde_json::to_string(c)?)))
+        .collect::<Result<Vec<_>>>()?;
+    sorted_set_remove_range(redis, &key, since, i64::MAX).await?;
+    sorted_set_add(redis, &key, &members).await?;
+    cache_set(redis, &cursor, &format!("{}:{}", last, latest), 0).await?;
+
+    Ok(())
+}
+
+/// Load stored candles whose bucket starts within `from..=to`, oldest first
+pub async fn load_candles(
+    redis: &RedisPool,
+    chain: Chain,
+    address: &str,
+    interval: Interval,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Candle>> {
+    let members = sorted_set_range(redis, &candles_key(chain, address, interval), from, to).await?;
+    let mut candles = members
+        .iter()
+        .map(|m| serde_json::from_str::<Candle>(m))
+        .collect::<Result<Vec<_>, _>>()?;
+    candles.sort_by_key(|c| c.timestamp);
+    Ok(candles)
+}
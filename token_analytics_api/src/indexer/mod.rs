@@ -0,0 +1,160 @@
+//! Incremental on-chain event indexing backed by Redis sorted sets
+
+pub mod approvals;
+pub mod candles;
+pub mod trades;
+pub mod transfers;
+
+use anyhow::Result;
+use tracing::debug;
+
+use crate::analytics::normalize_address;
+use crate::cache::{cache_get, cache_get_many, cache_set, set_add, set_members, set_remove, RedisPool};
+use crate::config::Config;
+use crate::models::Chain;
+use crate::rpc::solana::{SignatureInfo, SolanaClient};
+use crate::rpc::EthereumClient;
+
+pub use approvals::{load_approvals, sync_approvals};
+pub use candles::{load_candles, sync_candles};
+pub use trades::{invalidate_trade_pools, load_trades, sync_trades, trades_complete};
+pub use transfers::{
+    invalidate_holder_state, load_complete_transfers, load_holder_state, load_transfers, sync_transfers,
+    transfers_complete,
+};
+
+/// Requests in flight at once while indexing
+const RPC_CONCURRENCY: usize = 8;
+
+/// Backfill cursor value once an address's history has been fully indexed
+const BACKFILL_DONE: &str = "done";
+
+fn tracked_key(chain: Chain) -> String {
+    format!("tracked:{}", chain)
+}
+
+/// Register a token as tracked so wallet-level queries include it
+pub async fn track_token(redis: &RedisPool, chain: Chain, address: &str) -> Result<()> {
+    set_add(redis, &tracked_key(chain), &normalize_address(chain, address)).await
+}
+
+pub async fn tracked_tokens(redis: &RedisPool, chain: Chain) -> Result<Vec<String>> {
+    set_members(redis, &tracked_key(chain)).await
+}
+
+pub async fn untrack_token(redis: &RedisPool, chain: Chain, address: &str) -> Result<bool> {
+    set_remove(redis, &tracked_key(chain), &normalize_address(chain, address)).await
+}
+
+/// Block a first Ethereum sync of a token's events starts at: its creation
+/// block when it can be found (this needs an archive node), but no earlier
+/// than `index_start_block`. Also returns whether that start covers the
+/// token's whole history.
+async fn first_block(client: &EthereumClient, config: &Config, address: &str) -> Result<(u64, bool)> {
+    let created = match client.get_creation_block(address.parse()?).await {
+        Ok(created) => created,
+        Err(e) => {
+            debug!("No creation block for {}: {}", address, e);
+            None
+        }
+    };
+    let complete = match created {
+        Some(created) => created >= config.index_start_block,
+        None => config.index_start_block == 0,
+    };
+    Ok((created.unwrap_or_default().max(config.index_start_block), complete))
+}
+
+/// Last block one Ethereum sync starting at `from` scans: the chain head, or
+/// `eth_sync_block_limit` blocks on for a long backfill
+fn last_block(config: &Config, from: u64, head: u64) -> u64 {
+    head.min(from.saturating_add(config.eth_sync_block_limit.max(1) - 1))
+}
+
+/// Signatures of one Solana address still to be indexed, and the cursors to
+/// store once they have been
+pub(crate) struct PendingSignatures {
+    pub signatures: Vec<SignatureInfo>,
+    cursor: String,
+    newest: Option<String>,
+    backfill: String,
+}
+
+impl PendingSignatures {
+    /// Whether the address's history back to its first signature is covered
+    pub fn complete(&self) -> bool {
+        self.backfill == BACKFILL_DONE
+    }
+
+    /// Record the signatures as indexed
+    pub async fn commit(&self, redis: &RedisPool) -> Result<()> {
+        if let Some(newest) = &self.newest {
+            cache_set(redis, &self.cursor, newest, 0).await?;
+        }
+        cache_set(redis, &format!("{}:backfill", self.cursor), &self.backfill, 0).await
+    }
+}
+
+/// Whether the backfills under every one of `cursors` have reached the
+/// first signature of their address
+pub(crate) async fn backfills_complete(redis: &RedisPool, cursors: &[String]) -> Result<bool> {
+    let keys: Vec<String> = cursors.iter().map(|cursor| format!("{}:backfill", cursor)).collect();
+    Ok(cache_get_many(redis, &keys)
+        .await?
+        .iter()
+        .all(|backfill| backfill.as_deref() == Some(BACKFILL_DONE)))
+}
+
+/// Signatures of `address` not yet indexed under `cursor`. `cursor` holds the
+/// newest indexed signature and everything since it is fetched, however
+/// much that is. `{cursor}:backfill` holds the oldest one reached so far,
+/// and each sync walks up to `limit` signatures further back until the
+/// history runs out.
+pub(crate) async fn pending_signatures(
+    redis: &RedisPool,
+    client: &SolanaClient,
+    cursor: &str,
+    address: &str,
+    limit: usize,
+) -> Result<PendingSignatures> {
+    let limit = limit.max(1);
+    let newest = cache_get(redis, cursor).await?.filter(|s| !s.is_empty());
+    let backfill = cache_get(redis, &format!("{}:backfill", cursor)).await?.filter(|s| !s.is_empty());
+
+    // Without a backfill cursor (a first sync, or an index from before they
+    // existed) start again from the newest signature; re-indexed transfers
+    // are stored under the same members, so overlaps are harmless
+    let Some(backfill) = backfill else {
+        let signatures = client.get_signatures(address, None, None, limit).await?;
+        let backfill = match signatures.last() {
+            Some(oldest) if signatures.len() >= limit => oldest.signature.clone(),
+            _ => BACKFILL_DONE.to_string(),
+        };
+        return Ok(PendingSignatures {
+            newest: signatures.first().map(|s| s.signature.clone()),
+            cursor: cursor.to_string(),
+            signatures,
+            backfill,
+        });
+    };
+
+    let mut signatures = client.get_signatures(address, None, newest.as_deref(), usize::MAX).await?;
+    let newest = signatures.first().map(|s| s.signature.clone()).or(newest);
+
+    let mut next_backfill = backfill.clone();
+    if backfill != BACKFILL_DONE {
+        let older = client.get_signatures(address, Some(&backfill), None, limit).await?;
+        next_backfill = match older.last() {
+            Some(oldest) if older.len() >= limit => oldest.signature.clone(),
+            _ => BACKFILL_DONE.to_string(),
+        };
+        signatures.extend(older);
+    }
+
+    Ok(PendingSignatures {
+        signatures,
+        cursor: cursor.to_string(),
+        newest,
+        backfill: next_backfill,
+    })
+}
@@ -0,0 +1,360 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use futures::{stream, StreamExt, TryStreamExt};
+use tracing::debug;
+use web3::types::{Address, U256};
+
+use crate::analytics::normalize_address;
+use crate::cache::{cache_delete, cache_get, cache_set, counter_incr, sorted_set_add, sorted_set_range, RedisPool};
+use crate::config::Config;
+use crate::models::{Chain, Trade, TradeSide};
+use crate::pricing::{eth_usd_at_block, to_units, USDC_DECIMALS, WETH_DECIMALS};
+use crate::rpc::ethereum::{PoolSwap, UNISWAP_V2_FACTORY, UNISWAP_V3_FACTORY, USDC, V3_FEE_TIERS, WETH};
+use crate::rpc::solana::{USDC_MINT, WSOL_MINT};
+use crate::rpc::{EthereumClient, SolanaClient};
+use crate::pricing::native_price_usd;
+use super::{first_block, last_block, pending_signatures, PendingSignatures, RPC_CONCURRENCY};
+
+// Trades are stored like transfers: JSON members scored by timestamp
+fn events_key(chain: Chain, address: &str) -> String {
+    format!("trades:{}:{}", chain, normalize_address(chain, address))
+}
+
+fn cursor_key(chain: Chain, address: &str) -> String {
+    format!("trades:{}:{}:cursor", chain, normalize_address(chain, address))
+}
+
+/// `"1"` once the index reaches back to the token's first trade
+fn complete_key(chain: Chain, address: &str) -> String {
+    format!("trades:{}:{}:complete", chain, normalize_address(chain, address))
+}
+
+/// Whether the indexed trades cover the token's whole trading history, as
+/// `transfers_complete` does for transfers
+pub async fn trades_complete(redis: &RedisPool, chain: Chain, address: &str) -> Result<bool> {
+    Ok(cache_get(redis, &complete_key(chain, address)).await?.as_deref() == Some("1"))
+}
+
+/// The token's Uniswap pools as `(pool, dex, quote, fee)`, so syncs do not
+/// query the factories every time
+fn pools_key(address: &str) -> String {
+    format!("trades:{}:{}:pools", Chain::Ethereum, normalize_address(Chain::Ethereum, address))
+}
+
+async fn set_complete(redis: &RedisPool, chain: Chain, address: &str, complete: bool) -> Result<()> {
+    cache_set(redis, &complete_key(chain, address), if complete { "1" } else { "0" }, 0).await
+}
+
+// Each stored batch is logged as `seq:oldest timestamp`, scored by its
+// sequence number, so candle builds can find trades stored out of order
+fn batches_key(chain: Chain, address: &str) -> String {
+    format!("trades:{}:{}:batches", chain, normalize_address(chain, address))
+}
+
+async fn store(redis: &RedisPool, chain: Chain, address: &str, trades: &[Trade]) -> Result<()> {
+    let members = trades
+        .iter()
+        .map(|t| Ok((t.timestamp, serde_json::to_string(t)?)))
+        .collect::<Result<Vec<_>>>()?;
+    sorted_set_add(redis, &events_key(chain, address), &members).await?;
+
+    let Some(oldest) = trades.iter().map(|t| t.timestamp).min() else {
+        return Ok(());
+    };
+    let batches = batches_key(chain, address);
+    let seq = counter_incr(redis, &format!("{}:seq", batches)).await? as i64;
+    sorted_set_add(redis, &batches, &[(seq, format!("{}:{}", seq, oldest))]).await
+}
+
+/// Sequence number of the latest stored batch of trades, and the oldest
+/// trade timestamp among batches stored after `seq`
+pub(crate) async fn stored_since(redis: &RedisPool, chain: Chain, address: &str, seq: i64) -> Result<(i64, Option<i64>)> {
+    let mut latest = seq;
+    let mut oldest = None;
+    for member in sorted_set_range(redis, &batches_key(chain, address), seq.saturating_add(1), i64::MAX).await? {
+        let Some((batch, timestamp)) = member.split_once(':') else {
+            continue;
+        };
+        latest = latest.max(batch.parse()?);
+        let timestamp: i64 = timestamp.parse()?;
+        oldest = Some(oldest.map_or(timestamp, |o: i64| o.min(timestamp)));
+    }
+    Ok((latest, oldest))
+}
+
+/// Bring the trade index for a token up to the chain head
+pub async fn sync_trades(
+    redis: &RedisPool,
+    config: &Config,
+    chain: Chain,
+    address: &str,
+) -> Result<()> {
+    match chain {
+        Chain::Ethereum => sync_ethereum(redis, config, address).await,
+        Chain::Solana => sync_solana(redis, config, address).await,
+        // No swap decoder for these chains yet
+        Chain::Bsc | Chain::Other => Ok(()),
+    }
+}
+
+/// A Uniswap pool pairing the indexed token with a quote asset
+pub(crate) struct EvmPool {
+    pub address: Address,
+    pub dex: &'static str,
+    pub quote: &'static str,
+    pub quote_decimals: u8,
+    pub token_is_token0: bool,
+    /// Swap fee in hundredths of a basis point
+    pub fee: u32,
+}
+
+/// The token's USDC and WETH pools on Uniswap V2 and every V3 fee tier
+pub(crate) async fn evm_pools(client: &EthereumClient, address: &str) -> Result<Vec<EvmPool>> {
+    let token = address.to_lowercase();
+    let mut pools = Vec::new();
+    for (quote, quote_decimals) in [(USDC, USDC_DECIMALS), (WETH, WETH_DECIMALS)] {
+        if token == quote.to_lowercase() {
+            continue;
+        }
+        let token_is_token0 = token < quote.to_lowercase();
+        if let Some(pair) = client.get_v2_pair(UNISWAP_V2_FACTORY, address, quote).await? {
+            pools.push(EvmPool { address: pair, dex: "uniswap_v2", quote, quote_decimals, token_is_token0, fee: 3000 });
+        }
+        for fee in V3_FEE_TIERS {
+            if let Some(pool) = client.get_v3_pool(UNISWAP_V3_FACTORY, address, quote, fee).await? {
+                pools.push(EvmPool { address: pool, dex: "uniswap_v3", quote, quote_decimals, token_is_token0, fee });
+            }
+        }
+    }
+    Ok(pools)
+}
+
+/// `evm_pools`, cached for `cache_ttl` seconds so pools created since are
+/// picked up once it expires
+async fn cached_evm_pools(redis: &RedisPool, config: &Config, client: &EthereumClient, address: &str) -> Result<Vec<EvmPool>> {
+    let key = pools_key(address);
+    let Some(cached) = cache_get(redis, &key).await? else {
+        let pools = evm_pools(client, address).await?;
+        let entries: Vec<(Address, &str, &str, u32)> =
+            pools.iter().map(|p| (p.address, p.dex, p.quote, p.fee)).collect();
+        cache_set(redis, &key, &serde_json::to_string(&entries)?, config.cache_ttl.max(1)).await?;
+        return Ok(pools);
+    };
+
+    let token = address.to_lowercase();
+    let entries: Vec<(Address, String, String, u32)> = serde_json::from_str(&cached)?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|(pool, dex, quote, fee)| {
+            let dex = ["uniswap_v2", "uniswap_v3"].into_iter().find(|d| *d == dex)?;
+            let (quote, quote_decimals) = [(USDC, USDC_DECIMALS), (WETH, WETH_DECIMALS)]
+                .into_iter()
+                .find(|(q, _)| *q == quote)?;
+            let token_is_token0 = token < quote.to_lowercase();
+            Some(EvmPool { address: pool, dex, quote, quote_decimals, token_is_token0, fee })
+        })
+        .collect())
+}
+
+/// Drop the cached pool list so the next sync looks for new pools
+pub async fn invalidate_trade_pools(redis: &RedisPool, chain: Chain, address: &str) -> Result<()> {
+    match chain {
+        Chain::Ethereum => cache_delete(redis, &pools_key(address)).await,
+        _ => Ok(()),
+    }
+}
+
+async fn sync_ethereum(redis: &RedisPool, config: &Config, address: &str) -> Result<()> {
+    let cursor = cursor_key(Chain::Ethereum, address);
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+    let head = client.get_block_number().await?;
+
+    let token = address.to_lowercase();
+    let pools = cached_evm_pools(redis, config, &client, address).await?;
+    if pools.is_empty() {
+        // Without a pool there is no trade history to miss
+        return set_complete(redis, Chain::Ethereum, address, true).await;
+    }
+    let (_, _, decimals) = client.get_token_info(address).await?;
+
+    // No pool can trade the token before it exists, so a first sync starts
+    // at its creation block like the transfer index does
+    let mut from = match cache_get(redis, &cursor).await? {
+        Some(last) if !last.is_empty() => last.parse::<u64>()? + 1,
+        _ => {
+            let (first, complete) = first_block(&client, config, address).await?;
+            set_complete(redis, Chain::Ethereum, address, complete).await?;
+            first
+        }
+    };
+
+    let last = last_block(config, from, head);
+    while from <= last {
+        let to = (from + config.eth_log_chunk_size.max(1) - 1).min(last);
+
+        let mut swaps = Vec::new();
+        for pool in &pools {
+            let address = format!("{:?}", pool.address);
+            let logs = match pool.dex {
+                "uniswap_v3" => client.get_v3_swap_logs(&address, from, to).await?,
+                _ => client.get_v2_swap_logs(&address, from, to).await?,
+            };
+            swaps.extend(logs.into_iter().map(|s| (pool, s)));
+        }
+
+        let hashes: Vec<_> = swaps.iter().map(|(_, s)| s.tx_hash).collect::<HashSet<_>>().into_iter().collect();
+        let senders = client.get_transaction_senders(&hashes).await?;
+
+        let mut eth_usd: HashMap<u64, Option<f64>> = HashMap::new();
+        let mut trades = Vec::with_capacity(swaps.len());
+        for (pool, swap) in swaps {
+            let quote_usd = if pool.quote == USDC {
+                Some(1.0)
+            } else {
+                match eth_usd.get(&swap.block_number) {
+                    Some(price) => *price,
+                    None => {
+                        // Historical reserves need an archive node; trades stay unpriced without one
+                        let price = eth_usd_at_block(&client, swap.block_number).await.unwrap_or_else(|e| {
+                            debug!("No ETH/USD price at block {}: {}", swap.block_number, e);
+                            None
+                        });
+                        eth_usd.insert(swap.block_number, price);
+                        price
+                    }
+                }
+            };
+            let trader = senders.get(&swap.tx_hash).copied().unwrap_or(swap.to);
+            if let Some(trade) = decode_evm_trade(&token, decimals, pool, &swap, trader, quote_usd) {
+                trades.push(trade);
+            }
+        }
+        debug!("Indexed {} trades for {} in blocks {}..={}", trades.len(), address, from, to);
+
+        store(redis, Chain::Ethereum, address, &trades).await?;
+        cache_set(redis, &cursor, &to.to_string(), 0).await?;
+        from = to + 1;
+    }
+
+    Ok(())
+}
+
+/// Turn a pool swap into a buy or sell of `token`. Swaps that move the token in
+/// both directions (flash swaps, arbitrage legs) are skipped.
+fn decode_evm_trade(
+    token: &str,
+    decimals: u8,
+    pool: &EvmPool,
+    swap: &PoolSwap,
+    trader: Address,
+    quote_usd: Option<f64>,
+) -> Option<Trade> {
+    let (token_in, token_out, quote_in, quote_out) = if pool.token_is_token0 {
+        (swap.amount0_in, swap.amount0_out, swap.amount1_in, swap.amount1_out)
+    } else {
+        (swap.amount1_in, swap.amount1_out, swap.amount0_in, swap.amount0_out)
+    };
+
+    let (side, token_amount, quote_amount) = match (token_in.is_zero(), token_out.is_zero()) {
+        (true, false) if !quote_in.is_zero() => (TradeSide::Buy, token_out, quote_in),
+        (false, true) if !quote_out.is_zero() => (TradeSide::Sell, token_in, quote_out),
+        _ => return None,
+    };
+
+    let units = to_units(token_amount, decimals);
+    let price_usd = quote_usd
+        .filter(|_| units > 0.0)
+        .map(|quote_usd| to_units(quote_amount, pool.quote_decimals) * quote_usd / units);
+    let token = token.to_string();
+    let quote = pool.quote.to_lowercase();
+    let (token_in, amount_in, token_out, amount_out): (String, U256, String, U256) = match side {
+        TradeSide::Buy => (quote, quote_amount, token, token_amount),
+        TradeSide::Sell => (token, token_amount, quote, quote_amount),
+    };
+
+    Some(Trade {
+        tx_hash: format!("{:?}", swap.tx_hash),
+        block_number: swap.block_number,
+        log_index: swap.log_index,
+        timestamp: swap.timestamp,
+        pool: format!("{:?}", swap.pool),
+        dex: pool.dex.to_string(),
+        side,
+        trader: format!("{:?}", trader),
+        token_in,
+        amount_in: amount_in.to_string(),
+        token_out,
+        amount_out: amount_out.to_string(),
+        price_usd,
+        volume_usd: price_usd.map(|price| price * units),
+    })
+}
+
+/// Solana trades are decoded per pool from the pool account's signatures,
+/// since swap instructions do not name the token mint. Each pool keeps its
+/// own signature cursor: new signatures are always fetched in full, and the
+/// backfill walks `sol_signature_limit` signatures further back per sync.
+async fn sync_solana(redis: &RedisPool, config: &Config, address: &str) -> Result<()> {
+    let client = SolanaClient::new(&config.sol_rpc_url);
+
+    let pools: Vec<_> = client
+        .get_dex_pools(address)
+        .await?
+        .into_iter()
+        .filter(|p| matches!(p.other_mint(address), USDC_MINT | WSOL_MINT))
+        .collect();
+    // SOL legs are priced at the SOL/USD rate of the sync, not of each trade
+    let sol_usd = if pools.iter().any(|p| p.other_mint(address) == WSOL_MINT) {
+        native_price_usd(config, Chain::Solana).await.unwrap_or_else(|e| {
+            debug!("No SOL/USD price: {}", e);
+            None
+        })
+    } else {
+        None
+    };
+
+    let mut complete = true;
+    for pool in &pools {
+        let quote_usd = if pool.other_mint(address) == USDC_MINT { Some(1.0) } else { sol_usd };
+        let cursor = format!("{}:{}", cursor_key(Chain::Solana, address), pool.address);
+        let pending: PendingSignatures =
+            pending_signatures(redis, &client, &cursor, &pool.address, config.sol_signature_limit).await?;
+
+        let lookups: Vec<_> = pending
+            .signatures
+            .iter()
+            .map(|signature| client.get_swap(pool, address, signature, quote_usd))
+            .collect();
+        let trades: Vec<Option<Trade>> = stream::iter(lookups)
+            .buffered(RPC_CONCURRENCY)
+            .try_collect()
+            .await?;
+        let trades: Vec<Trade> = trades.into_iter().flatten().collect();
+        debug!("Indexed {} {} trades for {} from pool {}", trades.len(), pool.dex, address, pool.address);
+
+        store(redis, Chain::Solana, address, &trades).await?;
+        pending.commit(redis).await?;
+        complete &= pending.complete();
+    }
+    set_complete(redis, Chain::Solana, address, complete).await?;
+
+    Ok(())
+}
+
+/// Load indexed trades with `from <= timestamp <= to`, ordered by block and log index
+pub async fn load_trades(
+    redis: &RedisPool,
+    chain: Chain,
+    address: &str,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Trade>> {
+    let members = sorted_set_range(redis, &events_key(chain, address), from, to).await?;
+    let mut trades = members
+        .iter()
+        .map(|m| serde_json::from_str::<Trade>(m))
+        .collect::<Result<Vec<_>, _>>()?;
+    trades.sort_by_key(|t| (t.block_number, t.log_index));
+    Ok(trades)
+}
@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+use futures::{stream, StreamExt, TryStreamExt};
+use tracing::debug;
+
+use crate::analytics::holders::{holder_balances, HolderState};
+use crate::analytics::supply::current_supply;
+use crate::cache::{
+    cache_delete, cache_get, cache_set, set_add_many, set_members, sorted_set_add, sorted_set_len, sorted_set_range, RedisPool,
+};
+use crate::config::Config;
+use crate::analytics::{classify_transfer, normalize_address};
+use crate::models::{Chain, Transfer, TransferKind};
+use crate::rpc::{solana::SignatureInfo, EthereumClient, SolanaClient};
+
+use super::{backfills_complete, first_block, last_block, pending_signatures, PendingSignatures, RPC_CONCURRENCY};
+
+// Transfers are stored as JSON members scored by their timestamp, so time
+// ranges map directly onto ZRANGEBYSCORE. Keys use the normalized address so
+// differently cased EVM addresses share one index.
+fn events_key(chain: Chain, address: &str) -> String {
+    format!("transfers:{}:{}", chain, normalize_address(chain, address))
+}
+
+fn cursor_key(chain: Chain, address: &str) -> String {
+    format!("transfers:{}:{}:cursor", chain, normalize_address(chain, address))
+}
+
+/// Holder state replayed from the index when it held `version` transfers
+fn holders_key(chain: Chain, address: &str, version: u64) -> String {
+    format!("transfers:{}:{}:holders:{}", chain, normalize_address(chain, address), version)
+}
+
+/// `"1"` once the index reaches back to the token's creation and has caught
+/// up with the chain, `"0"` if it cannot reach back that far, and
+/// `"behind"` while an Ethereum backfill is still catching up
+fn complete_key(chain: Chain, address: &str) -> String {
+    format!("transfers:{}:{}:complete", chain, normalize_address(chain, address))
+}
+
+const BEHIND: &str = "behind";
+
+/// Whether the indexed transfers cover the token's whole history, so that
+/// balances and supply replayed from them are exact. Indexes built before
+/// this was recorded report `false` until their keys are deleted and rebuilt.
+pub async fn transfers_complete(redis: &RedisPool, chain: Chain, address: &str) -> Result<bool> {
+    Ok(cache_get(redis, &complete_key(chain, address)).await?.as_deref() == Some("1"))
+}
+
+async fn set_complete(redis: &RedisPool, chain: Chain, address: &str, complete: bool) -> Result<()> {
+    cache_set(redis, &complete_key(chain, address), if complete { "1" } else { "0" }, 0).await
+}
+
+async fn store(redis: &RedisPool, key: &str, transfers: &[Transfer]) -> Result<()> {
+    let members = transfers
+        .iter()
+        .map(|t| Ok((t.timestamp, serde_json::to_string(t)?)))
+        .collect::<Result<Vec<_>>>()?;
+    sorted_set_add(redis, key, &members).await
+}
+
+/// Bring the transfer index for a token up to the chain head
+pub async fn sync_transfers(
+    redis: &RedisPool,
+    config: &Config,
+    chain: Chain,
+    address: &str,
+) -> Result<()> {
+    match chain {
+        Chain::Ethereum => sync_ethereum(redis, config, address).await,
+        Chain::Solana => sync_solana(redis, config, address).await,
+        // No indexer for these chains yet
+        Chain::Bsc | Chain::Other => Ok(()),
+    }
+}
+
+async fn sync_ethereum(redis: &RedisPool, config: &Config, address: &str) -> Result<()> {
+    let key = events_key(Chain::Ethereum, address);
+    let cursor = cursor_key(Chain::Ethereum, address);
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+    let head = client.get_block_number().await?;
+
+    // The cursor holds the last fully indexed block. A first sync starting
+    // any later than the token's creation leaves the index incomplete.
+    let mut from = match cache_get(redis, &cursor).await? {
+        Some(last) if !last.is_empty() => last.parse::<u64>()? + 1,
+        _ => {
+            let (first, complete) = first_block(&client, config, address).await?;
+            set_complete(redis, Chain::Ethereum, address, complete).await?;
+            first
+        }
+    };
+
+    // Each sync scans at most `eth_sync_block_limit` blocks, so a long
+    // backfill is spread over several requests
+    let last = last_block(config, from, head);
+    while from <= last {
+        let to = (from + config.eth_log_chunk_size.max(1) - 1).min(last);
+        let transfers = client.get_transfer_logs(address, from, to).await?;
+        debug!("Indexed {} transfers for {} in blocks {}..={}", transfers.len(), address, from, to);
+
+        store(redis, &key, &transfers).await?;
+        cache_set(redis, &cursor, &to.to_string(), 0).await?;
+        from = to + 1;
+    }
+
+    // Replayed balances are only current once the backfill has caught up
+    let complete = complete_key(Chain::Ethereum, address);
+    if cache_get(redis, &complete).await?.as_deref() != Some("0") {
+        cache_set(redis, &complete, if from > head { "1" } else { BEHIND }, 0).await?;
+    }
+
+    Ok(())
+}
+
+/// Token accounts ever seen holding a Solana mint. Accounts stay listed
+/// after they close, so their history is still followed.
+fn accounts_key(address: &str) -> String {
+    format!("transfers:{}:{}:accounts", Chain::Solana, address)
+}
+
+async fn sync_solana(redis: &RedisPool, config: &Config, address: &str) -> Result<()> {
+    let key = events_key(Chain::Solana, address);
+    let cursor = cursor_key(Chain::Solana, address);
+    let client = SolanaClient::new(&config.sol_rpc_url);
+
+    // Plain `transfer` instructions name only the two token accounts, so
+    // follow the accounts' signatures as well as the mint's
+    let accounts_key = accounts_key(address);
+    let current: Vec<String> = client
+        .get_token_accounts(address)
+        .await?
+        .into_iter()
+        .map(|(account, _)| account)
+        .collect();
+    set_add_many(redis, &accounts_key, &current).await?;
+    let mut known = set_members(redis, &accounts_key).await?;
+    known.sort();
+
+    // Each sync follows the mint and the next `sol_account_sync_limit`
+    // accounts, rotating through the known ones
+    let offset_key = format!("{}:offset", accounts_key);
+    let offset = cache_get(redis, &offset_key).await?.and_then(|o| o.parse::<usize>().ok()).unwrap_or(0);
+    let offset = offset % known.len().max(1);
+    let window = config.sol_account_sync_limit.max(1).min(known.len());
+    let mut followed = vec![(address.to_string(), cursor.clone())];
+    followed.extend(known.iter().cycle().skip(offset).take(window).map(|account| {
+        (account.clone(), format!("{}:{}", cursor, account))
+    }));
+
+    let lookups: Vec<_> = followed
+        .iter()
+        .map(|(account, cursor)| pending_signatures(redis, &client, cursor, account, config.sol_signature_limit))
+        .collect();
+    let pending: Vec<PendingSignatures> = stream::iter(lookups)
+        .buffered(RPC_CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    // A transfer between two followed accounts is listed under both
+    let mut seen = HashSet::new();
+    let signatures: Vec<&SignatureInfo> = pending
+        .iter()
+        .flat_map(|p| &p.signatures)
+        .filter(|s| seen.insert(s.signature.as_str()))
+        .collect();
+
+    let lookups: Vec<_> = signatures
+        .iter()
+        .map(|signature| client.get_token_transfers(address, signature))
+        .collect();
+    let transfers: Vec<Vec<Transfer>> = stream::iter(lookups)
+        .buffered(RPC_CONCURRENCY)
+        .try_collect()
+        .await?;
+    let transfers: Vec<Transfer> = transfers.into_iter().flatten().collect();
+    debug!(
+        "Indexed {} transfers for {} from {} signatures across {} of {} accounts",
+        transfers.len(),
+        address,
+        signatures.len(),
+        window,
+        known.len()
+    );
+
+    store(redis, &key, &transfers).await?;
+    for p in &pending {
+        p.commit(redis).await?;
+    }
+    cache_set(redis, &offset_key, &((offset + window) % known.len().max(1)).to_string(), 0).await?;
+
+    // Complete once the mint and every known account reach back to their first signature
+    let mut cursors = vec![cursor.clone()];
+    cursors.extend(known.iter().map(|account| format!("{}:{}", cursor, account)));
+    set_complete(redis, Chain::Solana, address, backfills_complete(redis, &cursors).await?).await?;
+
+    Ok(())
+}
+
+/// Load indexed transfers with `from <= timestamp <= to`, ordered by
+/// timestamp, block and log index
+pub async fn load_transfers(
+    redis: &RedisPool,
+    chain: Chain,
+    address: &str,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Transfer>> {
+    let members = sorted_set_range(redis, &events_key(chain, address), from, to).await?;
+    let mut transfers = members
+        .iter()
+        .map(|m| serde_json::from_str::<Transfer>(m))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Entries indexed before mints and burns were classified default to plain transfers
+    for transfer in transfers.iter_mut() {
+        transfer.chain = chain;
+        if transfer.kind == TransferKind::Transfer {
+            transfer.kind = classify_transfer(&transfer.from, &transfer.to);
+        }
+    }
+
+    transfers.sort_by_key(|t| (t.timestamp, t.block_number, t.log_index));
+    Ok(transfers)
+}
+
+/// Load a token's whole indexed history for replaying balances and supply,
+/// failing while the index does not yet reach back to the token's creation
+pub async fn load_complete_transfers(redis: &RedisPool, chain: Chain, address: &str) -> Result<Vec<Transfer>> {
+    if !transfers_complete(redis, chain, address).await? {
+        return Err(anyhow!(
+            "Transfer history of {} on {} is still being backfilled; balances and supply are unavailable until it completes",
+            address,
+            chain
+        ));
+    }
+    load_transfers(redis, chain, address, i64::MIN, i64::MAX).await
+}
+
+/// Drop the cached holder state replayed from the index at its current size
+pub async fn invalidate_holder_state(redis: &RedisPool, chain: Chain, address: &str) -> Result<()> {
+    let version = sorted_set_len(redis, &events_key(chain, address)).await?;
+    cache_delete(redis, &holders_key(chain, address, version)).await
+}
+
+/// Supply and holder balances replayed from the complete index. The index
+/// only grows, so its size identifies what a replay covered; replays are
+/// cached in Redis for `ttl` seconds under it and reused until new
+/// transfers are indexed.
+pub async fn load_holder_state(redis: &RedisPool, chain: Chain, address: &str, ttl: u64) -> Result<HolderState> {
+    let version = sorted_set_len(redis, &events_key(chain, address)).await?;
+    let key = holders_key(chain, address, version);
+    if transfers_complete(redis, chain, address).await? {
+        if let Some(cached) = cache_get(redis, &key).await? {
+            return Ok(serde_json::from_str(&cached)?);
+        }
+    }
+
+    let transfers = load_complete_transfers(redis, chain, address).await?;
+    let state = HolderState {
+        supply: current_supply(&transfers),
+        balances: holder_balances(&transfers),
+    };
+    cache_set(redis, &key, &serde_json::to_string(&state)?, ttl).await?;
+    Ok(state)
+}
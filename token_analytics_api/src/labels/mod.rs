@@ -0,0 +1,189 @@
+//! Address labels from a local dataset plus automatically detected ones
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Result};
+use async_graphql::{Enum, SimpleObject};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::analytics::{is_zero_address, EVM_DEAD_ADDRESS};
+use crate::cache::{hash_set, hash_values, RedisPool};
+use crate::models::Chain;
+
+/// Redis hash of detected labels, one field per chain, address and kind, so they
+/// outlive the process and the caches that led to detecting them
+const DETECTED_KEY: &str = "labels:detected";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Enum, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LabelKind {
+    CexHotWallet,
+    DexPool,
+    Bridge,
+    Burn,
+    Deployer,
+    TeamMultisig,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+pub enum LabelSource {
+    /// Loaded from the `LABELS_PATH` dataset
+    Dataset,
+    /// Inferred by the API (zero address, discovered pools, ...)
+    Detected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct AddressLabel {
+    pub kind: LabelKind,
+    pub name: Option<String>,
+    pub source: LabelSource,
+}
+
+/// One row of the label dataset, in either JSON or CSV form
+#[derive(Debug, Deserialize)]
+struct LabelRecord {
+    address: String,
+    kind: LabelKind,
+    #[serde(default)]
+    name: Option<String>,
+    /// Restricts the label to one chain when set
+    #[serde(default)]
+    chain: Option<Chain>,
+}
+
+/// A detected label as persisted in Redis. Records from before labels were
+/// scoped to a chain have none and apply to every chain.
+#[derive(Serialize, Deserialize)]
+struct DetectedLabel {
+    address: String,
+    #[serde(default)]
+    chain: Option<Chain>,
+    label: AddressLabel,
+}
+
+/// Labels are keyed by address alone: EVM hex and Solana base58 never collide,
+/// and EVM labels usually hold across EVM chains.
+fn key(address: &str) -> String {
+    if address.starts_with("0x") {
+        address.to_lowercase()
+    } else {
+        address.to_string()
+    }
+}
+
+/// Labels per address, each scoped to one chain or to all of them
+type Dataset = HashMap<String, Vec<(Option<Chain>, AddressLabel)>>;
+
+/// Cheap to clone; clones share detected labels
+#[derive(Clone, Default)]
+pub struct LabelRegistry {
+    dataset: Arc<Dataset>,
+    detected: Arc<RwLock<Dataset>>,
+}
+
+impl LabelRegistry {
+    /// Load a `.json` (array of records) or `.csv` (`address,kind,name,chain` header) dataset
+    pub fn load(path: &str) -> Result<Self> {
+        let records: Vec<LabelRecord> = match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_reader(std::fs::File::open(path)?)?,
+            Some("csv") => csv::Reader::from_path(path)?
+                .deserialize()
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(anyhow!("Unsupported label dataset format: {}", path)),
+        };
+
+        let mut dataset = Dataset::new();
+        for record in records {
+            dataset.entry(key(&record.address)).or_default().push((
+                record.chain,
+                AddressLabel {
+                    kind: record.kind,
+                    name: record.name,
+                    source: LabelSource::Dataset,
+                },
+            ));
+        }
+
+        info!("Loaded labels for {} addresses from {}", dataset.len(), path);
+        Ok(Self {
+            dataset: Arc::new(dataset),
+            detected: Default::default(),
+        })
+    }
+
+    /// Load labels detected by earlier runs
+    pub async fn load_detected(&self, redis: &RedisPool) -> Result<()> {
+        let records = hash_values(redis, DETECTED_KEY).await?;
+        let mut detected = self.detected.write().unwrap();
+        for record in &records {
+            let DetectedLabel { address, chain, label } = serde_json::from_str(record)?;
+            let labels = detected.entry(address).or_default();
+            if !labels.iter().any(|(scope, l)| *scope == chain && l.kind == label.kind) {
+                labels.push((chain, label));
+            }
+        }
+        info!("Loaded {} detected labels", records.len());
+        Ok(())
+    }
+
+    /// Remember a label inferred at runtime on `chain`, ignoring duplicates of the same kind
+    pub async fn detect(
+        &self,
+        redis: &RedisPool,
+        chain: Chain,
+        address: &str,
+        kind: LabelKind,
+        name: Option<String>,
+    ) -> Result<()> {
+        let address = key(address);
+        let label = AddressLabel { kind, name, source: LabelSource::Detected };
+        {
+            let mut detected = self.detected.write().unwrap();
+            let labels = detected.entry(address.clone()).or_default();
+            if labels.iter().any(|(scope, l)| *scope == Some(chain) && l.kind == kind) {
+                return Ok(());
+            }
+            labels.push((Some(chain), label.clone()));
+        }
+
+        let field = format!("{}:{}:{:?}", chain, address, kind);
+        let record = DetectedLabel { address, chain: Some(chain), label };
+        hash_set(redis, DETECTED_KEY, &field, &serde_json::to_string(&record)?).await
+    }
+
+    /// Whether an address carries a label of the given kind
+    pub fn has_kind(&self, address: &str, chain: Option<Chain>, kind: LabelKind) -> bool {
+        self.lookup(address, chain).iter().any(|l| l.kind == kind)
+    }
+
+    /// All labels for an address; pass a chain to drop labels scoped to other chains
+    pub fn lookup(&self, address: &str, chain: Option<Chain>) -> Vec<AddressLabel> {
+        let key = key(address);
+        let in_scope = |labels: Option<&Vec<(Option<Chain>, AddressLabel)>>| -> Vec<AddressLabel> {
+            labels
+                .into_iter()
+                .flatten()
+                .filter(|(scope, _)| scope.is_none() || chain.is_none() || *scope == chain)
+                .map(|(_, label)| label.clone())
+                .collect()
+        };
+        let mut labels = in_scope(self.dataset.get(&key));
+
+        if is_zero_address(address) || key == EVM_DEAD_ADDRESS {
+            labels.push(AddressLabel {
+                kind: LabelKind::Burn,
+                name: None,
+                source: LabelSource::Detected,
+            });
+        }
+
+        labels.extend(in_scope(self.detected.read().unwrap().get(&key)));
+
+        labels
+    }
+}
@@ -3,32 +3,60 @@ mod config;
 mod cache;
 mod models;
 mod rpc;
+mod indexer;
+mod labels;
+mod analytics;
+mod pricing;
+mod streams;
+mod alerts;
+mod watchlists;
 mod graphql;
 
-use actix_web::{web, App, HttpServer};
-use async_graphql::{EmptyMutation, EmptySubscription, Schema};
-use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+use actix_web::{guard, web, App, HttpRequest, HttpResponse, HttpServer};
+use async_graphql::{dataloader::DataLoader, Schema};
+use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
 use dotenv::dotenv;
 use tracing::info;
 
 use crate::{
-    cache::RedisPool,
+    alerts::webhooks::WebhookDispatcher,
+    cache::init_redis_client,
     config::Config,
-    graphql::schema::QueryRoot,
+    graphql::{auth::ApiKey, loaders::{HolderKindLoader, TokenLoader}, MutationRoot, QueryRoot, SubscriptionRoot},
+    labels::LabelRegistry,
+    streams::FeedHub,
 };
 
-async fn init_redis_client(url: &str) -> Result<RedisPool, Box<dyn std::error::Error>> {
-    let client = redis::Client::open(url)?;
-    let mut conn = client.get_async_connection().await?;
-    let _: String = redis::cmd("PING").query_async(&mut conn).await?;
-    Ok(client)
+fn api_key(http_req: &HttpRequest) -> Option<ApiKey> {
+    let headers = http_req.headers();
+    headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get("X-Api-Key").and_then(|v| v.to_str().ok()))
+        .map(|key| ApiKey(key.trim().to_string()))
 }
 
+type ApiSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
+
 async fn graphql_handler(
-    schema: web::Data<Schema<QueryRoot, EmptyMutation, EmptySubscription>>,
+    schema: web::Data<ApiSchema>,
+    http_req: HttpRequest,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+    let mut req = req.into_inner();
+    if let Some(key) = api_key(&http_req) {
+        req = req.data(key);
+    }
+    schema.execute(req).await.into()
+}
+
+async fn graphql_ws_handler(
+    schema: web::Data<ApiSchema>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    GraphQLSubscription::new(ApiSchema::clone(&*schema)).start(&req, payload)
 }
 
 #[actix_web::main]
@@ -47,11 +75,33 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Failed to initialize Redis client");
     
+    // Load the address label dataset, if configured
+    let labels = match &config.labels_path {
+        Some(path) => LabelRegistry::load(path).expect("Failed to load address labels"),
+        None => LabelRegistry::default(),
+    };
+    labels
+        .load_detected(&redis_pool)
+        .await
+        .expect("Failed to load detected address labels");
+    
+    let webhooks = WebhookDispatcher::new(redis_pool.clone(), &config)
+        .expect("Failed to initialize webhook dispatcher");
+    
     // Create GraphQL schema
-    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
-        .data(redis_pool)
+    let schema = Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+        .data(redis_pool.clone())
+        .data(config.clone())
+        .data(labels.clone())
+        .data(webhooks.clone())
+        .data(FeedHub::default())
+        .data(DataLoader::new(HolderKindLoader::new(config.clone()), tokio::spawn))
+        .data(DataLoader::new(TokenLoader::new(redis_pool.clone(), config.clone()), tokio::spawn))
         .finish();
     
+    // Watch tracked tokens for alert rule matches
+    tokio::spawn(alerts::watcher::run(redis_pool, config.clone(), labels, webhooks));
+    
     // Start the HTTP server
     info!("🌐 Server running at http://{}", config.server_addr);
     
@@ -59,6 +109,12 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .app_data(web::Data::new(schema.clone()))
             .route("/graphql", web::post().to(graphql_handler))
+            .service(
+                web::resource("/graphql")
+                    .guard(guard::Get())
+                    .guard(guard::Header("upgrade", "websocket"))
+                    .to(graphql_ws_handler),
+            )
     })
     .bind(&config.server_addr)?
     .run()
@@ -1,6 +1,11 @@
 use std::fmt;
-use async_graphql::{SimpleObject, Enum};
+use std::str::FromStr;
+use std::sync::Arc;
+use async_graphql::{SimpleObject, Enum, InputObject};
 use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+
+use crate::analytics::holders::HolderState;
 
 impl fmt::Display for Chain {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -13,7 +18,23 @@ impl fmt::Display for Chain {
     }
 }
 
+impl FromStr for Chain {
+    type Err = String;
+
+    /// Parse the lowercase name written by `Display`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "ethereum" => Ok(Chain::Ethereum),
+            "solana" => Ok(Chain::Solana),
+            "bsc" => Ok(Chain::Bsc),
+            "other" => Ok(Chain::Other),
+            _ => Err(format!("Unknown chain: {}", name)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
 pub struct Token {
     pub address: String,
     pub chain: String,
@@ -27,15 +48,151 @@ pub struct Token {
     pub market_cap: Option<f64>,
 }
 
+/// The transaction that created a token contract or mint
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Deployment {
+    /// Account that signed the creating transaction
+    pub creator: String,
+    /// Transaction hash, or signature on Solana
+    pub tx_hash: String,
+    /// Block number, or slot on Solana
+    pub block_number: u64,
+    pub timestamp: i64,
+    /// Contract the creating transaction called, when the token came from a factory
+    pub factory: Option<String>,
+}
+
+/// A wallet that bought in a token's launch window
+#[derive(Debug, Clone, SimpleObject)]
+pub struct LaunchBuyer {
+    pub address: String,
+    /// Block (slot on Solana) of the wallet's first buy
+    pub first_block: u64,
+    /// Raw token units bought within the window
+    pub bought: String,
+    /// Raw token units held now
+    pub balance: String,
+    /// Current balance as a percentage of supply
+    pub share: f64,
+    /// Account that funded the wallet, when it could be traced
+    pub funder: Option<String>,
+}
+
+/// Launch buyers funded by the same account
+#[derive(Debug, Clone, SimpleObject)]
+pub struct FundingCluster {
+    pub funder: String,
+    pub wallets: Vec<String>,
+    /// Supply the wallets still hold, as a percentage
+    pub share: f64,
+}
+
+/// Who bought a token in its first blocks and what they still hold
+#[derive(Debug, Clone, SimpleObject)]
+pub struct LaunchAnalysis {
+    /// Block (slot on Solana) of the token's first trade
+    pub launch_block: u64,
+    pub launch_timestamp: i64,
+    /// Blocks or slots counted from the first trade
+    pub window: u64,
+    pub buyer_count: u32,
+    /// Buyers sharing a funder with at least one other buyer
+    pub common_funded_count: u32,
+    /// Buyers funded by the token's deployer
+    pub deployer_funded_count: u32,
+    /// Supply all launch buyers still hold, as a percentage
+    pub held_share: f64,
+    /// Funders of two or more buyers, largest first
+    pub clusters: Vec<FundingCluster>,
+    /// Largest buys first
+    pub buyers: Vec<LaunchBuyer>,
+}
+
+/// Wallets that trade against each other or pass tokens around in a loop
+#[derive(Debug, Clone, Default, SimpleObject)]
+pub struct WashCluster {
+    pub wallets: Vec<String>,
+    /// Transfer cycles closed among the wallets
+    pub cycle_count: u32,
+    /// Trades in the last 24h offset by an opposite trade of the cluster
+    pub trade_count: u32,
+    pub wash_volume_usd: f64,
+}
+
+/// Raw 24h trading volume and the part of it attributed to wash trading
+#[derive(Debug, Clone, SimpleObject)]
+pub struct WashTrading {
+    /// Seconds within which a cycle must close or a trade be reversed
+    pub window_seconds: i64,
+    pub volume_24h: f64,
+    pub wash_volume_24h: f64,
+    pub adjusted_volume_24h: f64,
+    /// Percentage of `volume24h` attributed to wash trading
+    pub wash_share: f64,
+    pub cluster_count: u32,
+    /// Largest wash volume first, at most 100
+    pub clusters: Vec<WashCluster>,
+}
+
+/// Another token created by the same deployer
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct DeployedToken {
+    pub address: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    /// Summed `reserveUsd` of the token's pools
+    pub liquidity_usd: f64,
+    /// Accounts holding a non-zero balance; null on EVM chains until the
+    /// token's transfer index is complete
+    pub holder_count: Option<u32>,
+    /// Whether the token still has at least $1,000 of liquidity
+    pub alive: bool,
+}
+
+/// Tokens a deployer created besides the one queried
+#[derive(Debug, Clone, SimpleObject)]
+pub struct DeployerHistory {
+    pub deployer: String,
+    pub chain: Chain,
+    /// The most recent tokens, analyzed for liquidity and holders
+    pub tokens: Vec<DeployedToken>,
+    /// Other tokens found, including those too old to be analyzed
+    pub token_count: u32,
+    /// Analyzed tokens whose liquidity is gone
+    pub dead_count: u32,
+    /// From 0 (serial rugger) to 100; `None` when there are no other tokens to judge
+    pub reputation_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
 pub struct Holder {
     pub address: String,
     pub balance: String,
     pub value_usd: Option<f64>,
     pub share: Option<f64>,
+    /// Chain of the token, for chain-scoped label lookups
+    #[serde(skip)]
+    #[graphql(skip)]
+    pub chain: Chain,
+}
+
+/// What kind of account holds a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+pub enum HolderKind {
+    /// Externally owned account, or a system-owned Solana account
+    Wallet,
+    /// Gnosis Safe proxy
+    Multisig,
+    /// EVM contract or executable Solana program
+    Contract,
+    /// Solana account owned by a program other than the system program (PDAs, vaults)
+    ProgramOwned,
+    Unknown,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
 pub struct Transfer {
     pub tx_hash: String,
     pub from: String,
@@ -43,19 +200,237 @@ pub struct Transfer {
     pub amount: String,
     pub timestamp: i64,
     pub block_number: u64,
+    #[serde(default)]
+    pub log_index: u64,
+    #[serde(default)]
+    pub kind: TransferKind,
+    /// Chain of the token, for chain-scoped label lookups. Not stored:
+    /// the index a transfer is loaded from already names its chain.
+    #[serde(skip)]
+    #[graphql(skip)]
+    pub chain: Chain,
+}
+
+/// Whether a token movement created, destroyed or moved supply
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Enum, Serialize, Deserialize)]
+pub enum TransferKind {
+    #[default]
+    Transfer,
+    Mint,
+    Burn,
 }
 
+/// Direction of a trade relative to the token it was indexed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// A DEX swap seen from one token's side. On a buy the token is `tokenOut`,
+/// on a sell it is `tokenIn`; amounts are raw units.
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Trade {
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub log_index: u64,
+    pub timestamp: i64,
+    pub pool: String,
+    pub dex: String,
+    pub side: TradeSide,
+    /// Account that signed the transaction
+    pub trader: String,
+    pub token_in: String,
+    pub amount_in: String,
+    pub token_out: String,
+    pub amount_out: String,
+    /// USD per whole token at execution, when the quote asset could be priced
+    pub price_usd: Option<f64>,
+    pub volume_usd: Option<f64>,
+}
+
+impl Trade {
+    /// Raw amount of the indexed token that changed hands
+    pub fn token_amount(&self) -> &str {
+        match self.side {
+            TradeSide::Buy => &self.amount_out,
+            TradeSide::Sell => &self.amount_in,
+        }
+    }
+}
+
+/// How sold tokens are matched against earlier buys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+pub enum CostBasisMethod {
+    /// Sells consume the oldest open buys first
+    Fifo,
+    /// Sells are costed at the running average purchase price
+    AverageCost,
+}
+
+/// Profit and loss on one token for a wallet. Quantities are in whole token units.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct TokenPnl {
+    pub chain: Chain,
+    pub token: String,
+    pub symbol: Option<String>,
+    pub buys: u32,
+    pub sells: u32,
+    pub bought: f64,
+    pub sold: f64,
+    /// Tokens still held from priced buys
+    pub position: f64,
+    /// USD cost of `position`
+    pub cost_basis_usd: f64,
+    pub average_cost_usd: Option<f64>,
+    pub realized_pnl_usd: f64,
+    pub unrealized_pnl_usd: Option<f64>,
+    pub current_price_usd: Option<f64>,
+    /// Trades left out because their USD price is unknown
+    pub unpriced_trades: u32,
+    /// Tokens sold beyond what priced buys cover (e.g. received by transfer); no PnL is booked on them
+    pub unmatched_sold: f64,
+}
+
+/// Per-token and total PnL of a wallet
+#[derive(Debug, Clone, SimpleObject)]
+pub struct WalletPnl {
+    pub address: String,
+    pub chain: Chain,
+    pub method: CostBasisMethod,
+    pub tokens: Vec<TokenPnl>,
+    pub realized_pnl_usd: f64,
+    /// Sum over tokens with a current price
+    pub unrealized_pnl_usd: f64,
+    pub total_pnl_usd: f64,
+}
+
+/// Simulated order of `amount_usd` against a token's DEX pools
+#[derive(Debug, Clone, SimpleObject)]
+pub struct PriceImpact {
+    pub side: TradeSide,
+    pub amount_usd: f64,
+    /// Spot price of the deepest pool; slippage is measured from it
+    pub spot_price_usd: Option<f64>,
+    /// Average price with the order split across pools
+    pub execution_price_usd: Option<f64>,
+    /// Percent worse than spot for the split order
+    pub slippage: Option<f64>,
+    /// Token units bought or sold by the split order
+    pub token_amount: f64,
+    /// Whether the known liquidity absorbs the whole order
+    pub filled: bool,
+    /// Filled single pool with the best execution price
+    pub best_pool: Option<PoolImpact>,
+    pub pools: Vec<PoolImpact>,
+}
+
+/// The same order simulated against a single pool
+#[derive(Debug, Clone, SimpleObject)]
+pub struct PoolImpact {
+    pub address: String,
+    pub dex: String,
+    pub spot_price_usd: f64,
+    pub execution_price_usd: Option<f64>,
+    /// Percent worse than the reference spot price
+    pub slippage: Option<f64>,
+    pub token_amount: f64,
+    pub filled: bool,
+    /// Percent of the split order routed through this pool
+    pub route_share: f64,
+}
+
+/// One asset held by a wallet
+#[derive(Debug, Clone, SimpleObject)]
+pub struct WalletBalance {
+    pub chain: Chain,
+    /// Token address, or `None` for the chain's native asset
+    pub token: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    /// Raw balance in the asset's smallest unit
+    pub balance: String,
+    pub price_usd: Option<f64>,
+    pub value_usd: Option<f64>,
+}
+
+/// A wallet's holdings across chains
+#[derive(Debug, Clone, SimpleObject)]
+pub struct Wallet {
+    pub address: String,
+    pub chains: Vec<Chain>,
+    pub native_balances: Vec<WalletBalance>,
+    pub tokens: Vec<WalletBalance>,
+    /// Sum of the priced balances; unpriced assets are left out
+    pub total_value_usd: f64,
+    /// Chains whose balances could not be read; the rest are still reported
+    pub errors: Vec<ChainError>,
+}
+
+/// A chain left out of a cross-chain result, and why
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ChainError {
+    pub chain: Chain,
+    pub error: String,
+}
+
+/// A token identified by address and chain, used by batch queries
+#[derive(Debug, Clone, InputObject)]
+pub struct TokenRef {
+    pub address: String,
+    pub chain: Chain,
+}
+
+/// One entry of a batch `tokens` query; exactly one of `token` and `error` is set
+#[derive(Debug, Clone, SimpleObject)]
+pub struct TokenResult {
+    pub address: String,
+    pub chain: Chain,
+    pub token: Option<Token>,
+    pub error: Option<String>,
+}
+
+/// One entry of a `tokenAnalyticsBatch` query; exactly one of `analytics` and `error` is set
+#[derive(Debug, Clone, SimpleObject)]
+pub struct TokenAnalyticsResult {
+    pub address: String,
+    pub chain: Chain,
+    pub analytics: Option<TokenAnalytics>,
+    pub error: Option<String>,
+}
+
+/// Entry point for per-token analytics. Only the token's identity is held here;
+/// each field is resolved, and cached, when a query selects it.
+#[derive(Debug, Clone)]
 pub struct TokenAnalytics {
-    pub token: Token,
-    pub holders: Vec<Holder>,
-    pub transfers: Vec<Transfer>,
-    pub liquidity_pools: Vec<LiquidityPool>,
+    pub address: String,
+    pub chain: Chain,
+    /// Set once the transfer index has been synced for this query
+    pub synced: Arc<OnceCell<()>>,
+    /// Set once the trade index has been synced for this query
+    pub trades_synced: Arc<OnceCell<()>>,
+    /// Holder state shared by the fields of this query that need it
+    pub holder_state: Arc<OnceCell<HolderState>>,
+}
+
+impl TokenAnalytics {
+    pub fn new(address: String, chain: Chain) -> Self {
+        Self {
+            address,
+            chain,
+            synced: Default::default(),
+            trades_synced: Default::default(),
+            holder_state: Default::default(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
 pub struct LiquidityPool {
     pub address: String,
+    pub chain: Chain,
     pub exchange: String,
     pub token0: String,
     pub token1: String,
@@ -63,12 +438,202 @@ pub struct LiquidityPool {
     pub reserve1: String,
     pub reserve_usd: f64,
     pub volume_24h: f64,
+    /// LP ownership, computed once per query for the fields that need it
+    #[serde(skip)]
+    #[graphql(skip)]
+    pub lock: Arc<OnceCell<Option<LiquidityLock>>>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+/// Who holds a V2 pool's LP tokens. Shares are percentages of the LP supply.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct LiquidityLock {
+    /// LP tokens outstanding, in raw units
+    pub lp_supply: String,
+    /// Held by the zero or dead address, or transferred straight to zero
+    pub burned: f64,
+    /// Held by locker contracts (`LP_LOCKERS`)
+    pub locked: f64,
+    /// Account that received the pool's first LP mint
+    pub deployer: Option<String>,
+    pub deployer_held: f64,
+    /// Withdrawable at any time, including the deployer's share
+    pub unlocked: f64,
+    /// `burned + locked`
+    pub lock_percentage: f64,
+    /// LP supply the indexed transfers do not account for
+    #[serde(default)]
+    pub unaccounted: f64,
+    /// Accounts holding LP tokens, burn addresses excluded
+    pub holder_count: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Enum, Serialize, Deserialize)]
 pub enum Chain {
     Ethereum,
     Solana,
     Bsc,
+    #[default]
     Other,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+pub enum Interval {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+    OneDay,
+    OneWeek,
+}
+
+impl Interval {
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Interval::OneMinute => 60,
+            Interval::FiveMinutes => 300,
+            Interval::OneHour => 3_600,
+            Interval::OneDay => 86_400,
+            Interval::OneWeek => 604_800,
+        }
+    }
+}
+
+/// OHLCV candle built from priced trades across all of a token's pools
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Candle {
+    /// Start of the bucket (unix seconds)
+    pub timestamp: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Volume-weighted average price over the bucket
+    pub vwap: f64,
+    /// Traded volume in whole token units
+    pub volume: f64,
+    pub volume_usd: f64,
+    pub trade_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct TokenHistoryPoint {
+    /// Start of the bucket (unix seconds)
+    pub timestamp: i64,
+    /// Total supply at the end of the bucket, in raw token units
+    pub total_supply: String,
+    /// Number of addresses with a non-zero balance at the end of the bucket
+    pub holder_count: u64,
+    pub transfer_count: u64,
+    /// Sum of transferred amounts within the bucket, in raw token units
+    pub volume: String,
+}
+
+/// Minted and burned totals over a trailing window
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct SupplyChange {
+    /// Window length in seconds, ending now
+    pub window: i64,
+    pub minted: String,
+    pub burned: String,
+    /// Minted minus burned; negative when the token is deflationary over the window
+    pub net: String,
+    pub mint_count: u64,
+    pub burn_count: u64,
+    /// Net change as a percentage of the supply at the start of the window
+    pub net_change_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Approval {
+    pub tx_hash: String,
+    pub owner: String,
+    pub spender: String,
+    pub amount: String,
+    pub timestamp: i64,
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+/// An outstanding allowance, verified with an `allowance()` read
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Allowance {
+    pub token: String,
+    pub owner: String,
+    pub spender: String,
+    pub amount: String,
+    /// Allowance is effectively unlimited (at least 2^255)
+    pub unlimited: bool,
+    /// Timestamp of the most recent `Approval` event for this pair
+    pub approved_at: i64,
+}
+
+/// A wallet's live approvals across tracked tokens, and the tokens that
+/// could not be checked
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct WalletApprovals {
+    pub allowances: Vec<Allowance>,
+    pub errors: Vec<TokenError>,
+}
+
+/// A token left out of a wallet-wide result, and why
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct TokenError {
+    pub token: String,
+    pub error: String,
+}
+
+/// Allowances granted to one spender across all owners of a token
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct SpenderExposure {
+    pub spender: String,
+    /// Sum of verified allowances (saturating)
+    pub total_allowance: String,
+    /// Sum over owners of `min(allowance, balance)`, i.e. what the spender could move right now
+    pub exposed_balance: String,
+    pub owner_count: u64,
+    pub unlimited_count: u64,
+    pub allowances: Vec<Allowance>,
+}
+
+/// How concentrated token ownership is. Shares are percentages of the
+/// circulating balance after exclusions.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Concentration {
+    pub holder_count: u64,
+    /// 0 (perfectly equal) to 1 (one holder owns everything)
+    pub gini: Option<f64>,
+    /// Herfindahl-Hirschman index on a 0-10,000 scale
+    pub hhi: Option<f64>,
+    /// Fewest holders controlling more than 50%
+    pub nakamoto: Option<u64>,
+    pub top10_share: Option<f64>,
+    pub top50_share: Option<f64>,
+    pub top100_share: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct DistributionBucket {
+    /// Inclusive lower edge
+    pub min: f64,
+    /// Exclusive upper edge; open-ended when absent
+    pub max: Option<f64>,
+    pub holder_count: u64,
+    /// Summed balance of holders in the bucket, in token units
+    pub total_balance: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct HolderDistribution {
+    pub by_balance: Vec<DistributionBucket>,
+    /// Empty when no on-chain price is available
+    pub by_usd: Vec<DistributionBucket>,
+    pub price_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct PriceUpdate {
+    pub address: String,
+    pub chain: Chain,
+    pub price_usd: f64,
+    pub block_number: u64,
+    pub timestamp: i64,
+}
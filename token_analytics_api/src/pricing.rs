@@ -0,0 +1,169 @@
+//! Spot USD prices derived from on-chain DEX reserves
+
+use anyhow::Result;
+use web3::types::U256;
+
+use crate::config::Config;
+use crate::models::Chain;
+use crate::rpc::ethereum::{EthereumClient, UNISWAP_V2_FACTORY, USDC, WETH};
+use crate::rpc::solana::{SolanaClient, RAYDIUM_AMM_V4, SOL_DECIMALS, USDC_MINT, WSOL_MINT};
+
+pub const USDC_DECIMALS: u8 = 6;
+pub const WETH_DECIMALS: u8 = 18;
+/// Uniswap V2 USDC/WETH pair; USDC sorts first so it is `token0`
+const USDC_WETH_V2_PAIR: &str = "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc";
+/// Raydium AMM v4 SOL/USDC pool; wSOL is its coin (mint A) and USDC its pc
+const SOL_USDC_RAYDIUM_POOL: &str = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2";
+
+pub fn to_units(amount: U256, decimals: u8) -> f64 {
+    amount.to_string().parse::<f64>().unwrap_or_default() / 10f64.powi(decimals as i32)
+}
+
+/// Price of `token` in units of `quote` from their Uniswap V2 pair, if one exists
+async fn quote_v2(
+    client: &EthereumClient,
+    token: &str,
+    token_decimals: u8,
+    quote: &str,
+    quote_decimals: u8,
+) -> Result<Option<f64>> {
+    let Some(pair) = client.get_v2_pair(UNISWAP_V2_FACTORY, token, quote).await? else {
+        return Ok(None);
+    };
+    let (token0, _, reserve0, reserve1) = client.get_v2_reserves(&format!("{:?}", pair)).await?;
+
+    let (token_reserve, quote_reserve) = if format!("{:?}", token0) == token.to_lowercase() {
+        (reserve0, reserve1)
+    } else {
+        (reserve1, reserve0)
+    };
+    if token_reserve.is_zero() {
+        return Ok(None);
+    }
+
+    Ok(Some(to_units(quote_reserve, quote_decimals) / to_units(token_reserve, token_decimals)))
+}
+
+/// ETH/USD as of a past block, from the USDC/WETH pair's reserves
+pub async fn eth_usd_at_block(client: &EthereumClient, block_number: u64) -> Result<Option<f64>> {
+    let (usdc, weth) = client.get_v2_reserves_at(USDC_WETH_V2_PAIR, block_number).await?;
+    if weth.is_zero() {
+        return Ok(None);
+    }
+    Ok(Some(to_units(usdc, USDC_DECIMALS) / to_units(weth, WETH_DECIMALS)))
+}
+
+/// SOL/USD from the reserves of the Raydium SOL/USDC pool
+async fn sol_usd(config: &Config) -> Result<Option<f64>> {
+    let client = SolanaClient::new(&config.sol_rpc_url);
+    let reserves = client.get_raydium_reserves(&[SOL_USDC_RAYDIUM_POOL.to_string()]).await?;
+
+    Ok(reserves
+        .into_iter()
+        .flatten()
+        .find(|r| r.reserve_a > 0)
+        .map(|r| {
+            to_units(r.reserve_b.into(), USDC_DECIMALS) / to_units(r.reserve_a.into(), SOL_DECIMALS)
+        }))
+}
+
+/// Spot USD price of a chain's native asset: ETH via WETH, SOL via Raydium
+pub async fn native_price_usd(config: &Config, chain: Chain) -> Result<Option<f64>> {
+    match chain {
+        Chain::Ethereum => token_price_usd(config, chain, WETH, WETH_DECIMALS).await,
+        Chain::Solana => sol_usd(config).await,
+        _ => Ok(None),
+    }
+}
+
+/// Spot USD price of a token: routed through USDC or WETH pairs on
+/// Ethereum, and through Raydium USDC or wSOL pools on Solana. Returns
+/// `None` when no priced pool is found.
+pub async fn token_price_usd(
+    config: &Config,
+    chain: Chain,
+    address: &str,
+    decimals: u8,
+) -> Result<Option<f64>> {
+    match chain {
+        Chain::Ethereum => erc20_price_usd(config, address, decimals).await,
+        Chain::Solana => spl_price_usd(config, address, decimals).await,
+        _ => Ok(None),
+    }
+}
+
+async fn erc20_price_usd(config: &Config, address: &str, decimals: u8) -> Result<Option<f64>> {
+    let address = address.to_lowercase();
+    if address == USDC.to_lowercase() {
+        return Ok(Some(1.0));
+    }
+
+    let client = EthereumClient::new(&config.eth_rpc_url).await?;
+    if let Some(price) = quote_v2(&client, &address, decimals, USDC, USDC_DECIMALS).await? {
+        return Ok(Some(price));
+    }
+
+    let Some(eth_usd) = quote_v2(&client, WETH, WETH_DECIMALS, USDC, USDC_DECIMALS).await? else {
+        return Ok(None);
+    };
+    if address == WETH.to_lowercase() {
+        return Ok(Some(eth_usd));
+    }
+
+    Ok(quote_v2(&client, &address, decimals, WETH, WETH_DECIMALS)
+        .await?
+        .map(|price_eth| price_eth * eth_usd))
+}
+
+/// Price of an SPL token from the reserves of its deepest Raydium AMM v4
+/// pool against USDC or wSOL
+async fn spl_price_usd(config: &Config, mint: &str, decimals: u8) -> Result<Option<f64>> {
+    match mint {
+        USDC_MINT => return Ok(Some(1.0)),
+        WSOL_MINT => return sol_usd(config).await,
+        _ => {}
+    }
+
+    let client = SolanaClient::new(&config.sol_rpc_url);
+    let pools: Vec<_> = client
+        .get_dex_pools(mint)
+        .await?
+        .into_iter()
+        .filter(|p| p.program == RAYDIUM_AMM_V4 && matches!(p.other_mint(mint), USDC_MINT | WSOL_MINT))
+        .collect();
+    if pools.is_empty() {
+        return Ok(None);
+    }
+    let addresses: Vec<String> = pools.iter().map(|p| p.address.clone()).collect();
+    let reserves = client.get_raydium_reserves(&addresses).await?;
+    let sol_usd = if pools.iter().any(|p| p.other_mint(mint) == WSOL_MINT) {
+        sol_usd(config).await?
+    } else {
+        None
+    };
+
+    // Price from the pool with the most quote-side liquidity in USD
+    Ok(pools
+        .iter()
+        .zip(reserves)
+        .filter_map(|(pool, reserves)| {
+            let reserves = reserves?;
+            let (token_reserve, quote_reserve) = if pool.mint_a == mint {
+                (reserves.reserve_a, reserves.reserve_b)
+            } else {
+                (reserves.reserve_b, reserves.reserve_a)
+            };
+            let (quote_usd, quote_decimals) = match pool.other_mint(mint) {
+                USDC_MINT => (1.0, USDC_DECIMALS),
+                _ => (sol_usd?, SOL_DECIMALS),
+            };
+            let token_units = to_units(token_reserve.into(), decimals);
+            if token_units <= 0.0 {
+                return None;
+            }
+            let liquidity = to_units(quote_reserve.into(), quote_decimals) * quote_usd;
+            Some((liquidity, liquidity / token_units))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, price)| price))
+}
@@ -1,12 +1,46 @@
 use web3::{
-    types::{Address, U256, H160},
+    ethabi::{self, Token},
+    types::{
+        Address, BlockId, BlockNumber, Bytes, CallRequest, FilterBuilder, Log, TransactionId, H256, U256, U64, H160,
+    },
     contract::{Contract, Options},
-    transports::Http,
+    transports::{Batch, Http},
     Web3,
 };
 use anyhow::{Result, anyhow};
+use std::collections::HashMap;
 use std::str::FromStr;
-use std::sync::Arc;
+
+use crate::analytics::classify_transfer;
+use crate::models::{Approval, Chain, Deployment, Transfer};
+
+/// keccak256("Transfer(address,address,uint256)")
+pub const TRANSFER_TOPIC: &str = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+pub const UNISWAP_V2_FACTORY: &str = "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f";
+pub const UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+/// Uniswap V3 fee tiers in hundredths of a basis point
+pub const V3_FEE_TIERS: [u32; 4] = [100, 500, 3000, 10000];
+pub const WETH: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+pub const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+/// Multicall3 is deployed at the same address on every major EVM chain
+pub const MULTICALL3: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+/// Calls packed into one `aggregate3`, keeping each `eth_call` well under gas limits
+const MULTICALL_CHUNK_SIZE: usize = 300;
+
+/// keccak256("PairCreated(address,address,address,uint256)")
+pub const PAIR_CREATED_TOPIC: &str = "0d3648bd0f6ba80134a33ba9275ac585d9d315f0ad8355cddefde31afa28d0e9";
+/// keccak256("Swap(address,uint256,uint256,uint256,uint256,address)")
+pub const V2_SWAP_TOPIC: &str = "d78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822";
+/// keccak256("Swap(address,address,int256,int256,uint160,uint128,int24)")
+pub const V3_SWAP_TOPIC: &str = "c42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67";
+/// Tick bitmap words read either side of the current tick when simulating V3 swaps
+const V3_TICK_WORDS: i32 = 2;
+/// keccak256("Approval(address,address,uint256)")
+const APPROVAL_TOPIC: &str = "8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+
+/// Blocks per `eth_getBlockByNumber` batch; providers cap batch sizes
+const BLOCK_BATCH_SIZE: usize = 500;
 
 pub struct EthereumClient {
     web3: Web3<Http>,
@@ -28,8 +62,19 @@ impl EthereumClient {
         // ABI for ERC20
         let abi = include_bytes!("erc20.abi.json");
         
-        Ok(Contract::from_json(self.web3.eth(), address, abi)
-            .map_err(|e| anyhow!("Failed to create contract: {}", e))?)
+        Contract::from_json(self.web3.eth(), address, abi)
+            .map_err(|e| anyhow!("Failed to create contract: {}", e))
+    }
+
+    fn get_v2_contract(&self, address: &str) -> Result<Contract<Http>> {
+        let address = address.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid address: {}", e))?;
+
+        // Factory and pair functions share one ABI
+        let abi = include_bytes!("uniswap_v2.abi.json");
+
+        Contract::from_json(self.web3.eth(), address, abi)
+            .map_err(|e| anyhow!("Failed to create contract: {}", e))
     }
 
     pub async fn get_token_info(&self, address: &str) -> Result<(String, String, u8)> {
@@ -56,20 +101,6 @@ impl EthereumClient {
         Ok((name, symbol, decimals))
     }
 
-    pub async fn get_token_balance(&self, token_address: &str, holder_address: &str) -> Result<U256> {
-        let contract = self.get_contract(token_address)?;
-        let holder = holder_address.parse::<Address>()
-            .map_err(|e| anyhow!("Invalid holder address: {}", e))?;
-        
-        // Call balanceOf(address)
-        let balance: U256 = contract
-            .query("balanceOf", holder, None, Options::default(), None)
-            .await
-            .map_err(|e| anyhow!("Failed to get token balance: {}", e))?;
-        
-        Ok(balance)
-    }
-
     pub async fn get_token_supply(&self, token_address: &str) -> Result<U256> {
         let contract = self.get_contract(token_address)?;
         
@@ -81,4 +112,829 @@ impl EthereumClient {
         
         Ok(supply)
     }
+
+    /// Run many read-only calls through Multicall3 `aggregate3`, chunked. Each
+    /// call is `(target, function, args)`; reverted or undecodable calls yield `None`.
+    pub async fn multicall(
+        &self,
+        calls: &[(Address, &ethabi::Function, Vec<Token>)],
+    ) -> Result<Vec<Option<Vec<Token>>>> {
+        let multicall = ethabi::Contract::load(&include_bytes!("multicall3.abi.json")[..])
+            .map_err(|e| anyhow!("Failed to load Multicall3 ABI: {}", e))?;
+        let aggregate = multicall.function("aggregate3")?;
+        let target = MULTICALL3.parse::<Address>()?;
+
+        let mut results = Vec::with_capacity(calls.len());
+        for chunk in calls.chunks(MULTICALL_CHUNK_SIZE) {
+            let packed = chunk
+                .iter()
+                .map(|(address, function, args)| {
+                    Ok(Token::Tuple(vec![
+                        Token::Address(*address),
+                        Token::Bool(true),
+                        Token::Bytes(function.encode_input(args)?),
+                    ]))
+                })
+                .collect::<Result<Vec<_>, ethabi::Error>>()?;
+            let request = CallRequest::builder()
+                .to(target)
+                .data(Bytes(aggregate.encode_input(&[Token::Array(packed)])?))
+                .build();
+            let output = self.web3.eth()
+                .call(request, None)
+                .await
+                .map_err(|e| anyhow!("Failed to call Multicall3: {}", e))?;
+
+            let returned = match aggregate.decode_output(&output.0)?.into_iter().next() {
+                Some(Token::Array(returned)) => returned,
+                _ => return Err(anyhow!("Unexpected Multicall3 response")),
+            };
+            for ((_, function, _), result) in chunk.iter().zip(returned) {
+                let decoded = match result {
+                    Token::Tuple(fields) => match fields.as_slice() {
+                        [Token::Bool(true), Token::Bytes(data)] => function.decode_output(data).ok(),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                results.push(decoded);
+            }
+        }
+        Ok(results)
+    }
+
+    /// `(name, symbol, decimals, totalSupply)` for many tokens in one multicall.
+    /// Tokens missing any of the four (e.g. non-standard `bytes32` names) yield `None`.
+    pub async fn get_token_infos(&self, tokens: &[Address]) -> Result<Vec<Option<(String, String, u8, U256)>>> {
+        let erc20 = erc20_abi()?;
+        let functions = [
+            erc20.function("name")?,
+            erc20.function("symbol")?,
+            erc20.function("decimals")?,
+            erc20.function("totalSupply")?,
+        ];
+        let calls: Vec<_> = tokens
+            .iter()
+            .flat_map(|token| functions.iter().map(move |f| (*token, *f, vec![])))
+            .collect();
+        let results = self.multicall(&calls).await?;
+
+        Ok(results
+            .chunks(functions.len())
+            .map(|fields| match fields {
+                [Some(name), Some(symbol), Some(decimals), Some(supply)] => {
+                    match (&name[..], &symbol[..], &decimals[..], &supply[..]) {
+                        (
+                            [Token::String(name)],
+                            [Token::String(symbol)],
+                            [Token::Uint(decimals)],
+                            [Token::Uint(supply)],
+                        ) => Some((name.clone(), symbol.clone(), decimals.low_u32() as u8, *supply)),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// `totalSupply` for many tokens in one multicall
+    pub async fn get_token_supplies(&self, tokens: &[Address]) -> Result<Vec<Option<U256>>> {
+        let erc20 = erc20_abi()?;
+        let total_supply = erc20.function("totalSupply")?;
+        let calls: Vec<_> = tokens.iter().map(|token| (*token, total_supply, vec![])).collect();
+
+        Ok(self.multicall(&calls).await?.into_iter().map(first_uint).collect())
+    }
+
+    /// `balanceOf` for many `(token, holder)` pairs in one multicall
+    pub async fn get_token_balances(&self, pairs: &[(Address, Address)]) -> Result<Vec<Option<U256>>> {
+        let erc20 = erc20_abi()?;
+        let balance_of = erc20.function("balanceOf")?;
+        let calls: Vec<_> = pairs
+            .iter()
+            .map(|(token, holder)| (*token, balance_of, vec![Token::Address(*holder)]))
+            .collect();
+
+        Ok(self.multicall(&calls).await?.into_iter().map(first_uint).collect())
+    }
+
+    /// `allowance` of `token` for many `(owner, spender)` pairs in one multicall
+    pub async fn get_allowances(&self, token: Address, pairs: &[(Address, Address)]) -> Result<Vec<Option<U256>>> {
+        let erc20 = erc20_abi()?;
+        let allowance = erc20.function("allowance")?;
+        let calls: Vec<_> = pairs
+            .iter()
+            .map(|(owner, spender)| (token, allowance, vec![Token::Address(*owner), Token::Address(*spender)]))
+            .collect();
+
+        Ok(self.multicall(&calls).await?.into_iter().map(first_uint).collect())
+    }
+
+    /// Native ETH balance in wei
+    pub async fn get_native_balance(&self, address: &str) -> Result<U256> {
+        let address = address.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid address: {}", e))?;
+
+        self.web3.eth()
+            .balance(address, None)
+            .await
+            .map_err(|e| anyhow!("Failed to get balance: {}", e))
+    }
+
+    pub async fn get_block_number(&self) -> Result<u64> {
+        let number = self.web3.eth()
+            .block_number()
+            .await
+            .map_err(|e| anyhow!("Failed to get block number: {}", e))?;
+
+        Ok(number.as_u64())
+    }
+
+    /// Timestamps of many blocks, fetched in one JSON-RPC batch
+    pub async fn get_block_timestamps(&self, block_numbers: &[u64]) -> Result<HashMap<u64, i64>> {
+        let batch = Web3::new(Batch::new(self.web3.transport().clone()));
+        let calls: Vec<_> = block_numbers
+            .iter()
+            .map(|n| batch.eth().block(BlockId::Number(BlockNumber::Number(U64::from(*n)))))
+            .collect();
+        if calls.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        batch.transport()
+            .submit_batch()
+            .await
+            .map_err(|e| anyhow!("Failed to submit eth_getBlockByNumber batch: {}", e))?;
+
+        let mut timestamps = HashMap::with_capacity(calls.len());
+        for (block_number, call) in block_numbers.iter().zip(calls) {
+            let block = call
+                .await
+                .map_err(|e| anyhow!("Failed to get block {}: {}", block_number, e))?
+                .ok_or_else(|| anyhow!("Block {} not found", block_number))?;
+            timestamps.insert(*block_number, block.timestamp.as_u64() as i64);
+        }
+        Ok(timestamps)
+    }
+
+    /// Whether `address` had code as of a block. Needs an archive node for old blocks.
+    async fn has_code_at(&self, address: Address, block_number: u64) -> Result<bool> {
+        let code = self.web3.eth()
+            .code(address, Some(BlockNumber::Number(U64::from(block_number))))
+            .await
+            .map_err(|e| anyhow!("Failed to get code at block {}: {}", block_number, e))?;
+        Ok(!code.0.is_empty())
+    }
+
+    /// First block in which `address` has code, found by binary search over
+    /// `eth_getCode`; `None` if it has no code now
+    pub async fn get_creation_block(&self, address: Address) -> Result<Option<u64>> {
+        let head = self.get_block_number().await?;
+        if !self.has_code_at(address, head).await? {
+            return Ok(None);
+        }
+
+        let (mut low, mut high) = (0, head);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.has_code_at(address, mid).await? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok(Some(low))
+    }
+
+    /// Who created a contract and when. Within the creation block, the creating
+    /// transaction is the one whose receipt names the contract, or for factory
+    /// deployments the first one the contract emitted logs in.
+    pub async fn get_deployment(&self, address: &str) -> Result<Option<Deployment>> {
+        let address = address.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid address: {}", e))?;
+        let Some(low) = self.get_creation_block(address).await? else {
+            return Ok(None);
+        };
+
+        let block = self.web3.eth()
+            .block_with_txs(BlockId::Number(BlockNumber::Number(U64::from(low))))
+            .await
+            .map_err(|e| anyhow!("Failed to get block {}: {}", low, e))?
+            .ok_or_else(|| anyhow!("Block {} not found", low))?;
+
+        let batch = Web3::new(Batch::new(self.web3.transport().clone()));
+        let calls: Vec<_> = block.transactions
+            .iter()
+            .map(|tx| batch.eth().transaction_receipt(tx.hash))
+            .collect();
+        batch.transport()
+            .submit_batch()
+            .await
+            .map_err(|e| anyhow!("Failed to submit eth_getTransactionReceipt batch: {}", e))?;
+        let mut receipts = Vec::with_capacity(calls.len());
+        for call in calls {
+            receipts.push(call.await.map_err(|e| anyhow!("Failed to get receipt: {}", e))?);
+        }
+
+        let transactions = block.transactions.iter().zip(&receipts);
+        let direct = transactions
+            .clone()
+            .find(|(_, receipt)| receipt.as_ref().and_then(|r| r.contract_address) == Some(address));
+        let (transaction, factory) = match direct {
+            Some((tx, _)) => (tx, None),
+            None => match transactions.clone().find(|(_, receipt)| {
+                receipt.as_ref().is_some_and(|r| r.logs.iter().any(|log| log.address == address))
+            }) {
+                Some((tx, _)) => (tx, tx.to),
+                None => return Ok(None),
+            },
+        };
+        let Some(creator) = transaction.from else {
+            return Ok(None);
+        };
+
+        Ok(Some(Deployment {
+            creator: format!("{:?}", creator),
+            tx_hash: format!("{:?}", transaction.hash),
+            block_number: low,
+            timestamp: block.timestamp.as_u64() as i64,
+            factory: factory.map(|f| format!("{:?}", f)),
+        }))
+    }
+
+    /// Fetch deployed bytecode for many addresses in one JSON-RPC batch.
+    /// An empty result means the address is an externally owned account.
+    pub async fn get_codes(&self, addresses: &[Address]) -> Result<Vec<Vec<u8>>> {
+        let batch = Web3::new(Batch::new(self.web3.transport().clone()));
+        let calls: Vec<_> = addresses.iter().map(|a| batch.eth().code(*a, None)).collect();
+
+        batch.transport()
+            .submit_batch()
+            .await
+            .map_err(|e| anyhow!("Failed to submit eth_getCode batch: {}", e))?;
+
+        let mut codes = Vec::with_capacity(calls.len());
+        for call in calls {
+            let code = call.await.map_err(|e| anyhow!("Failed to get code: {}", e))?;
+            codes.push(code.0);
+        }
+        Ok(codes)
+    }
+
+    /// Whether `address` held any ETH at the end of `block_number`
+    async fn has_balance_at(&self, address: Address, block_number: u64) -> Result<bool> {
+        let balance = self.web3.eth()
+            .balance(address, Some(BlockNumber::Number(U64::from(block_number))))
+            .await
+            .map_err(|e| anyhow!("Failed to get balance at block {}: {}", block_number, e))?;
+        Ok(!balance.is_zero())
+    }
+
+    /// Whether `address` had sent any transaction by the end of `block_number`
+    async fn has_sent_by(&self, address: Address, block_number: u64) -> Result<bool> {
+        let nonce = self.web3.eth()
+            .transaction_count(address, Some(BlockNumber::Number(U64::from(block_number))))
+            .await
+            .map_err(|e| anyhow!("Failed to get transaction count at block {}: {}", block_number, e))?;
+        Ok(!nonce.is_zero())
+    }
+
+    /// Account that sent `wallet` its ETH as of `block_number`. A binary search
+    /// over `eth_getBalance` finds the block where the balance turned non-zero,
+    /// and the funder is the sender of a plain ETH transfer to the wallet in
+    /// that block. `None` if the wallet was funded through an internal call.
+    ///
+    /// The balance search is bounded by the wallet's first transaction, found
+    /// by a binary search over its nonce. Until then the balance can only
+    /// grow, so a wallet that is later emptied and refilled still resolves to
+    /// its first funding.
+    pub async fn get_funder(&self, wallet: &str, block_number: u64) -> Result<Option<Address>> {
+        let wallet = wallet.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid address: {}", e))?;
+
+        // The first funding lands in or before the block of the first transaction
+        let high = if self.has_sent_by(wallet, block_number).await? {
+            let (mut low, mut high) = (0, block_number);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                if self.has_sent_by(wallet, mid).await? {
+                    high = mid;
+                } else {
+                    low = mid + 1;
+                }
+            }
+            high
+        } else if self.has_balance_at(wallet, block_number).await? {
+            block_number
+        } else {
+            return Ok(None);
+        };
+
+        let (mut low, mut high) = (0, high);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.has_balance_at(wallet, mid).await? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        let block = self.web3.eth()
+            .block_with_txs(BlockId::Number(BlockNumber::Number(U64::from(low))))
+            .await
+            .map_err(|e| anyhow!("Failed to get block {}: {}", low, e))?
+            .ok_or_else(|| anyhow!("Block {} not found", low))?;
+
+        Ok(block.transactions
+            .iter()
+            .find(|tx| tx.to == Some(wallet) && !tx.value.is_zero())
+            .and_then(|tx| tx.from))
+    }
+
+    /// Contracts `deployer` created directly, for its latest `limit` nonces.
+    /// CREATE addresses follow from the sender and nonce, so each candidate is
+    /// derived locally and kept if it has code. Contracts deployed through a
+    /// factory or with CREATE2 are not found this way.
+    pub async fn get_created_contracts(&self, deployer: &str, limit: u64) -> Result<Vec<Address>> {
+        let deployer = deployer.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid address: {}", e))?;
+        let nonce = self.web3.eth()
+            .transaction_count(deployer, None)
+            .await
+            .map_err(|e| anyhow!("Failed to get transaction count: {}", e))?
+            .low_u64();
+
+        let candidates: Vec<Address> = (nonce.saturating_sub(limit)..nonce)
+            .map(|n| create_address(deployer, n))
+            .collect();
+        let codes = self.get_codes(&candidates).await?;
+
+        Ok(candidates
+            .into_iter()
+            .zip(codes)
+            .filter(|(_, code)| !code.is_empty())
+            .map(|(address, _)| address)
+            .collect())
+    }
+
+    /// Read the same storage slot from many contracts in one JSON-RPC batch
+    pub async fn get_storage_slots(&self, addresses: &[Address], slot: U256) -> Result<Vec<H256>> {
+        let batch = Web3::new(Batch::new(self.web3.transport().clone()));
+        let calls: Vec<_> = addresses.iter().map(|a| batch.eth().storage(*a, slot, None)).collect();
+
+        batch.transport()
+            .submit_batch()
+            .await
+            .map_err(|e| anyhow!("Failed to submit eth_getStorageAt batch: {}", e))?;
+
+        let mut values = Vec::with_capacity(calls.len());
+        for call in calls {
+            values.push(call.await.map_err(|e| anyhow!("Failed to get storage: {}", e))?);
+        }
+        Ok(values)
+    }
+
+    /// Look up the Uniswap V2 pair for two tokens, if one exists
+    pub async fn get_v2_pair(&self, factory: &str, token_a: &str, token_b: &str) -> Result<Option<Address>> {
+        let contract = self.get_v2_contract(factory)?;
+        let token_a = token_a.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid token address: {}", e))?;
+        let token_b = token_b.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid token address: {}", e))?;
+
+        // Call getPair(address,address)
+        let pair: Address = contract
+            .query("getPair", (token_a, token_b), None, Options::default(), None)
+            .await
+            .map_err(|e| anyhow!("Failed to get pair: {}", e))?;
+
+        Ok((!pair.is_zero()).then_some(pair))
+    }
+
+    /// Look up the Uniswap V3 pool for two tokens at one fee tier, if one exists
+    pub async fn get_v3_pool(&self, factory: &str, token_a: &str, token_b: &str, fee: u32) -> Result<Option<Address>> {
+        let factory = factory.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid factory address: {}", e))?;
+        let contract = Contract::from_json(self.web3.eth(), factory, include_bytes!("uniswap_v3.abi.json"))
+            .map_err(|e| anyhow!("Failed to create contract: {}", e))?;
+        let token_a = token_a.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid token address: {}", e))?;
+        let token_b = token_b.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid token address: {}", e))?;
+
+        // Call getPool(address,address,uint24)
+        let pool: Address = contract
+            .query("getPool", (token_a, token_b, U256::from(fee)), None, Options::default(), None)
+            .await
+            .map_err(|e| anyhow!("Failed to get pool: {}", e))?;
+
+        Ok((!pool.is_zero()).then_some(pool))
+    }
+
+    /// Reserves of a V2 pair as `(token0, token1, reserve0, reserve1)`
+    pub async fn get_v2_reserves(&self, pair: &str) -> Result<(Address, Address, U256, U256)> {
+        let contract = self.get_v2_contract(pair)?;
+
+        let token0: Address = contract
+            .query("token0", (), None, Options::default(), None)
+            .await
+            .map_err(|e| anyhow!("Failed to get token0: {}", e))?;
+        let token1: Address = contract
+            .query("token1", (), None, Options::default(), None)
+            .await
+            .map_err(|e| anyhow!("Failed to get token1: {}", e))?;
+        let (reserve0, reserve1, _): (U256, U256, U256) = contract
+            .query("getReserves", (), None, Options::default(), None)
+            .await
+            .map_err(|e| anyhow!("Failed to get reserves: {}", e))?;
+
+        Ok((token0, token1, reserve0, reserve1))
+    }
+
+    /// Reserves of a V2 pair as of a past block. Needs an archive node for old blocks.
+    pub async fn get_v2_reserves_at(&self, pair: &str, block_number: u64) -> Result<(U256, U256)> {
+        let contract = self.get_v2_contract(pair)?;
+        let block = BlockId::Number(BlockNumber::Number(U64::from(block_number)));
+
+        let (reserve0, reserve1, _): (U256, U256, U256) = contract
+            .query("getReserves", (), None, Options::default(), Some(block))
+            .await
+            .map_err(|e| anyhow!("Failed to get reserves at block {}: {}", block_number, e))?;
+
+        Ok((reserve0, reserve1))
+    }
+
+    /// Sender (`from`) of many transactions in one JSON-RPC batch
+    pub async fn get_transaction_senders(&self, hashes: &[H256]) -> Result<HashMap<H256, Address>> {
+        let batch = Web3::new(Batch::new(self.web3.transport().clone()));
+        let calls: Vec<_> = hashes
+            .iter()
+            .map(|h| batch.eth().transaction(TransactionId::Hash(*h)))
+            .collect();
+
+        batch.transport()
+            .submit_batch()
+            .await
+            .map_err(|e| anyhow!("Failed to submit eth_getTransactionByHash batch: {}", e))?;
+
+        let mut senders = HashMap::with_capacity(hashes.len());
+        for (hash, call) in hashes.iter().zip(calls) {
+            let transaction = call.await.map_err(|e| anyhow!("Failed to get transaction: {}", e))?;
+            if let Some(from) = transaction.and_then(|tx| tx.from) {
+                senders.insert(*hash, from);
+            }
+        }
+        Ok(senders)
+    }
+
+    /// Price, in-range liquidity and nearby initialized ticks of a Uniswap V3
+    /// pool, or `None` if the pool does not answer like one
+    pub async fn get_v3_pool_state(&self, pool: Address) -> Result<Option<V3PoolState>> {
+        let abi = ethabi::Contract::load(&include_bytes!("uniswap_v3_pool.abi.json")[..])
+            .map_err(|e| anyhow!("Failed to load Uniswap V3 pool ABI: {}", e))?;
+
+        let calls = [
+            (pool, abi.function("slot0")?, vec![]),
+            (pool, abi.function("liquidity")?, vec![]),
+            (pool, abi.function("tickSpacing")?, vec![]),
+        ];
+        let mut results = self.multicall(&calls).await?.into_iter();
+        let (Some(slot0), Some(liquidity), Some(spacing)) =
+            (results.next().flatten(), first_uint(results.next().flatten()), results.next().flatten())
+        else {
+            return Ok(None);
+        };
+        let (sqrt_price_x96, tick) = match slot0.as_slice() {
+            [Token::Uint(price), Token::Int(tick), ..] => (*price, signed(*tick) as i32),
+            _ => return Ok(None),
+        };
+        let tick_spacing = match spacing.first() {
+            Some(Token::Int(spacing)) if signed(*spacing) > 0 => signed(*spacing) as i32,
+            _ => return Ok(None),
+        };
+
+        // Each bitmap word flags 256 consecutive multiples of the tick spacing
+        let word = tick.div_euclid(tick_spacing) >> 8;
+        let words: Vec<i32> = (word - V3_TICK_WORDS..=word + V3_TICK_WORDS).collect();
+        let tick_bitmap = abi.function("tickBitmap")?;
+        let calls: Vec<_> = words
+            .iter()
+            .map(|w| (pool, tick_bitmap, vec![int_token(*w as i128)]))
+            .collect();
+        let mut initialized = Vec::new();
+        for (word, bits) in words.iter().zip(self.multicall(&calls).await?) {
+            let Some(bits) = first_uint(bits) else {
+                continue;
+            };
+            initialized.extend(
+                (0..256)
+                    .filter(|bit| bits.bit(*bit))
+                    .map(|bit| ((word << 8) + bit as i32) * tick_spacing),
+            );
+        }
+
+        let ticks_function = abi.function("ticks")?;
+        let calls: Vec<_> = initialized
+            .iter()
+            .map(|t| (pool, ticks_function, vec![int_token(*t as i128)]))
+            .collect();
+        let ticks = initialized
+            .iter()
+            .zip(self.multicall(&calls).await?)
+            .filter_map(|(tick, info)| match info?.get(1)? {
+                Token::Int(net) => Some((*tick, signed(*net))),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Some(V3PoolState {
+            sqrt_price_x96,
+            liquidity: liquidity.low_u128(),
+            ticks,
+            tick_range: (
+                ((word - V3_TICK_WORDS) << 8) * tick_spacing,
+                ((word + V3_TICK_WORDS + 1) << 8) * tick_spacing,
+            ),
+        }))
+    }
+
+    /// Fetch `Swap` events emitted by a Uniswap V3 pool between two blocks (inclusive)
+    pub async fn get_v3_swap_logs(&self, pool: &str, from_block: u64, to_block: u64) -> Result<Vec<PoolSwap>> {
+        let logs = self.get_event_logs(pool, V3_SWAP_TOPIC, from_block, to_block).await?;
+
+        Ok(logs
+            .iter()
+            .filter_map(|(log, block_number, timestamp)| decode_v3_swap_log(log, *block_number, *timestamp))
+            .collect())
+    }
+
+    /// Fetch `Swap` events emitted by a Uniswap V2 pair between two blocks (inclusive)
+    pub async fn get_v2_swap_logs(&self, pair: &str, from_block: u64, to_block: u64) -> Result<Vec<PoolSwap>> {
+        let logs = self.get_event_logs(pair, V2_SWAP_TOPIC, from_block, to_block).await?;
+
+        Ok(logs
+            .iter()
+            .filter_map(|(log, block_number, timestamp)| decode_v2_swap_log(log, *block_number, *timestamp))
+            .collect())
+    }
+
+    /// Fetch logs with `topic` emitted by `address` between two blocks (inclusive),
+    /// paired with their block number and timestamp
+    async fn get_event_logs(
+        &self,
+        address: &str,
+        topic: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<(Log, u64, i64)>> {
+        let address = address.parse::<Address>()
+            .map_err(|e| anyhow!("Invalid address: {}", e))?;
+        let topic = H256::from_str(topic)
+            .map_err(|e| anyhow!("Invalid topic: {}", e))?;
+
+        let filter = FilterBuilder::default()
+            .address(vec![address])
+            .topics(Some(vec![topic]), None, None, None)
+            .from_block(BlockNumber::Number(U64::from(from_block)))
+            .to_block(BlockNumber::Number(U64::from(to_block)))
+            .build();
+
+        let logs = self.web3.eth()
+            .logs(filter)
+            .await
+            .map_err(|e| anyhow!("Failed to get logs: {}", e))?;
+
+        // Logs only carry block numbers, so resolve each distinct block's
+        // timestamp in one batch
+        let logs: Vec<(Log, u64)> = logs
+            .into_iter()
+            .filter(|log| !log.is_removed())
+            .filter_map(|log| log.block_number.map(|n| (log, n.as_u64())))
+            .collect();
+        let mut blocks: Vec<u64> = logs.iter().map(|(_, block_number)| *block_number).collect();
+        blocks.sort_unstable();
+        blocks.dedup();
+        let mut timestamps = HashMap::with_capacity(blocks.len());
+        for chunk in blocks.chunks(BLOCK_BATCH_SIZE) {
+            timestamps.extend(self.get_block_timestamps(chunk).await?);
+        }
+
+        let events = logs
+            .into_iter()
+            .filter_map(|(log, block_number)| {
+                let timestamp = *timestamps.get(&block_number)?;
+                Some((log, block_number, timestamp))
+            })
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Fetch `Transfer` events emitted by a token between two blocks (inclusive).
+    /// Mints and burns show up as transfers from/to the zero address.
+    pub async fn get_transfer_logs(
+        &self,
+        token_address: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Transfer>> {
+        let logs = self.get_event_logs(token_address, TRANSFER_TOPIC, from_block, to_block).await?;
+
+        Ok(logs
+            .iter()
+            .filter_map(|(log, block_number, timestamp)| decode_transfer_log(log, *block_number, *timestamp))
+            .collect())
+    }
+
+    /// Fetch `Approval` events emitted by a token between two blocks (inclusive)
+    pub async fn get_approval_logs(
+        &self,
+        token_address: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Approval>> {
+        let logs = self.get_event_logs(token_address, APPROVAL_TOPIC, from_block, to_block).await?;
+
+        Ok(logs
+            .iter()
+            .filter_map(|(log, block_number, timestamp)| decode_approval_log(log, *block_number, *timestamp))
+            .collect())
+    }
+}
+
+/// A decoded Uniswap V2 or V3 `Swap` event, normalized to V2's in/out form.
+/// Amounts are in raw units of the pool's `token0`/`token1`.
+#[derive(Debug, Clone)]
+pub struct PoolSwap {
+    pub pool: Address,
+    pub tx_hash: H256,
+    pub block_number: u64,
+    pub log_index: u64,
+    pub timestamp: i64,
+    pub amount0_in: U256,
+    pub amount1_in: U256,
+    pub amount0_out: U256,
+    pub amount1_out: U256,
+    pub to: Address,
+}
+
+/// Live state of a Uniswap V3 pool, enough to simulate swaps near the current price
+#[derive(Debug, Clone)]
+pub struct V3PoolState {
+    pub sqrt_price_x96: U256,
+    /// Liquidity active at the current tick
+    pub liquidity: u128,
+    /// Initialized ticks near the current price and their `liquidityNet`, ascending
+    pub ticks: Vec<(i32, i128)>,
+    /// Ticks covered by the bitmap words read; `ticks` is complete within them
+    pub tick_range: (i32, i32),
+}
+
+fn decode_v2_swap_log(log: &Log, block_number: u64, timestamp: i64) -> Option<PoolSwap> {
+    // sender and to are indexed; the four amounts fill data
+    if log.topics.len() != 3 || log.data.0.len() != 128 {
+        return None;
+    }
+
+    let word = |i: usize| U256::from_big_endian(&log.data.0[i * 32..(i + 1) * 32]);
+    Some(PoolSwap {
+        pool: log.address,
+        tx_hash: log.transaction_hash?,
+        block_number,
+        log_index: log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+        timestamp,
+        amount0_in: word(0),
+        amount1_in: word(1),
+        amount0_out: word(2),
+        amount1_out: word(3),
+        to: topic_to_address(&log.topics[2]),
+    })
+}
+
+fn decode_v3_swap_log(log: &Log, block_number: u64, timestamp: i64) -> Option<PoolSwap> {
+    // sender and recipient are indexed; data holds amount0, amount1,
+    // sqrtPriceX96, liquidity and tick
+    if log.topics.len() != 3 || log.data.0.len() != 160 {
+        return None;
+    }
+
+    // Signed pool deltas: positive amounts flow into the pool
+    let delta = |i: usize| {
+        let word = U256::from_big_endian(&log.data.0[i * 32..(i + 1) * 32]);
+        if word.bit(255) {
+            (U256::zero(), (!word).overflowing_add(U256::one()).0)
+        } else {
+            (word, U256::zero())
+        }
+    };
+    let (amount0_in, amount0_out) = delta(0);
+    let (amount1_in, amount1_out) = delta(1);
+
+    Some(PoolSwap {
+        pool: log.address,
+        tx_hash: log.transaction_hash?,
+        block_number,
+        log_index: log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+        timestamp,
+        amount0_in,
+        amount1_in,
+        amount0_out,
+        amount1_out,
+        to: topic_to_address(&log.topics[2]),
+    })
+}
+
+fn erc20_abi() -> Result<ethabi::Contract> {
+    ethabi::Contract::load(&include_bytes!("erc20.abi.json")[..])
+        .map_err(|e| anyhow!("Failed to load ERC20 ABI: {}", e))
+}
+
+fn first_uint(tokens: Option<Vec<Token>>) -> Option<U256> {
+    match tokens?.first()? {
+        Token::Uint(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Address of the contract `sender` creates with CREATE at `nonce`:
+/// `keccak256(rlp([sender, nonce]))[12..]`
+fn create_address(sender: Address, nonce: u64) -> Address {
+    let nonce_bytes = nonce.to_be_bytes();
+    let significant = &nonce_bytes[nonce_bytes.iter().take_while(|b| **b == 0).count()..];
+    let nonce_rlp = match significant {
+        [] => vec![0x80],
+        [byte] if *byte < 0x80 => vec![*byte],
+        bytes => [&[0x80 + bytes.len() as u8][..], bytes].concat(),
+    };
+
+    let mut rlp = vec![0xc0 + (21 + nonce_rlp.len()) as u8, 0x94];
+    rlp.extend_from_slice(sender.as_bytes());
+    rlp.extend_from_slice(&nonce_rlp);
+    Address::from_slice(&web3::signing::keccak256(&rlp)[12..])
+}
+
+/// Decode a two's-complement ABI `int` of up to 128 bits
+fn signed(value: U256) -> i128 {
+    if value.bit(255) {
+        -((!value).overflowing_add(U256::one()).0.low_u128() as i128)
+    } else {
+        value.low_u128() as i128
+    }
+}
+
+/// Encode a signed ABI `int` argument in two's complement
+fn int_token(value: i128) -> Token {
+    let magnitude = U256::from(value.unsigned_abs());
+    if value < 0 {
+        Token::Int((!magnitude).overflowing_add(U256::one()).0)
+    } else {
+        Token::Int(magnitude)
+    }
+}
+
+pub fn topic_to_address(topic: &H256) -> H160 {
+    H160::from_slice(&topic.as_bytes()[12..])
+}
+
+pub fn decode_transfer_log(log: &Log, block_number: u64, timestamp: i64) -> Option<Transfer> {
+    // ERC-721 also emits Transfer with the token id indexed, so require
+    // exactly two indexed addresses and a 32-byte amount in data
+    if log.topics.len() != 3 || log.data.0.len() != 32 {
+        return None;
+    }
+
+    let from = format!("{:?}", topic_to_address(&log.topics[1]));
+    let to = format!("{:?}", topic_to_address(&log.topics[2]));
+    let kind = classify_transfer(&from, &to);
+
+    Some(Transfer {
+        tx_hash: format!("{:?}", log.transaction_hash?),
+        from,
+        to,
+        amount: U256::from_big_endian(&log.data.0).to_string(),
+        timestamp,
+        block_number,
+        log_index: log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+        kind,
+        chain: Chain::Ethereum,
+    })
+}
+
+fn decode_approval_log(log: &Log, block_number: u64, timestamp: i64) -> Option<Approval> {
+    // Same shape check as transfers: ERC-721 approvals index the token id
+    if log.topics.len() != 3 || log.data.0.len() != 32 {
+        return None;
+    }
+
+    Some(Approval {
+        tx_hash: format!("{:?}", log.transaction_hash?),
+        owner: format!("{:?}", topic_to_address(&log.topics[1])),
+        spender: format!("{:?}", topic_to_address(&log.topics[2])),
+        amount: U256::from_big_endian(&log.data.0).to_string(),
+        timestamp,
+        block_number,
+        log_index: log.log_index.map(|i| i.as_u64()).unwrap_or_default(),
+    })
 }
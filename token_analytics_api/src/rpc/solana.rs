@@ -1,16 +1,92 @@
 // In src/rpc/solana.rs
 use solana_client::{
-    rpc_client::RpcClient,
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
     rpc_request::TokenAccountsFilter,
 };
 use solana_sdk::{
+    account::Account,
     pubkey::Pubkey,
     program_pack::Pack,
+    signature::Signature,
 };
+use solana_account_decoder::{UiAccountData, UiAccountEncoding, UiDataSliceConfig};
+use solana_transaction_status::UiTransactionEncoding;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_token::state::Account as TokenAccount;
+use spl_token_2022::extension::StateWithExtensions;
 use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::str::FromStr;
 
+use crate::models::{Chain, Deployment, Trade, TradeSide, Transfer, TransferKind};
+
+/// Raydium AMM v4 program; its `initialize2` instruction creates a pool
+pub const RAYDIUM_AMM_V4: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+pub const ORCA_WHIRLPOOL: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+/// Owner of every Raydium AMM v4 vault, so pool transfers name it rather than the pool
+pub const RAYDIUM_AUTHORITY: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// Metaplex Token Metadata program; a mint's name and symbol live in its PDA
+pub const METAPLEX_METADATA: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+pub const USDC_DECIMALS: u8 = 6;
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Pool account layouts: `(program, dex, account size, [mint A, mint B, vault A, vault B] offsets)`.
+/// Raydium AMM v4 stores its coin/pc vaults followed by the two mints; each
+/// Whirlpool mint is followed by its vault and fee growth.
+const POOL_LAYOUTS: [(&str, &str, u64, [usize; 4]); 2] = [
+    (RAYDIUM_AMM_V4, "raydium", 752, [400, 432, 336, 368]),
+    (ORCA_WHIRLPOOL, "orca", 653, [101, 181, 133, 213]),
+];
+
+/// Raydium AMM v4 field offsets: swap fee numerator and denominator, PnL still
+/// owed to the protocol for each side, and the coin and pc vaults
+const RAYDIUM_SWAP_FEE_OFFSET: usize = 176;
+const RAYDIUM_NEED_TAKE_PNL_OFFSET: usize = 192;
+const RAYDIUM_VAULTS_OFFSET: usize = 336;
+
+/// Fee, coin PnL, pc PnL, coin vault and pc vault read from a Raydium pool
+type RaydiumLayout = (f64, u64, u64, Pubkey, Pubkey);
+
+/// Pages of 1000 signatures read when looking for an address's oldest
+/// transaction before giving up on it
+const MAX_HISTORY_PAGES: usize = 10;
+
+/// Name and symbol from a Metaplex metadata account: a key byte, the update
+/// authority and mint, then Borsh strings padded with NULs to fixed widths
+fn parse_metadata(data: &[u8]) -> Option<(String, String)> {
+    fn string(data: &[u8], offset: &mut usize) -> Option<String> {
+        let len = u32::from_le_bytes(data.get(*offset..*offset + 4)?.try_into().ok()?) as usize;
+        let bytes = data.get(*offset + 4..*offset + 4 + len)?;
+        *offset += 4 + len;
+        Some(String::from_utf8_lossy(bytes).trim_end_matches('\0').trim().to_string())
+    }
+
+    let mut offset = 1 + 32 + 32;
+    let name = string(data, &mut offset)?;
+    let symbol = string(data, &mut offset)?;
+    Some((name, symbol))
+}
+
+/// The search for an address's oldest transaction gave up after
+/// [`MAX_HISTORY_PAGES`] pages of history without reaching it
+#[derive(Debug, thiserror::Error)]
+#[error("history of {0} is longer than {} signatures; its oldest transaction was not searched for", MAX_HISTORY_PAGES * 1000)]
+pub struct HistoryTruncated(pub String);
+
+/// A confirmed transaction signature touching an account
+#[derive(Debug, Clone)]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+}
+
 pub struct SolanaClient {
     client: RpcClient,
 }
@@ -22,43 +98,840 @@ impl SolanaClient {
         }
     }
 
-    pub async fn get_token_balance(&self, mint_address: &str, owner_address: &str) -> Result<u64> {
-        let mint_pubkey = Pubkey::from_str(mint_address)?;
+    /// Native SOL balance in lamports
+    pub async fn get_native_balance(&self, owner_address: &str) -> Result<u64> {
+        let owner_pubkey = Pubkey::from_str(owner_address)?;
+        self.client
+            .get_balance(&owner_pubkey)
+            .await
+            .map_err(|e| anyhow!("Failed to get balance: {}", e))
+    }
+
+    /// Every SPL token held by `owner_address` under spl-token or Token-2022,
+    /// summed per mint
+    pub async fn get_token_holdings(&self, owner_address: &str) -> Result<Vec<TokenHolding>> {
         let owner_pubkey = Pubkey::from_str(owner_address)?;
-        
-        // Get token accounts by owner and mint
-        let accounts = self.client.get_token_accounts_by_owner(
-            &owner_pubkey,
-            TokenAccountsFilter::Mint(mint_pubkey),
-        )?;
-        
-        // Sum up all token account balances for this mint
-        let mut total_balance = 0;
-        for account in accounts {
-            // Get the account data
-            let account_data = self.client.get_account_data(&account.pubkey)?;
-            if let Ok(token_account) = TokenAccount::unpack(&account_data) {
-                total_balance += token_account.amount;
+        let mut holdings: HashMap<String, TokenHolding> = HashMap::new();
+        for program in [spl_token::id(), spl_token_2022::id()] {
+            let accounts = self.client
+                .get_token_accounts_by_owner(&owner_pubkey, TokenAccountsFilter::ProgramId(program))
+                .await
+                .map_err(|e| anyhow!("Failed to get token accounts: {}", e))?;
+
+            for keyed in accounts {
+                // Token accounts come back `jsonParsed`
+                let UiAccountData::Json(parsed) = keyed.account.data else {
+                    continue;
+                };
+                let info = &parsed.parsed["info"];
+                let (Some(mint), Some(amount), Some(decimals)) = (
+                    info["mint"].as_str(),
+                    info["tokenAmount"]["amount"].as_str().and_then(|a| a.parse::<u64>().ok()),
+                    info["tokenAmount"]["decimals"].as_u64(),
+                ) else {
+                    continue;
+                };
+
+                holdings
+                    .entry(mint.to_string())
+                    .or_insert_with(|| TokenHolding { mint: mint.to_string(), amount: 0, decimals: decimals as u8 })
+                    .amount += amount;
+            }
+        }
+
+        Ok(holdings.into_values().collect())
+    }
+
+    /// Name and symbol of many mints, read from their Metaplex metadata
+    /// accounts in batches. Mints without metadata come back as `None`.
+    pub async fn get_token_metadata(&self, mints: &[String]) -> Result<Vec<Option<(String, String)>>> {
+        let program = Pubkey::from_str(METAPLEX_METADATA)?;
+        let pdas = mints
+            .iter()
+            .map(|m| {
+                let mint = Pubkey::from_str(m)?;
+                let seeds: [&[u8]; 3] = [b"metadata", program.as_ref(), mint.as_ref()];
+                Ok(Pubkey::find_program_address(&seeds, &program).0)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(self
+            .get_accounts(&pdas)
+            .await?
+            .into_iter()
+            .map(|account| account.filter(|a| a.owner == program).and_then(|a| parse_metadata(&a.data)))
+            .collect())
+    }
+
+    /// Signatures touching `address`, newest first, starting below `before` and
+    /// stopping at `until` (both exclusive) or once `limit` signatures have
+    /// been collected. Fewer than `limit` means the history ran out.
+    pub async fn get_signatures(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
+        let pubkey = Pubkey::from_str(address)?;
+        let until = until.map(Signature::from_str).transpose()
+            .map_err(|e| anyhow!("Invalid signature: {}", e))?;
+        let mut before = before.map(Signature::from_str).transpose()
+            .map_err(|e| anyhow!("Invalid signature: {}", e))?;
+
+        let mut signatures = Vec::new();
+        while signatures.len() < limit {
+            let page = self.client
+                .get_signatures_for_address_with_config(
+                    &pubkey,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some((limit - signatures.len()).min(1000)),
+                        commitment: None,
+                    },
+                )
+                .await
+                .map_err(|e| anyhow!("Failed to get signatures: {}", e))?;
+
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(Signature::from_str(&last.signature)
+                .map_err(|e| anyhow!("Invalid signature: {}", e))?);
+
+            signatures.extend(page.into_iter().filter(|s| s.err.is_none()).map(|s| SignatureInfo {
+                signature: s.signature,
+                slot: s.slot,
+                block_time: s.block_time,
+            }));
+        }
+
+        Ok(signatures)
+    }
+
+    /// Oldest successful signature touching an address, paging back one
+    /// request per 1000 signatures. `None` if the address has no history;
+    /// fails with [`HistoryTruncated`] past [`MAX_HISTORY_PAGES`] pages of it.
+    pub async fn get_first_signature(&self, address: &str) -> Result<Option<SignatureInfo>> {
+        let pubkey = Pubkey::from_str(address)?;
+
+        let mut oldest = None;
+        let mut before = None;
+        for _ in 0..MAX_HISTORY_PAGES {
+            let page = self.client
+                .get_signatures_for_address_with_config(
+                    &pubkey,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: Some(1000),
+                        commitment: None,
+                    },
+                )
+                .await
+                .map_err(|e| anyhow!("Failed to get signatures: {}", e))?;
+
+            if let Some(s) = page.iter().rev().find(|s| s.err.is_none()) {
+                oldest = Some(SignatureInfo {
+                    signature: s.signature.clone(),
+                    slot: s.slot,
+                    block_time: s.block_time,
+                });
+            }
+            let Some(last) = page.last() else {
+                return Ok(oldest);
+            };
+            if page.len() < 1000 {
+                return Ok(oldest);
+            }
+            before = Some(Signature::from_str(&last.signature)
+                .map_err(|e| anyhow!("Invalid signature: {}", e))?);
+        }
+
+        Err(HistoryTruncated(address.to_string()).into())
+    }
+
+    /// Who created a mint and when: the fee payer of its oldest transaction
+    pub async fn get_deployment(&self, mint_address: &str) -> Result<Option<Deployment>> {
+        let Some(first) = self.get_first_signature(mint_address).await? else {
+            return Ok(None);
+        };
+        let transaction = self.get_parsed_transaction(&first.signature).await?;
+        let payer = &transaction["transaction"]["transaction"]["message"]["accountKeys"][0];
+        let Some(creator) = payer["pubkey"].as_str().or_else(|| payer.as_str()) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Deployment {
+            creator: creator.to_string(),
+            tx_hash: first.signature,
+            block_number: first.slot,
+            timestamp: transaction["blockTime"].as_i64().or(first.block_time).unwrap_or_default(),
+            factory: None,
+        }))
+    }
+
+    /// Account that funded `wallet`: the fee payer of its oldest transaction,
+    /// since a new wallet cannot pay for its first one. `None` if the wallet
+    /// paid for it itself.
+    pub async fn get_funder(&self, wallet: &str) -> Result<Option<String>> {
+        let Some(first) = self.get_first_signature(wallet).await? else {
+            return Ok(None);
+        };
+        let transaction = self.get_parsed_transaction(&first.signature).await?;
+        let payer = &transaction["transaction"]["transaction"]["message"]["accountKeys"][0];
+        Ok(payer["pubkey"]
+            .as_str()
+            .or_else(|| payer.as_str())
+            .filter(|payer| *payer != wallet)
+            .map(str::to_string))
+    }
+
+    /// Mints initialized in the latest `limit` transactions paid for by `creator`
+    pub async fn get_created_mints(&self, creator: &str, limit: usize) -> Result<Vec<String>> {
+        let mut mints = Vec::new();
+        for signature in self.get_signatures(creator, None, None, limit).await? {
+            let transaction = self.get_parsed_transaction(&signature.signature).await?;
+            let payer = &transaction["transaction"]["transaction"]["message"]["accountKeys"][0];
+            if payer["pubkey"].as_str().or_else(|| payer.as_str()) != Some(creator) {
+                continue;
+            }
+
+            for instruction in parsed_instructions(&transaction) {
+                let program = instruction["program"].as_str().unwrap_or_default();
+                let kind = instruction["parsed"]["type"].as_str().unwrap_or_default();
+                if matches!(program, "spl-token" | "spl-token-2022")
+                    && matches!(kind, "initializeMint" | "initializeMint2")
+                {
+                    if let Some(mint) = instruction["parsed"]["info"]["mint"].as_str() {
+                        mints.push(mint.to_string());
+                    }
+                }
+            }
+        }
+        Ok(mints)
+    }
+
+    /// Token accounts of `mint_address` under both token programs, with their
+    /// raw balances. Token-2022 accounts with extensions are longer than the
+    /// base layout and tagged with account type 2 right after it.
+    pub async fn get_token_accounts(&self, mint_address: &str) -> Result<Vec<(String, u64)>> {
+        let mint = Pubkey::from_str(mint_address)?;
+        let base_size = RpcFilterType::DataSize(TokenAccount::LEN as u64);
+        let queries = [
+            (spl_token::id(), base_size.clone()),
+            (spl_token_2022::id(), base_size),
+            (spl_token_2022::id(), RpcFilterType::Memcmp(Memcmp::new_raw_bytes(TokenAccount::LEN, vec![2]))),
+        ];
+
+        let mut accounts = Vec::new();
+        for (program, layout) in queries {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    layout,
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    // The amount follows the 32-byte mint and 32-byte owner
+                    data_slice: Some(UiDataSliceConfig { offset: 64, length: 8 }),
+                    commitment: None,
+                    min_context_slot: None,
+                },
+                with_context: None,
+            };
+            let found = self.client
+                .get_program_accounts_with_config(&program, config)
+                .await
+                .map_err(|e| anyhow!("Failed to get token accounts: {}", e))?;
+
+            accounts.extend(found.into_iter().map(|(pubkey, account)| {
+                let amount = account.data.get(..8)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .unwrap_or_default();
+                (pubkey.to_string(), amount)
+            }));
+        }
+        Ok(accounts)
+    }
+
+    /// Number of spl-token and Token-2022 accounts holding a non-zero balance
+    /// of `mint_address`
+    pub async fn get_holder_count(&self, mint_address: &str) -> Result<u32> {
+        Ok(self
+            .get_token_accounts(mint_address)
+            .await?
+            .iter()
+            .filter(|(_, amount)| *amount > 0)
+            .count() as u32)
+    }
+
+    /// Raydium AMM v4 and Orca Whirlpool pools that trade `mint_address`, found
+    /// with `getProgramAccounts` filtered on either mint slot
+    pub async fn get_dex_pools(&self, mint_address: &str) -> Result<Vec<DexPool>> {
+        let mint = Pubkey::from_str(mint_address)?;
+
+        let mut pools = Vec::new();
+        for (program, dex, size, offsets) in POOL_LAYOUTS {
+            let program_id = Pubkey::from_str(program)?;
+            // Only the mints and vaults are needed, so slice the account data down to them
+            let start = offsets.iter().copied().min().unwrap_or_default();
+            let slice_len = offsets.iter().copied().max().unwrap_or_default() + 32 - start;
+            for offset in &offsets[..2] {
+                let config = RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(size),
+                        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(*offset, mint.as_ref())),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        data_slice: Some(UiDataSliceConfig { offset: start, length: slice_len }),
+                        commitment: None,
+                        min_context_slot: None,
+                    },
+                    with_context: None,
+                };
+                let accounts = self.client
+                    .get_program_accounts_with_config(&program_id, config)
+                    .await
+                    .map_err(|e| anyhow!("Failed to get {} pools: {}", dex, e))?;
+
+                for (address, account) in accounts {
+                    let key = |offset: usize| {
+                        let bytes = account.data.get(offset - start..offset - start + 32)?;
+                        Pubkey::try_from(bytes).ok().map(|key| key.to_string())
+                    };
+                    let (Some(mint_a), Some(mint_b), Some(vault_a), Some(vault_b)) =
+                        (key(offsets[0]), key(offsets[1]), key(offsets[2]), key(offsets[3]))
+                    else {
+                        continue;
+                    };
+                    pools.push(DexPool {
+                        address: address.to_string(),
+                        program,
+                        dex,
+                        mint_a,
+                        mint_b,
+                        vault_a,
+                        vault_b,
+                    });
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    /// Tradable reserves of Raydium AMM v4 pools: vault balances less the PnL the
+    /// protocol has yet to take. Pools that cannot be read yield `None`.
+    pub async fn get_raydium_reserves(&self, pools: &[String]) -> Result<Vec<Option<AmmReserves>>> {
+        let pubkeys = pools
+            .iter()
+            .map(|p| Pubkey::from_str(p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let layouts: Vec<Option<RaydiumLayout>> = self
+            .get_accounts(&pubkeys)
+            .await?
+            .into_iter()
+            .map(|account| {
+                let data = account?.data;
+                let word = |offset: usize| data.get(offset..offset + 8)?.try_into().ok().map(u64::from_le_bytes);
+                let key = |offset: usize| Pubkey::try_from(data.get(offset..offset + 32)?).ok();
+                let fee_numerator = word(RAYDIUM_SWAP_FEE_OFFSET)?;
+                let fee_denominator = word(RAYDIUM_SWAP_FEE_OFFSET + 8).filter(|d| *d > 0)?;
+                Some((
+                    fee_numerator as f64 / fee_denominator as f64,
+                    word(RAYDIUM_NEED_TAKE_PNL_OFFSET)?,
+                    word(RAYDIUM_NEED_TAKE_PNL_OFFSET + 8)?,
+                    key(RAYDIUM_VAULTS_OFFSET)?,
+                    key(RAYDIUM_VAULTS_OFFSET + 32)?,
+                ))
+            })
+            .collect();
+
+        let vaults: Vec<Pubkey> = layouts
+            .iter()
+            .flatten()
+            .flat_map(|(_, _, _, coin, pc)| [*coin, *pc])
+            .collect();
+        let mut balances = self
+            .get_accounts(&vaults)
+            .await?
+            .into_iter()
+            .map(|account| {
+                account
+                    .and_then(|account| TokenAccount::unpack(&account.data).ok())
+                    .map(|token_account| token_account.amount)
+            });
+
+        Ok(layouts
+            .into_iter()
+            .map(|layout| {
+                let (fee, coin_pnl, pc_pnl, _, _) = layout?;
+                let (coin, pc) = (balances.next().flatten(), balances.next().flatten());
+                Some(AmmReserves {
+                    reserve_a: coin?.saturating_sub(coin_pnl),
+                    reserve_b: pc?.saturating_sub(pc_pnl),
+                    fee,
+                })
+            })
+            .collect())
+    }
+
+    /// Decode the swap `signature` made against `pool`, seen from `mint_address`.
+    /// `quote_usd` prices the pool's other mint; without it the trade is unpriced.
+    pub async fn get_swap(
+        &self,
+        pool: &DexPool,
+        mint_address: &str,
+        signature: &SignatureInfo,
+        quote_usd: Option<f64>,
+    ) -> Result<Option<Trade>> {
+        let transaction = self.get_parsed_transaction(&signature.signature).await?;
+        let timestamp = transaction["blockTime"].as_i64()
+            .or(signature.block_time)
+            .unwrap_or_default();
+
+        Ok(parse_swap(&transaction, pool, mint_address, &signature.signature, signature.slot, timestamp, quote_usd))
+    }
+
+    /// Look up account owners in batches; missing accounts yield `None`
+    pub async fn get_account_owners(&self, addresses: &[String]) -> Result<Vec<Option<AccountOwner>>> {
+        let pubkeys = addresses
+            .iter()
+            .map(|a| Pubkey::from_str(a))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(self
+            .get_accounts(&pubkeys)
+            .await?
+            .into_iter()
+            .map(|account| {
+                account.map(|account| AccountOwner {
+                    owner: account.owner.to_string(),
+                    executable: account.executable,
+                })
+            })
+            .collect())
+    }
+
+    /// Supply and decimals of many mints, read from the mint accounts in batches.
+    /// Token-2022 mints are decoded past their extensions.
+    pub async fn get_mints(&self, mints: &[String]) -> Result<Vec<Option<MintInfo>>> {
+        let pubkeys = mints
+            .iter()
+            .map(|m| Pubkey::from_str(m))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(self
+            .get_accounts(&pubkeys)
+            .await?
+            .into_iter()
+            .map(|account| {
+                let account = account?;
+                let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).ok()?;
+                Some(MintInfo {
+                    supply: mint.base.supply,
+                    decimals: mint.base.decimals,
+                    program: account.owner,
+                })
+            })
+            .collect())
+    }
+
+    /// Balances for many `(mint, owner)` pairs, read from the owners' associated
+    /// token accounts in batches. Each account is derived under its mint's token
+    /// program; a missing account or mint means a zero balance.
+    pub async fn get_token_balances(&self, pairs: &[(String, String)]) -> Result<Vec<u64>> {
+        let mut mints: Vec<String> = pairs.iter().map(|(mint, _)| mint.clone()).collect();
+        mints.sort();
+        mints.dedup();
+        let programs: HashMap<String, Pubkey> = mints
+            .iter()
+            .cloned()
+            .zip(self.get_mints(&mints).await?)
+            .filter_map(|(mint, info)| Some((mint, info?.program)))
+            .collect();
+
+        let accounts = pairs
+            .iter()
+            .map(|(mint, owner)| {
+                let program = programs.get(mint).copied().unwrap_or_else(spl_token::id);
+                Ok(get_associated_token_address_with_program_id(
+                    &Pubkey::from_str(owner)?,
+                    &Pubkey::from_str(mint)?,
+                    &program,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(self
+            .get_accounts(&accounts)
+            .await?
+            .into_iter()
+            .map(|account| {
+                account
+                    .and_then(|account| {
+                        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).ok()
+                            .map(|token_account| token_account.base.amount)
+                    })
+                    .unwrap_or(0)
+            })
+            .collect())
+    }
+
+    /// `getMultipleAccounts` over any number of keys; missing accounts yield `None`
+    async fn get_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let mut accounts = Vec::with_capacity(pubkeys.len());
+        for chunk in pubkeys.chunks(MULTIPLE_ACCOUNTS_LIMIT) {
+            accounts.extend(
+                self.client
+                    .get_multiple_accounts(chunk)
+                    .await
+                    .map_err(|e| anyhow!("Failed to get accounts: {}", e))?,
+            );
+        }
+        Ok(accounts)
+    }
+
+    /// Fetch a transaction in `jsonParsed` encoding as a raw JSON value
+    pub async fn get_parsed_transaction(&self, signature: &str) -> Result<Value> {
+        let signature = Signature::from_str(signature)
+            .map_err(|e| anyhow!("Invalid signature: {}", e))?;
+        let transaction = self.client
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::JsonParsed),
+                    commitment: None,
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to get transaction: {}", e))?;
+
+        Ok(serde_json::to_value(transaction)?)
+    }
+
+    /// Decode SPL token movements of `mint_address` within one transaction.
+    /// `mintTo`/`burn` instructions are classified as mints and burns, with the
+    /// all-zero pubkey as counterparty to match how ERC-20 tokens surface them.
+    pub async fn get_token_transfers(
+        &self,
+        mint_address: &str,
+        signature: &SignatureInfo,
+    ) -> Result<Vec<Transfer>> {
+        let transaction = self.get_parsed_transaction(&signature.signature).await?;
+        let timestamp = transaction["blockTime"].as_i64()
+            .or(signature.block_time)
+            .unwrap_or_default();
+
+        Ok(parse_token_transfers(&transaction, mint_address, &signature.signature, signature.slot, timestamp))
+    }
+}
+
+/// A Raydium or Orca pool account and the two mints it trades
+#[derive(Debug, Clone)]
+pub struct DexPool {
+    pub address: String,
+    pub program: &'static str,
+    pub dex: &'static str,
+    pub mint_a: String,
+    pub mint_b: String,
+    /// Token accounts holding the pool's mint A and mint B reserves
+    pub vault_a: String,
+    pub vault_b: String,
+}
+
+impl DexPool {
+    /// The mint paired with `mint`
+    pub fn other_mint(&self, mint: &str) -> &str {
+        if self.mint_a == mint {
+            &self.mint_b
+        } else {
+            &self.mint_a
+        }
+    }
+
+    /// The vault holding `mint`
+    pub fn vault(&self, mint: &str) -> &str {
+        if self.mint_a == mint {
+            &self.vault_a
+        } else {
+            &self.vault_b
+        }
+    }
+}
+
+/// Reserves of a constant-product pool in raw units of its mint A and mint B
+#[derive(Debug, Clone, Copy)]
+pub struct AmmReserves {
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    /// Swap fee as a fraction of the input amount
+    pub fee: f64,
+}
+
+/// Owner program and executable flag of an account
+#[derive(Debug, Clone)]
+pub struct AccountOwner {
+    pub owner: String,
+    pub executable: bool,
+}
+
+/// A wallet's total balance of one SPL mint across its token accounts
+#[derive(Debug, Clone)]
+pub struct TokenHolding {
+    pub mint: String,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// Supply and decimals of an SPL token mint
+#[derive(Debug, Clone, Copy)]
+pub struct MintInfo {
+    pub supply: u64,
+    pub decimals: u8,
+    /// Token program owning the mint, spl-token or Token-2022
+    pub program: Pubkey,
+}
+
+/// `getMultipleAccounts` accepts at most 100 keys per request
+const MULTIPLE_ACCOUNTS_LIMIT: usize = 100;
+
+/// All-zero pubkey used as the counterparty of mints and burns
+pub fn zero_address() -> String {
+    Pubkey::default().to_string()
+}
+
+/// Instructions of a `jsonParsed` transaction, outer instructions first followed by inner ones
+pub(crate) fn parsed_instructions(transaction: &Value) -> Vec<&Value> {
+    let mut instructions: Vec<&Value> = transaction["transaction"]["transaction"]["message"]["instructions"]
+        .as_array()
+        .map(|ixs| ixs.iter().collect())
+        .unwrap_or_default();
+
+    if let Some(inner) = transaction["transaction"]["meta"]["innerInstructions"].as_array() {
+        for group in inner {
+            if let Some(ixs) = group["instructions"].as_array() {
+                instructions.extend(ixs.iter());
             }
         }
-        
-        Ok(total_balance)
     }
 
-    pub async fn get_token_supply(&self, mint_address: &str) -> Result<u64> {
-        let mint_pubkey = Pubkey::from_str(mint_address)?;
-        let token_supply = self.client
-            .get_token_supply(&mint_pubkey)
-            .map_err(|e| anyhow!("Failed to get token supply: {}", e))?;
-            
-        // Convert the string amount to u64
-        token_supply.amount.parse::<u64>()
-            .map_err(|e| anyhow!("Failed to parse token supply: {}", e))
+    instructions
+}
+
+/// Map token account addresses to `(mint, owner)` using the pre/post token balances
+pub(crate) fn token_account_owners(transaction: &Value) -> HashMap<String, (String, String)> {
+    let account_keys = &transaction["transaction"]["transaction"]["message"]["accountKeys"];
+    let meta = &transaction["transaction"]["meta"];
+
+    let mut owners = HashMap::new();
+    for balances in [&meta["preTokenBalances"], &meta["postTokenBalances"]] {
+        for balance in balances.as_array().into_iter().flatten() {
+            let Some(index) = balance["accountIndex"].as_u64() else {
+                continue;
+            };
+            let key = &account_keys[index as usize];
+            let account = key["pubkey"].as_str().or_else(|| key.as_str());
+            if let (Some(account), Some(mint), Some(owner)) =
+                (account, balance["mint"].as_str(), balance["owner"].as_str())
+            {
+                owners.insert(account.to_string(), (mint.to_string(), owner.to_string()));
+            }
+        }
+    }
+
+    owners
+}
+
+fn parse_token_transfers(
+    transaction: &Value,
+    mint_address: &str,
+    signature: &str,
+    slot: u64,
+    timestamp: i64,
+) -> Vec<Transfer> {
+    let owners = token_account_owners(transaction);
+    let owner_of = |account: &str| -> Option<String> {
+        owners.get(account)
+            .filter(|(mint, _)| mint == mint_address)
+            .map(|(_, owner)| owner.clone())
+    };
+
+    let mut transfers = Vec::new();
+    for (index, instruction) in parsed_instructions(transaction).into_iter().enumerate() {
+        let program = instruction["program"].as_str().unwrap_or_default();
+        if program != "spl-token" && program != "spl-token-2022" {
+            continue;
+        }
+
+        let info = &instruction["parsed"]["info"];
+        let amount = info["amount"].as_str()
+            .or_else(|| info["tokenAmount"]["amount"].as_str())
+            .unwrap_or("0")
+            .to_string();
+        let instruction_mint = info["mint"].as_str();
+
+        let (from, to, kind) = match instruction["parsed"]["type"].as_str().unwrap_or_default() {
+            "transfer" | "transferChecked" => {
+                if instruction_mint.is_some_and(|mint| mint != mint_address) {
+                    continue;
+                }
+                let source = info["source"].as_str().unwrap_or_default();
+                let destination = info["destination"].as_str().unwrap_or_default();
+                // Plain `transfer` does not name the mint, so rely on the token balances
+                let (Some(from), Some(to)) = (owner_of(source), owner_of(destination)) else {
+                    continue;
+                };
+                (from, to, TransferKind::Transfer)
+            }
+            "mintTo" | "mintToChecked" if instruction_mint == Some(mint_address) => {
+                let account = info["account"].as_str().unwrap_or_default();
+                let to = owner_of(account).unwrap_or_else(|| account.to_string());
+                (zero_address(), to, TransferKind::Mint)
+            }
+            "burn" | "burnChecked" if instruction_mint == Some(mint_address) => {
+                let account = info["account"].as_str().unwrap_or_default();
+                let from = owner_of(account).unwrap_or_else(|| account.to_string());
+                (from, zero_address(), TransferKind::Burn)
+            }
+            _ => continue,
+        };
+
+        transfers.push(Transfer {
+            tx_hash: signature.to_string(),
+            from,
+            to,
+            amount,
+            timestamp,
+            block_number: slot,
+            log_index: index as u64,
+            kind,
+            chain: Chain::Solana,
+        });
+    }
+
+    transfers
+}
+
+/// Net change of one token account's balance across a transaction, with the
+/// mint's decimals
+fn account_token_delta(transaction: &Value, account: &str) -> (i128, Option<u8>) {
+    let keys = &transaction["transaction"]["transaction"]["message"]["accountKeys"];
+    let meta = &transaction["transaction"]["meta"];
+    let mut delta = 0i128;
+    let mut decimals = None;
+    for (balances, sign) in [(&meta["preTokenBalances"], -1), (&meta["postTokenBalances"], 1)] {
+        for balance in balances.as_array().into_iter().flatten() {
+            let key = &keys[balance["accountIndex"].as_u64().unwrap_or(u64::MAX) as usize];
+            if key["pubkey"].as_str().or_else(|| key.as_str()) != Some(account) {
+                continue;
+            }
+            let amount = &balance["uiTokenAmount"];
+            delta += sign * amount["amount"].as_str().and_then(|a| a.parse::<i128>().ok()).unwrap_or(0);
+            decimals = amount["decimals"].as_u64().map(|d| d as u8).or(decimals);
+        }
+    }
+    (delta, decimals)
+}
+
+/// A swap is read from the pool's own vault balances in a successful
+/// transaction that invokes the pool's program with the pool account, so
+/// other legs of a routed or bundled transaction are left out. The token
+/// vault and quote vault must move in opposite directions, which rules out
+/// liquidity adds and removes. The fee payer is taken as the trader.
+fn parse_swap(
+    transaction: &Value,
+    pool: &DexPool,
+    mint_address: &str,
+    signature: &str,
+    slot: u64,
+    timestamp: i64,
+    quote_usd: Option<f64>,
+) -> Option<Trade> {
+    let meta = &transaction["transaction"]["meta"];
+    if !meta["err"].is_null() {
+        return None;
     }
 
-    pub async fn get_token_info(&self, mint_address: &str) -> Result<(String, String, u8)> {
-        // In a real implementation, you would fetch the token metadata
-        // For now, return placeholder data
-        Ok(("Token".to_string(), "TKN".to_string(), 9))
+    let invokes_pool = parsed_instructions(transaction).into_iter().any(|ix| {
+        ix["programId"].as_str() == Some(pool.program)
+            && ix["accounts"]
+                .as_array()
+                .is_some_and(|accounts| accounts.iter().any(|a| a.as_str() == Some(pool.address.as_str())))
+    });
+    if !invokes_pool {
+        return None;
     }
-}
\ No newline at end of file
+
+    let payer = &transaction["transaction"]["transaction"]["message"]["accountKeys"][0];
+    let trader = payer["pubkey"].as_str().or_else(|| payer.as_str())?;
+
+    // The pool pays out the token on a buy and takes it in on a sell
+    let quote = pool.other_mint(mint_address);
+    let (token_vault, decimals) = account_token_delta(transaction, pool.vault(mint_address));
+    let (quote_vault, quote_decimals) = account_token_delta(transaction, pool.vault(quote));
+    let (token_delta, quote_delta) = (-token_vault, -quote_vault);
+
+    let side = match (token_delta.signum(), quote_delta.signum()) {
+        (1, -1) => TradeSide::Buy,
+        (-1, 1) => TradeSide::Sell,
+        _ => return None,
+    };
+    let (token_amount, quote_amount) = (token_delta.unsigned_abs(), quote_delta.unsigned_abs());
+
+    let units = token_amount as f64 / 10f64.powi(decimals? as i32);
+    let price_usd = quote_usd
+        .zip(quote_decimals)
+        .map(|(quote_usd, quote_decimals)| quote_amount as f64 / 10f64.powi(quote_decimals as i32) * quote_usd / units);
+    let (token_in, amount_in, token_out, amount_out) = match side {
+        TradeSide::Buy => (quote, quote_amount, mint_address, token_amount),
+        TradeSide::Sell => (mint_address, token_amount, quote, quote_amount),
+    };
+
+    Some(Trade {
+        tx_hash: signature.to_string(),
+        block_number: slot,
+        log_index: 0,
+        timestamp,
+        pool: pool.address.clone(),
+        dex: pool.dex.to_string(),
+        side,
+        trader: trader.to_string(),
+        token_in: token_in.to_string(),
+        amount_in: amount_in.to_string(),
+        token_out: token_out.to_string(),
+        amount_out: amount_out.to_string(),
+        price_usd,
+        volume_usd: price_usd.map(|price| price * units),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn borsh_string(value: &str, width: usize) -> Vec<u8> {
+        let mut bytes = (width as u32).to_le_bytes().to_vec();
+        bytes.extend(value.as_bytes());
+        bytes.resize(4 + width, 0);
+        bytes
+    }
+
+    #[test]
+    fn metadata_strips_padding() {
+        let mut data = vec![4];
+        data.extend([0; 64]);
+        data.extend(borsh_string("Bonk", 32));
+        data.extend(borsh_string("BONK", 10));
+        data.extend(borsh_string("https://example.com", 200));
+
+        assert_eq!(parse_metadata(&data), Some(("Bonk".to_string(), "BONK".to_string())));
+        assert_eq!(parse_metadata(&data[..80]), None);
+    }
+}
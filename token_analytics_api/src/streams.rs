@@ -0,0 +1,772 @@
+//! Live event streams backing GraphQL subscriptions.
+//!
+//! Ethereum feeds use `eth_subscribe` (logs / newHeads) over `ETH_WS_URL`,
+//! Solana feeds use `programSubscribe` and `logsSubscribe` over `SOL_WS_URL`.
+//! Any node speaking the standard JSON-RPC pubsub protocol works, including
+//! a local stub. Subscribers to the same token share one upstream feed.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use futures::{Stream, StreamExt};
+use serde_json::Value;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+use web3::{
+    transports::WebSocket,
+    types::{Address, BlockId, BlockNumber, FilterBuilder, H256, U256, U64},
+    Web3,
+};
+
+use crate::analytics::normalize_address;
+use crate::config::Config;
+use crate::models::{Chain, LiquidityPool, PriceUpdate, Transfer};
+use crate::pricing::token_price_usd;
+use crate::rpc::ethereum::{
+    decode_transfer_log, topic_to_address, EthereumClient, PAIR_CREATED_TOPIC, TRANSFER_TOPIC, UNISWAP_V2_FACTORY,
+};
+use crate::rpc::solana::{SignatureInfo, SolanaClient, RAYDIUM_AMM_V4};
+
+fn eth_ws_url(config: &Config) -> Result<String> {
+    config.eth_ws_url.clone().ok_or_else(|| anyhow!("ETH_WS_URL must be set for Ethereum subscriptions"))
+}
+
+fn sol_ws_url(config: &Config) -> Result<String> {
+    config.sol_ws_url.clone().ok_or_else(|| anyhow!("SOL_WS_URL must be set for Solana subscriptions"))
+}
+
+/// Events buffered per shared feed for subscribers that fall behind
+const FEED_CAPACITY: usize = 256;
+/// How often an idle feed checks whether it still has subscribers
+const FEED_IDLE_CHECK: Duration = Duration::from_secs(30);
+/// Recently decoded Solana signatures remembered to skip repeats
+const SEEN_SIGNATURES: usize = 1024;
+
+type FeedSender<T> = broadcast::Sender<Result<T, Arc<anyhow::Error>>>;
+type Feeds<T> = Arc<Mutex<HashMap<(Chain, String), FeedSender<T>>>>;
+
+/// Upstream feeds shared by every subscriber to the same token. The first
+/// subscriber starts a feed and later ones attach to its broadcast channel;
+/// the feed stops once its last subscriber is gone. Cheap to clone.
+#[derive(Clone, Default)]
+pub struct FeedHub {
+    transfers: Feeds<Transfer>,
+    prices: Feeds<PriceUpdate>,
+}
+
+impl FeedHub {
+    /// New transfers of a token as they are included on chain
+    pub fn transfers(&self, config: Config, chain: Chain, address: String) -> impl Stream<Item = Result<Transfer>> {
+        let key = (chain, normalize_address(chain, &address));
+        attach(&self.transfers, key, move || transfers(config, chain, address))
+    }
+
+    /// Spot price of a token, re-quoted on every new block and emitted when it changes
+    pub fn price_updates(&self, config: Config, chain: Chain, address: String) -> impl Stream<Item = Result<PriceUpdate>> {
+        let key = (chain, normalize_address(chain, &address));
+        attach(&self.prices, key, move || price_updates(config, chain, address))
+    }
+}
+
+/// Subscribe to the feed under `key`, starting it with `start` if none is running
+fn attach<T, S>(feeds: &Feeds<T>, key: (Chain, String), start: impl FnOnce() -> S) -> impl Stream<Item = Result<T>>
+where
+    T: Clone + Send + 'static,
+    S: Stream<Item = Result<T>> + Send + 'static,
+{
+    let mut running = feeds.lock().unwrap();
+    let mut receiver = match running.get(&key) {
+        Some(sender) if sender.receiver_count() > 0 => sender.subscribe(),
+        _ => {
+            let (sender, receiver) = broadcast::channel(FEED_CAPACITY);
+            running.insert(key.clone(), sender.clone());
+            tokio::spawn(run_feed(feeds.clone(), key, sender, start()));
+            receiver
+        }
+    };
+    drop(running);
+
+    async_stream::stream! {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => yield event.map_err(|e| anyhow!("{}", e)),
+                Err(RecvError::Lagged(skipped)) => warn!("Subscriber fell behind and skipped {} events", skipped),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+/// Forward `upstream` to the feed's subscribers until it ends or none are left
+async fn run_feed<T, S>(feeds: Feeds<T>, key: (Chain, String), sender: FeedSender<T>, upstream: S)
+where
+    T: Clone + Send + 'static,
+    S: Stream<Item = Result<T>> + Send + 'static,
+{
+    let mut upstream = Box::pin(upstream);
+    let mut idle_check = tokio::time::interval(FEED_IDLE_CHECK);
+    loop {
+        tokio::select! {
+            event = upstream.next() => {
+                let Some(event) = event else { break };
+                if sender.send(event.map_err(Arc::new)).is_err() {
+                    break;
+                }
+            }
+            _ = idle_check.tick() => {
+                if sender.receiver_count() == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    // A newer feed may have replaced this one under the same key
+    let mut running = feeds.lock().unwrap();
+    if running.get(&key).is_some_and(|current| current.same_channel(&sender)) {
+        running.remove(&key);
+    }
+}
+
+fn topic(hex: &str) -> Result<H256> {
+    H256::from_str(hex).map_err(|e| anyhow!("Invalid topic: {}", e))
+}
+
+/// Signatures and program logs of successful transactions mentioning `address`
+fn solana_signatures(ws_url: String, address: String) -> impl Stream<Item = Result<(SignatureInfo, Vec<String>)>> {
+    async_stream::stream! {
+        let client = match PubsubClient::new(&ws_url).await {
+            Ok(client) => client,
+            Err(e) => {
+                yield Err(anyhow!("Failed to connect to {}: {}", ws_url, e));
+                return;
+            }
+        };
+        let subscription = client
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![address.clone()]),
+                RpcTransactionLogsConfig { commitment: None },
+            )
+            .await;
+        let (mut notifications, _unsubscribe) = match subscription {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                yield Err(anyhow!("Failed to subscribe to logs: {}", e));
+                return;
+            }
+        };
+
+        while let Some(notification) = notifications.next().await {
+            if notification.value.err.is_some() {
+                continue;
+            }
+            let signature = SignatureInfo {
+                signature: notification.value.signature,
+                slot: notification.context.slot,
+                block_time: None,
+            };
+            yield Ok((signature, notification.value.logs));
+        }
+    }
+}
+
+/// Token accounts of `mint` as they change, with the slot of the change.
+/// Every transfer changes the two accounts it moves tokens between, even a
+/// plain `transfer` instruction that does not name the mint.
+fn solana_token_accounts(ws_url: String, mint: String) -> impl Stream<Item = Result<(String, u64)>> {
+    async_stream::stream! {
+        let mint_key = match Pubkey::from_str(&mint) {
+            Ok(mint) => mint,
+            Err(e) => {
+                yield Err(anyhow!("Invalid mint: {}", e));
+                return;
+            }
+        };
+        let client = match PubsubClient::new(&ws_url).await {
+            Ok(client) => client,
+            Err(e) => {
+                yield Err(anyhow!("Failed to connect to {}: {}", ws_url, e));
+                return;
+            }
+        };
+        // Token accounts start with their mint; Token-2022 accounts may be
+        // longer than the base layout, so only classic ones filter on size
+        let config = |size: Option<usize>| RpcProgramAccountsConfig {
+            filters: Some(
+                size.map(|size| RpcFilterType::DataSize(size as u64))
+                    .into_iter()
+                    .chain([RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint_key.as_ref()))])
+                    .collect(),
+            ),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: None,
+                commitment: None,
+                min_context_slot: None,
+            },
+            with_context: Some(true),
+        };
+        let classic = client.program_subscribe(&spl_token::id(), Some(config(Some(TokenAccount::LEN)))).await;
+        let extended = client.program_subscribe(&spl_token_2022::id(), Some(config(None))).await;
+        let (classic, extended) = match (classic, extended) {
+            (Ok((classic, _)), Ok((extended, _))) => (classic, extended),
+            (Err(e), _) | (_, Err(e)) => {
+                yield Err(anyhow!("Failed to subscribe to token accounts: {}", e));
+                return;
+            }
+        };
+
+        let mut updates = futures::stream::select(classic, extended);
+        while let Some(update) = updates.next().await {
+            let is_account = update
+                .value
+                .account
+                .data
+                .decode()
+                .is_some_and(|data| StateWithExtensions::<TokenAccount>::unpack(&data).is_ok());
+            if is_account {
+                yield Ok((update.value.pubkey, update.context.slot));
+            }
+        }
+    }
+}
+
+/// SPL transfers of `mint` found from its token accounts' changes: each
+/// change is matched to the account's signatures in that slot, and each
+/// transaction is decoded once however many of its accounts changed
+fn solana_transfers(config: Config, ws_url: String, mint: String) -> impl Stream<Item = Result<Transfer>> {
+    async_stream::stream! {
+        let client = SolanaClient::new(&config.sol_rpc_url);
+        let mut seen: (HashSet<String>, VecDeque<String>) = Default::default();
+        let mut accounts = Box::pin(solana_token_accounts(ws_url, mint.clone()));
+        while let Some(update) = accounts.next().await {
+            let (account, slot) = match update {
+                Ok(update) => update,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+            let signatures = match client.get_signatures(&account, None, None, 10).await {
+                Ok(signatures) => signatures,
+                Err(e) => {
+                    warn!("Failed to get signatures of {}: {}", account, e);
+                    continue;
+                }
+            };
+            for signature in signatures.into_iter().filter(|s| s.slot == slot) {
+                if !seen.0.insert(signature.signature.clone()) {
+                    continue;
+                }
+                seen.1.push_back(signature.signature.clone());
+                if seen.1.len() > SEEN_SIGNATURES {
+                    if let Some(oldest) = seen.1.pop_front() {
+                        seen.0.remove(&oldest);
+                    }
+                }
+
+                match client.get_token_transfers(&mint, &signature).await {
+                    Ok(transfers) => {
+                        for transfer in transfers {
+                            yield Ok(transfer);
+                        }
+                    }
+                    Err(e) => warn!("Failed to decode transaction {}: {}", signature.signature, e),
+                }
+            }
+        }
+    }
+}
+
+/// ERC-20 transfers of `address` from an `eth_subscribe` log feed,
+/// timestamped with their block's time. Logs retracted by a reorg
+/// (`removed: true`) are skipped rather than emitted as new transfers.
+fn eth_transfers(ws_url: String, address: String) -> impl Stream<Item = Result<Transfer>> {
+    async_stream::stream! {
+        let token = match address.parse::<Address>() {
+            Ok(token) => token,
+            Err(e) => {
+                yield Err(anyhow!("Invalid address: {}", e));
+                return;
+            }
+        };
+        let web3 = match WebSocket::new(&ws_url).await {
+            Ok(transport) => Web3::new(transport),
+            Err(e) => {
+                yield Err(anyhow!("Failed to connect to {}: {}", ws_url, e));
+                return;
+            }
+        };
+        let filter = match topic(TRANSFER_TOPIC) {
+            Ok(topic) => FilterBuilder::default()
+                .address(vec![token])
+                .topics(Some(vec![topic]), None, None, None)
+                .build(),
+            Err(e) => {
+                yield Err(e);
+                return;
+            }
+        };
+        let mut logs = match web3.eth_subscribe().subscribe_logs(filter).await {
+            Ok(logs) => logs,
+            Err(e) => {
+                yield Err(anyhow!("Failed to subscribe to logs: {}", e));
+                return;
+            }
+        };
+
+        // Transfers in the same block share one timestamp lookup
+        let mut block_time: Option<(u64, i64)> = None;
+        while let Some(log) = logs.next().await {
+            let log = match log {
+                Ok(log) => log,
+                Err(e) => {
+                    yield Err(anyhow!("Log subscription failed: {}", e));
+                    return;
+                }
+            };
+            if log.is_removed() {
+                continue;
+            }
+            let Some(block_number) = log.block_number.map(|n| n.as_u64()) else {
+                continue;
+            };
+
+            let timestamp = match block_time {
+                Some((number, timestamp)) if number == block_number => timestamp,
+                _ => {
+                    let block = web3
+                        .eth()
+                        .block(BlockId::Number(BlockNumber::Number(U64::from(block_number))))
+                        .await;
+                    match block {
+                        Ok(Some(block)) => {
+                            let timestamp = block.timestamp.as_u64() as i64;
+                            block_time = Some((block_number, timestamp));
+                            timestamp
+                        }
+                        Ok(None) => {
+                            yield Err(anyhow!("Block {} not found", block_number));
+                            return;
+                        }
+                        Err(e) => {
+                            yield Err(anyhow!("Failed to get block {}: {}", block_number, e));
+                            return;
+                        }
+                    }
+                }
+            };
+            if let Some(transfer) = decode_transfer_log(&log, block_number, timestamp) {
+                yield Ok(transfer);
+            }
+        }
+    }
+}
+
+/// New transfers of a token as they are included on chain, from a feed of its own
+fn transfers(config: Config, chain: Chain, address: String) -> impl Stream<Item = Result<Transfer>> {
+    async_stream::stream! {
+        match chain {
+            Chain::Ethereum => {
+                let ws_url = match eth_ws_url(&config) {
+                    Ok(url) => url,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                let mut transfers = Box::pin(eth_transfers(ws_url, address));
+                while let Some(transfer) = transfers.next().await {
+                    yield transfer;
+                }
+            }
+            Chain::Solana => {
+                let ws_url = match sol_ws_url(&config) {
+                    Ok(url) => url,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                let mut transfers = Box::pin(solana_transfers(config, ws_url, address));
+                while let Some(transfer) = transfers.next().await {
+                    yield transfer;
+                }
+            }
+            Chain::Bsc | Chain::Other => yield Err(anyhow!("Transfer subscriptions are not supported on {}", chain)),
+        }
+    }
+}
+
+/// Spot price of a token, re-quoted on every new block and emitted when it
+/// changes, from a feed of its own
+fn price_updates(config: Config, chain: Chain, address: String) -> impl Stream<Item = Result<PriceUpdate>> {
+    async_stream::stream! {
+        if chain != Chain::Ethereum {
+            yield Err(anyhow!("Price updates are only available on Ethereum"));
+            return;
+        }
+
+        let ws_url = match eth_ws_url(&config) {
+            Ok(url) => url,
+            Err(e) => {
+                yield Err(e);
+                return;
+            }
+        };
+        let decimals = match EthereumClient::new(&config.eth_rpc_url).await {
+            Ok(client) => match client.get_token_info(&address).await {
+                Ok((_, _, decimals)) => decimals,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            },
+            Err(e) => {
+                yield Err(e);
+                return;
+            }
+        };
+        let web3 = match WebSocket::new(&ws_url).await {
+            Ok(transport) => Web3::new(transport),
+            Err(e) => {
+                yield Err(anyhow!("Failed to connect to {}: {}", ws_url, e));
+                return;
+            }
+        };
+        let mut heads = match web3.eth_subscribe().subscribe_new_heads().await {
+            Ok(heads) => heads,
+            Err(e) => {
+                yield Err(anyhow!("Failed to subscribe to new heads: {}", e));
+                return;
+            }
+        };
+
+        let mut last_price = None;
+        while let Some(head) = heads.next().await {
+            let head = match head {
+                Ok(head) => head,
+                Err(e) => {
+                    yield Err(anyhow!("Head subscription failed: {}", e));
+                    return;
+                }
+            };
+
+            match token_price_usd(&config, chain, &address, decimals).await {
+                Ok(Some(price)) if last_price != Some(price) => {
+                    last_price = Some(price);
+                    yield Ok(PriceUpdate {
+                        address: address.clone(),
+                        chain,
+                        price_usd: price,
+                        block_number: head.number.map(|n| n.as_u64()).unwrap_or_default(),
+                        timestamp: head.timestamp.as_u64() as i64,
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to price {}: {}", address, e),
+            }
+        }
+    }
+}
+
+/// Raydium `initialize2` accounts: 4 = AMM id, 8 = coin mint, 9 = pc mint
+fn raydium_pool(transaction: &Value) -> Option<LiquidityPool> {
+    crate::rpc::solana::parsed_instructions(transaction)
+        .into_iter()
+        .filter(|ix| ix["programId"].as_str() == Some(RAYDIUM_AMM_V4))
+        .find_map(|ix| {
+            let accounts = ix["accounts"].as_array()?;
+            let account = |i: usize| accounts.get(i).and_then(|a| a.as_str()).map(str::to_string);
+            Some(LiquidityPool {
+                address: account(4)?,
+                chain: Chain::Solana,
+                exchange: "raydium".to_string(),
+                token0: account(8)?,
+                token1: account(9)?,
+                reserve0: "0".to_string(),
+                reserve1: "0".to_string(),
+                reserve_usd: 0.0,
+                volume_24h: 0.0,
+                lock: Default::default(),
+            })
+        })
+}
+
+/// Pools created on the chain's main AMM (Uniswap V2 / Raydium AMM v4)
+pub fn new_pools(config: Config, chain: Chain) -> impl Stream<Item = Result<LiquidityPool>> {
+    async_stream::stream! {
+        match chain {
+            Chain::Ethereum => {
+                let ws_url = match eth_ws_url(&config) {
+                    Ok(url) => url,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                let filter = match (UNISWAP_V2_FACTORY.parse::<Address>(), topic(PAIR_CREATED_TOPIC)) {
+                    (Ok(factory), Ok(topic)) => FilterBuilder::default()
+                        .address(vec![factory])
+                        .topics(Some(vec![topic]), None, None, None)
+                        .build(),
+                    _ => {
+                        yield Err(anyhow!("Invalid factory filter"));
+                        return;
+                    }
+                };
+                let web3 = match WebSocket::new(&ws_url).await {
+                    Ok(transport) => Web3::new(transport),
+                    Err(e) => {
+                        yield Err(anyhow!("Failed to connect to {}: {}", ws_url, e));
+                        return;
+                    }
+                };
+                let mut logs = match web3.eth_subscribe().subscribe_logs(filter).await {
+                    Ok(logs) => logs,
+                    Err(e) => {
+                        yield Err(anyhow!("Failed to subscribe to logs: {}", e));
+                        return;
+                    }
+                };
+
+                while let Some(log) = logs.next().await {
+                    let log = match log {
+                        Ok(log) => log,
+                        Err(e) => {
+                            yield Err(anyhow!("Log subscription failed: {}", e));
+                            return;
+                        }
+                    };
+                    // PairCreated(token0 indexed, token1 indexed, pair, allPairsLength)
+                    if log.topics.len() != 3 || log.data.0.len() < 32 {
+                        continue;
+                    }
+                    let pair = Address::from_slice(&log.data.0[12..32]);
+                    yield Ok(LiquidityPool {
+                        address: format!("{:?}", pair),
+                        chain: Chain::Ethereum,
+                        exchange: "uniswap_v2".to_string(),
+                        token0: format!("{:?}", topic_to_address(&log.topics[1])),
+                        token1: format!("{:?}", topic_to_address(&log.topics[2])),
+                        reserve0: U256::zero().to_string(),
+                        reserve1: U256::zero().to_string(),
+                        reserve_usd: 0.0,
+                        volume_24h: 0.0,
+                        lock: Default::default(),
+                    });
+                }
+            }
+            Chain::Solana => {
+                let ws_url = match sol_ws_url(&config) {
+                    Ok(url) => url,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                let client = SolanaClient::new(&config.sol_rpc_url);
+                let mut signatures = Box::pin(solana_signatures(ws_url, RAYDIUM_AMM_V4.to_string()));
+                while let Some(signature) = signatures.next().await {
+                    let (signature, logs) = match signature {
+                        Ok(signature) => signature,
+                        Err(e) => {
+                            yield Err(e);
+                            return;
+                        }
+                    };
+                    // Most Raydium transactions are swaps; only fetch pool initializations
+                    if !logs.iter().any(|log| log.contains("initialize2")) {
+                        continue;
+                    }
+                    match client.get_parsed_transaction(&signature.signature).await {
+                        Ok(transaction) => {
+                            if let Some(pool) = raydium_pool(&transaction) {
+                                yield Ok(pool);
+                            }
+                        }
+                        Err(e) => warn!("Failed to fetch transaction {}: {}", signature.signature, e),
+                    }
+                }
+            }
+            Chain::Bsc | Chain::Other => yield Err(anyhow!("Pool subscriptions are not supported on {}", chain)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::SinkExt;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::*;
+
+    const TOKEN: &str = "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984";
+    const GENESIS_TIME: u64 = 1_700_000_000;
+
+    fn hex_word(value: u64) -> String {
+        format!("0x{:064x}", value)
+    }
+
+    /// A 1-token transfer log in `block`; `removed` marks a reorged-out log
+    fn transfer_log(block: u64, log_index: u64, removed: bool) -> Value {
+        json!({
+            "address": TOKEN,
+            "topics": [TRANSFER_TOPIC, hex_word(0xa), hex_word(0xb)],
+            "data": hex_word(1_000_000_000_000_000_000),
+            "blockHash": hex_word(block),
+            "blockNumber": format!("{:#x}", block),
+            "transactionHash": hex_word(block * 100 + log_index),
+            "transactionIndex": "0x0",
+            "logIndex": format!("{:#x}", log_index),
+            "removed": removed,
+        })
+    }
+
+    /// Minimal block whose timestamp is `GENESIS_TIME + number`
+    fn block(number: u64) -> Value {
+        json!({
+            "hash": hex_word(number),
+            "parentHash": hex_word(number - 1),
+            "sha3Uncles": hex_word(0),
+            "miner": format!("0x{:040x}", 0),
+            "stateRoot": hex_word(0),
+            "transactionsRoot": hex_word(0),
+            "receiptsRoot": hex_word(0),
+            "number": format!("{:#x}", number),
+            "gasUsed": "0x0",
+            "gasLimit": "0x0",
+            "extraData": "0x",
+            "logsBloom": null,
+            "timestamp": format!("{:#x}", GENESIS_TIME + number),
+            "difficulty": "0x0",
+            "totalDifficulty": null,
+            "uncles": [],
+            "transactions": [],
+            "size": null,
+            "mixHash": null,
+            "nonce": null,
+        })
+    }
+
+    /// JSON-RPC pubsub stub: confirms `eth_subscribe`, pushes `logs` as
+    /// notifications and answers `eth_getBlockByNumber`
+    async fn ws_stub(logs: Vec<Value>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while let Some(Ok(message)) = ws.next().await {
+                let Message::Text(text) = message else { continue };
+                let request: Value = serde_json::from_str(&text).unwrap();
+                let id = request["id"].clone();
+                let reply = |result: Value| Message::Text(json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string());
+
+                match request["method"].as_str() {
+                    Some("eth_subscribe") => {
+                        ws.send(reply(json!("0x1"))).await.unwrap();
+                        // The client registers the subscription after reading the reply
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        for log in &logs {
+                            let notification = json!({
+                                "jsonrpc": "2.0",
+                                "method": "eth_subscription",
+                                "params": { "subscription": "0x1", "result": log },
+                            });
+                            ws.send(Message::Text(notification.to_string())).await.unwrap();
+                        }
+                    }
+                    Some("eth_getBlockByNumber") => {
+                        let number = request["params"][0].as_str().unwrap().trim_start_matches("0x");
+                        ws.send(reply(block(u64::from_str_radix(number, 16).unwrap()))).await.unwrap();
+                    }
+                    method => panic!("Unexpected request {:?}", method),
+                }
+            }
+        });
+
+        url
+    }
+
+    #[tokio::test]
+    async fn eth_transfers_skip_removed_logs_and_use_block_time() {
+        let url = ws_stub(vec![
+            transfer_log(100, 0, false),
+            transfer_log(100, 1, true),
+            transfer_log(101, 0, false),
+        ])
+        .await;
+
+        let transfers: Vec<Transfer> = tokio::time::timeout(
+            Duration::from_secs(10),
+            eth_transfers(url, TOKEN.to_string()).take(2).map(Result::unwrap).collect(),
+        )
+        .await
+        .expect("stream stalled");
+
+        let seen: Vec<(u64, u64, i64)> = transfers
+            .iter()
+            .map(|t| (t.block_number, t.log_index, t.timestamp))
+            .collect();
+        assert_eq!(
+            seen,
+            [(100, 0, GENESIS_TIME as i64 + 100), (101, 0, GENESIS_TIME as i64 + 101)]
+        );
+        assert_eq!(transfers[0].amount, "1000000000000000000");
+    }
+
+    #[tokio::test]
+    async fn subscribers_to_one_token_share_a_feed() {
+        let feeds: Feeds<u64> = Default::default();
+        let (upstream, mut events) = tokio::sync::mpsc::unbounded_channel::<u64>();
+        let start = move || {
+            async_stream::stream! {
+                while let Some(event) = events.recv().await {
+                    yield Ok(event);
+                }
+            }
+        };
+        let started_twice = || -> futures::stream::Empty<Result<u64>> { panic!("feed started twice") };
+
+        let key = (Chain::Ethereum, TOKEN.to_string());
+        let first = attach(&feeds, key.clone(), start);
+        let second = attach(&feeds, key.clone(), started_twice);
+
+        upstream.send(7).unwrap();
+        upstream.send(8).unwrap();
+        let (first, second): (Vec<u64>, Vec<u64>) = tokio::time::timeout(Duration::from_secs(10), async {
+            let first = first.take(2).map(Result::unwrap).collect().await;
+            let second = second.take(2).map(Result::unwrap).collect().await;
+            (first, second)
+        })
+        .await
+        .expect("stream stalled");
+        assert_eq!(first, [7, 8]);
+        assert_eq!(second, [7, 8]);
+
+        // The feed ends with its upstream and leaves no entry behind
+        drop(upstream);
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while feeds.lock().unwrap().contains_key(&key) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("feed not removed");
+    }
+}
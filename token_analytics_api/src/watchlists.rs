@@ -0,0 +1,99 @@
+//! Named token watchlists persisted in Redis
+
+use anyhow::{anyhow, Result};
+use async_graphql::SimpleObject;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{hash_delete, hash_get, hash_update, hash_values, RedisPool};
+use crate::models::Chain;
+
+const WATCHLISTS_KEY: &str = "watchlists";
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct WatchedToken {
+    pub address: String,
+    pub chain: Chain,
+    pub added_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Watchlist {
+    pub name: String,
+    pub tokens: Vec<WatchedToken>,
+    pub created_at: i64,
+}
+
+/// Read, change and write back one watchlist atomically
+async fn update(
+    redis: &RedisPool,
+    name: &str,
+    mut change: impl FnMut(Option<Watchlist>) -> Result<Watchlist>,
+) -> Result<Watchlist> {
+    let saved = hash_update(redis, WATCHLISTS_KEY, name, |current| {
+        let current = current.map(|w| serde_json::from_str(&w)).transpose()?;
+        Ok(serde_json::to_string(&change(current)?)?)
+    })
+    .await?;
+    Ok(serde_json::from_str(&saved)?)
+}
+
+pub async fn get(redis: &RedisPool, name: &str) -> Result<Option<Watchlist>> {
+    match hash_get(redis, WATCHLISTS_KEY, name).await? {
+        Some(watchlist) => Ok(Some(serde_json::from_str(&watchlist)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn list(redis: &RedisPool) -> Result<Vec<Watchlist>> {
+    let mut watchlists = hash_values(redis, WATCHLISTS_KEY)
+        .await?
+        .iter()
+        .map(|w| serde_json::from_str::<Watchlist>(w))
+        .collect::<Result<Vec<_>, _>>()?;
+    watchlists.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(watchlists)
+}
+
+pub async fn create(redis: &RedisPool, name: String) -> Result<Watchlist> {
+    if name.trim().is_empty() {
+        return Err(anyhow!("Watchlist name must not be empty"));
+    }
+
+    update(redis, &name, |current| match current {
+        Some(_) => Err(anyhow!("Watchlist {} already exists", name)),
+        None => Ok(Watchlist {
+            name: name.clone(),
+            tokens: Vec::new(),
+            created_at: chrono::Utc::now().timestamp(),
+        }),
+    })
+    .await
+}
+
+pub async fn delete(redis: &RedisPool, name: &str) -> Result<bool> {
+    hash_delete(redis, WATCHLISTS_KEY, name).await
+}
+
+pub async fn add_token(redis: &RedisPool, name: &str, address: String, chain: Chain) -> Result<Watchlist> {
+    update(redis, name, |current| {
+        let mut watchlist = current.ok_or_else(|| anyhow!("Watchlist {} not found", name))?;
+        if !watchlist.tokens.iter().any(|t| t.address == address && t.chain == chain) {
+            watchlist.tokens.push(WatchedToken {
+                address: address.clone(),
+                chain,
+                added_at: chrono::Utc::now().timestamp(),
+            });
+        }
+        Ok(watchlist)
+    })
+    .await
+}
+
+pub async fn remove_token(redis: &RedisPool, name: &str, address: &str, chain: Chain) -> Result<Watchlist> {
+    update(redis, name, |current| {
+        let mut watchlist = current.ok_or_else(|| anyhow!("Watchlist {} not found", name))?;
+        watchlist.tokens.retain(|t| !(t.address == address && t.chain == chain));
+        Ok(watchlist)
+    })
+    .await
+}